@@ -0,0 +1,182 @@
+use axum::{
+    extract::Request,
+    http::{Method, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use log::info;
+
+use crate::{
+    api::error::ApiError,
+    config::{ApiRole, get_config},
+};
+
+/// Who made a request, available to handlers as a request extension
+#[derive(Clone, Debug)]
+pub struct Caller {
+    /// Name of the token used, "anonymous" when authentication is disabled
+    pub name: String,
+    pub role: ApiRole,
+}
+
+/// Checks the bearer token against `api.tokens` and audits every mutating call
+pub async fn authenticate(mut request: Request, next: Next) -> Response {
+    let tokens = get_config().api.tokens;
+    let caller = if tokens.is_empty() {
+        Caller {
+            name: "anonymous".to_string(),
+            role: ApiRole::Admin,
+        }
+    } else {
+        let presented = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim);
+        let Some(token) = presented.and_then(|presented| {
+            tokens
+                .iter()
+                .find(|token| constant_time_eq(token.token.as_bytes(), presented.as_bytes()))
+        }) else {
+            return reject(StatusCode::UNAUTHORIZED, "Missing or invalid bearer token");
+        };
+        Caller {
+            name: token.name.clone(),
+            role: token.role,
+        }
+    };
+
+    let method = request.method().clone();
+    let read_only = matches!(method, Method::GET | Method::HEAD | Method::OPTIONS);
+    if !read_only && caller.role != ApiRole::Admin {
+        info!(
+            target: "audit",
+            token_name = caller.name.as_str(), method = method.as_str(), path = request.uri().path();
+            "Audit: denied {} {} for read-only token '{}'", method, request.uri().path(), caller.name
+        );
+        return reject(StatusCode::FORBIDDEN, "This token is read-only");
+    }
+
+    let path = request.uri().path().to_string();
+    request.extensions_mut().insert(caller.clone());
+    let response = next.run(request).await;
+    if !read_only {
+        info!(
+            target: "audit",
+            token_name = caller.name.as_str(), method = method.as_str(), path = path.as_str(),
+            status = response.status().as_u16();
+            "Audit: {} {} by '{}' -> {}", method, path, caller.name, response.status()
+        );
+    }
+    response
+}
+
+fn reject(status: StatusCode, message: &str) -> Response {
+    let kind = if status == StatusCode::UNAUTHORIZED {
+        "unauthorized"
+    } else {
+        "forbidden"
+    };
+    let mut response = ApiError::new(status, kind, message).into_response();
+    if status == StatusCode::UNAUTHORIZED {
+        response.headers_mut().insert(
+            header::WWW_AUTHENTICATE,
+            header::HeaderValue::from_static("Bearer"),
+        );
+    }
+    response
+}
+
+/// Compares without returning early, so response times don't leak how much of a token matched
+fn constant_time_eq(expected: &[u8], presented: &[u8]) -> bool {
+    let mut difference = expected.len() ^ presented.len();
+    for (index, byte) in expected.iter().enumerate() {
+        difference |= (byte ^ presented.get(index).copied().unwrap_or(0)) as usize;
+    }
+    difference == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Extension, Router, body::Body, middleware::from_fn, routing::get};
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::config::{ApiToken, config_turn, set_config};
+
+    async fn whoami(Extension(caller): Extension<Caller>) -> impl IntoResponse {
+        [("x-caller", caller.name)]
+    }
+
+    fn router() -> Router {
+        Router::new()
+            .route("/jobs", get(whoami).post(whoami).put(whoami).delete(whoami))
+            .route_layer(from_fn(authenticate))
+    }
+
+    fn set_tokens(tokens: &[(&str, ApiRole)]) {
+        let mut config = get_config();
+        config.api.tokens = tokens
+            .iter()
+            .map(|(name, role)| ApiToken {
+                name: name.to_string(),
+                token: format!("{}-secret", name),
+                role: *role,
+            })
+            .collect();
+        set_config(config);
+    }
+
+    /// Blocks on the request, as the tests hold the configuration lock while calling
+    fn call(method: Method, token: Option<&str>) -> Response {
+        let mut request = Request::builder().method(method).uri("/jobs");
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime
+            .block_on(router().oneshot(request.body(Body::empty()).unwrap()))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_rejects_missing_and_unknown_tokens() {
+        let _turn = config_turn();
+        set_tokens(&[("ci", ApiRole::Admin)]);
+        let missing = call(Method::GET, None);
+        assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(missing.headers()[header::WWW_AUTHENTICATE], "Bearer");
+        let unknown = call(Method::GET, Some("ci-secreT"));
+        assert_eq!(unknown.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_read_tokens_only_pass_read_only_methods() {
+        let _turn = config_turn();
+        set_tokens(&[("dashboard", ApiRole::Read), ("ci", ApiRole::Admin)]);
+        let read = call(Method::GET, Some("dashboard-secret"));
+        assert_eq!(read.status(), StatusCode::OK);
+        assert_eq!(read.headers()["x-caller"], "dashboard");
+        for method in [Method::POST, Method::PUT, Method::DELETE] {
+            let denied = call(method.clone(), Some("dashboard-secret"));
+            assert_eq!(denied.status(), StatusCode::FORBIDDEN, "{}", method);
+            let admin = call(method.clone(), Some("ci-secret"));
+            assert_eq!(admin.status(), StatusCode::OK, "{}", method);
+            assert_eq!(admin.headers()["x-caller"], "ci");
+        }
+    }
+
+    #[test]
+    fn test_without_tokens_everyone_is_an_anonymous_admin() {
+        let _turn = config_turn();
+        set_tokens(&[]);
+        for method in [Method::GET, Method::POST, Method::DELETE] {
+            let response = call(method.clone(), None);
+            assert_eq!(response.status(), StatusCode::OK, "{}", method);
+            assert_eq!(response.headers()["x-caller"], "anonymous");
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! A small HTTP client for the daemon's own API, so local commands can act through a running
+//! daemon instead of changing its files behind its back.
+
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+use rustls::pki_types::ServerName;
+use serde_json::Value;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader, Lines},
+    net::TcpStream,
+};
+use tokio_rustls::TlsConnector;
+
+use crate::{
+    api::tls::load_client_config,
+    config::{ApiRole, ListenAddress, get_config},
+    error::AutoPilotError,
+};
+
+/// Environment variable holding the token to send, preferred over `api.tokens`
+const TOKEN_VAR: &str = "AUTOPILOT_TOKEN";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A TCP, TLS or Unix socket stream to the daemon
+pub trait Connection: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Connection for T {}
+
+/// Body of a response, positioned right after the headers
+pub type ResponseBody = BufReader<Box<dyn Connection>>;
+
+#[derive(Clone)]
+pub struct ApiClient {
+    address: ListenAddress,
+    token: Option<String>,
+    tls: Option<TlsConnector>,
+}
+
+impl ApiClient {
+    /// Client for the first address in `api.listen`, or `None` when the API is disabled. The token
+    /// comes from `AUTOPILOT_TOKEN`, else the first admin token of `api.tokens`.
+    pub fn from_config() -> Result<Option<Self>, AutoPilotError> {
+        let Some(address) = get_config().api.listen.first().cloned() else {
+            return Ok(None);
+        };
+        Self::for_address(address).map(Some)
+    }
+
+    /// Client for one address of `api.listen`, with the same token and TLS settings as
+    /// `from_config`
+    pub fn for_address(address: ListenAddress) -> Result<Self, AutoPilotError> {
+        let config = get_config().api;
+        let token = std::env::var(TOKEN_VAR).ok().or_else(|| {
+            config
+                .tokens
+                .iter()
+                .find(|token| token.role == ApiRole::Admin)
+                .or(config.tokens.first())
+                .map(|token| token.token.clone())
+        });
+        // Unix sockets are never wrapped in TLS by the server
+        let tls = match (&config.tls, &address) {
+            (Some(tls), ListenAddress::Tcp(_)) => {
+                Some(TlsConnector::from(load_client_config(tls)?))
+            }
+            _ => None,
+        };
+        Ok(Self {
+            address,
+            token,
+            tls,
+        })
+    }
+
+    /// Whether a daemon answers on the configured address
+    pub async fn is_reachable(&self) -> bool {
+        self.request("GET", "/health", None).await.is_ok()
+    }
+
+    /// Sends a request and returns its JSON body, or the API's error message for non-2xx answers
+    pub async fn request(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<Value, AutoPilotError> {
+        let (status, mut reader) = self.send(method, path, body).await?;
+        let body = read_body(&mut reader).await?;
+        if (200..300).contains(&status) {
+            Ok(serde_json::from_slice(&body).unwrap_or(Value::Null))
+        } else {
+            Err(error_from(status, &body))
+        }
+    }
+
+    /// Sends a request whose answer is streamed, e.g. `/events` or `/jobs/{id}/run`, and
+    /// returns its lines as they arrive
+    pub async fn lines(
+        &self,
+        method: &str,
+        path: &str,
+    ) -> Result<Lines<ResponseBody>, AutoPilotError> {
+        let (status, mut reader) = self.send(method, path, None).await?;
+        if !(200..300).contains(&status) {
+            let body = read_body(&mut reader).await?;
+            return Err(error_from(status, &body));
+        }
+        Ok(reader.lines())
+    }
+
+    /// HTTP/1.0 keeps this simple: one request per connection and no chunked bodies
+    async fn send(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<(u16, ResponseBody), AutoPilotError> {
+        let mut connection = self.connect().await?;
+        let mut request = format!("{} {} HTTP/1.0\r\nHost: autopilot\r\n", method, path);
+        if let Some(token) = &self.token {
+            request.push_str(&format!("Authorization: Bearer {}\r\n", token));
+        }
+        let body = body.map(Value::to_string).unwrap_or_default();
+        if !body.is_empty() {
+            request.push_str(&format!(
+                "Content-Type: application/json\r\nContent-Length: {}\r\n",
+                body.len()
+            ));
+        }
+        request.push_str("\r\n");
+        request.push_str(&body);
+        connection.write_all(request.as_bytes()).await?;
+        connection.flush().await?;
+
+        let mut reader = BufReader::new(connection);
+        let mut status_line = String::new();
+        reader.read_line(&mut status_line).await?;
+        let status = status_line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|status| status.parse::<u16>().ok())
+            .ok_or_else(|| {
+                AutoPilotError::Api(format!(
+                    "Unexpected answer from {}: {}",
+                    self.address,
+                    status_line.trim()
+                ))
+            })?;
+        let mut header = String::new();
+        loop {
+            header.clear();
+            if reader.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+                break;
+            }
+        }
+        Ok((status, reader))
+    }
+
+    async fn connect(&self) -> Result<Box<dyn Connection>, AutoPilotError> {
+        let unreachable = |e: std::io::Error| {
+            AutoPilotError::Api(format!("{} is unreachable: {}", self.address, e))
+        };
+        match &self.address {
+            ListenAddress::Tcp(address) => {
+                let address = connectable(*address);
+                let stream = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(address))
+                    .await
+                    .map_err(|_| AutoPilotError::Api(format!("{} timed out", self.address)))?
+                    .map_err(unreachable)?;
+                let Some(tls) = &self.tls else {
+                    return Ok(Box::new(stream));
+                };
+                // Certificates for a local daemon are usually issued to `localhost`
+                let server_name = if address.ip().is_loopback() {
+                    ServerName::try_from("localhost").expect("localhost is a valid name")
+                } else {
+                    ServerName::IpAddress(address.ip().into())
+                };
+                let stream = tls
+                    .connect(server_name, stream)
+                    .await
+                    .map_err(|e| AutoPilotError::Api(format!("TLS handshake failed: {}", e)))?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(unix)]
+            ListenAddress::Unix(path) => {
+                let stream = tokio::net::UnixStream::connect(path)
+                    .await
+                    .map_err(unreachable)?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(not(unix))]
+            ListenAddress::Unix(_) => Err(AutoPilotError::Api(
+                "Unix sockets are not supported on this platform".to_string(),
+            )),
+        }
+    }
+}
+
+/// A daemon listening on every interface is reached over loopback
+fn connectable(address: SocketAddr) -> SocketAddr {
+    match address.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => {
+            SocketAddr::from((Ipv4Addr::LOCALHOST, address.port()))
+        }
+        IpAddr::V6(ip) if ip.is_unspecified() => {
+            SocketAddr::from((Ipv6Addr::LOCALHOST, address.port()))
+        }
+        _ => address,
+    }
+}
+
+async fn read_body(reader: &mut ResponseBody) -> Result<Vec<u8>, AutoPilotError> {
+    let mut body = Vec::new();
+    match reader.read_to_end(&mut body).await {
+        // TLS peers may hang up without a close_notify once the body is sent
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(body),
+        result => result.map(|_| body).map_err(AutoPilotError::from),
+    }
+}
+
+fn error_from(status: u16, body: &[u8]) -> AutoPilotError {
+    let message = serde_json::from_slice::<Value>(body)
+        .ok()
+        .and_then(|body| body["error"]["message"].as_str().map(str::to_string))
+        .unwrap_or_else(|| format!("HTTP {}", status));
+    AutoPilotError::Api(message)
+}
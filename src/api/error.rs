@@ -0,0 +1,278 @@
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{FromRequest, FromRequestParts, Query, Request},
+    http::{Method, StatusCode, Uri, header, request::Parts},
+    response::{IntoResponse, Response},
+};
+use log::error;
+use serde::{Serialize, de::DeserializeOwned};
+use utoipa::ToSchema;
+
+use crate::error::AutoPilotError;
+
+/// Error returned by the API routes, sent as an [`ErrorResponse`]
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub kind: &'static str,
+    pub message: String,
+    pub details: Vec<ErrorDetail>,
+}
+
+/// `{"error": {"kind": ..., "message": ..., "details": [...]}}`
+#[derive(Serialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: ErrorBody,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ErrorBody {
+    /// Machine readable error category, e.g. `invalid_body` or `not_found`
+    pub kind: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<ErrorDetail>,
+}
+
+/// A problem with one part of the request
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorDetail {
+    /// JSON path of the offending value, e.g. `conditions[1].condition.threshold`
+    pub path: String,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, kind: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            kind,
+            message: message.into(),
+            details: vec![],
+        }
+    }
+
+    pub fn with_detail(mut self, path: impl Into<String>, message: impl Into<String>) -> Self {
+        self.details.push(ErrorDetail {
+            path: path.into(),
+            message: message.into(),
+        });
+        self
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "not_found", message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, "conflict", message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "internal", message)
+    }
+}
+
+impl From<AutoPilotError> for ApiError {
+    fn from(error: AutoPilotError) -> Self {
+        let (status, kind) = match &error {
+            AutoPilotError::JobNotFound(_) => (StatusCode::NOT_FOUND, "not_found"),
+            AutoPilotError::JobExists(_) => (StatusCode::CONFLICT, "conflict"),
+            AutoPilotError::JobModified(_) => {
+                (StatusCode::PRECONDITION_FAILED, "precondition_failed")
+            }
+            AutoPilotError::InvalidJob(_)
+            | AutoPilotError::InvalidJobField { .. }
+            | AutoPilotError::Condition(_)
+            | AutoPilotError::Json(_)
+            | AutoPilotError::Time(_)
+            | AutoPilotError::Cron(_) => (StatusCode::UNPROCESSABLE_ENTITY, "invalid_job"),
+            AutoPilotError::Config(_) => (StatusCode::INTERNAL_SERVER_ERROR, "config"),
+            AutoPilotError::Io(source) | AutoPilotError::Job { source, .. } => io_status(source),
+            AutoPilotError::State(_) | AutoPilotError::DirectoryInit(_) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "storage")
+            }
+            AutoPilotError::Command(_) | AutoPilotError::JobExecution(_) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "execution")
+            }
+            AutoPilotError::Dialoguer(_)
+            | AutoPilotError::Signal(_)
+            | AutoPilotError::Unknown(_)
+            | AutoPilotError::Autopilot(_)
+            | AutoPilotError::Service(_)
+            | AutoPilotError::Api(_) => (StatusCode::INTERNAL_SERVER_ERROR, "internal"),
+        };
+        let api_error = Self::new(status, kind, error.to_string());
+        match error {
+            AutoPilotError::InvalidJobField { path, message } => {
+                api_error.with_detail(path, message)
+            }
+            _ => api_error,
+        }
+    }
+}
+
+/// Job files that vanish or appear between lookup and use are the client racing itself, anything
+/// else the file system reports is a failure on our side
+fn io_status(error: &std::io::Error) -> (StatusCode, &'static str) {
+    match error.kind() {
+        std::io::ErrorKind::NotFound => (StatusCode::NOT_FOUND, "not_found"),
+        std::io::ErrorKind::AlreadyExists => (StatusCode::CONFLICT, "conflict"),
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, "storage"),
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        // Client errors are the client's business, failures on our side belong in our log
+        if self.status.is_server_error() {
+            error!("API request failed: {}", self.message);
+        }
+        let body = ErrorResponse {
+            error: ErrorBody {
+                kind: self.kind.to_string(),
+                message: self.message,
+                details: self.details,
+            },
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+/// JSON request body that reports where it failed to deserialize instead of a bare status
+pub struct ApiJson<T>(pub T);
+
+impl<T: DeserializeOwned, S: Send + Sync> FromRequest<S> for ApiJson<T> {
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let is_json = request
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| {
+                let essence = value.split(';').next().unwrap_or_default().trim();
+                essence == "application/json" || essence.ends_with("+json")
+            });
+        if !is_json {
+            return Err(ApiError::new(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "unsupported_media_type",
+                "Expected a request body with Content-Type: application/json",
+            ));
+        }
+        let bytes = Bytes::from_request(request, state)
+            .await
+            .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, "invalid_body", e.body_text()))?;
+        parse_json(&bytes).map(ApiJson)
+    }
+}
+
+/// Query string that reports which parameter is invalid instead of a bare status
+pub struct ApiQuery<T>(pub T);
+
+impl<T: DeserializeOwned, S: Send + Sync> FromRequestParts<S> for ApiQuery<T> {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Query::try_from_uri(&parts.uri)
+            .map(|Query(value)| ApiQuery(value))
+            .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, "invalid_query", e.body_text()))
+    }
+}
+
+/// Answers requests that match no route
+pub async fn route_not_found(method: Method, uri: Uri) -> ApiError {
+    ApiError::not_found(format!("No route for {} {}", method, uri.path()))
+}
+
+pub async fn method_not_allowed(method: Method, uri: Uri) -> ApiError {
+    ApiError::new(
+        StatusCode::METHOD_NOT_ALLOWED,
+        "method_not_allowed",
+        format!("{} is not allowed on {}", method, uri.path()),
+    )
+}
+
+/// Deserializes `bytes`, pointing at the offending field when the JSON doesn't match `T`
+pub fn parse_json<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ApiError> {
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+    serde_path_to_error::deserialize(&mut deserializer).map_err(|e| {
+        let path = e.path().to_string();
+        let inner = e.into_inner();
+        if inner.is_syntax() || inner.is_eof() {
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "malformed_json",
+                format!("Request body is not valid JSON: {}", inner),
+            )
+        } else {
+            ApiError::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "invalid_body",
+                format!("Request body does not match the expected schema: {}", inner),
+            )
+            .with_detail(path, inner.to_string())
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job::JobScheme;
+
+    #[test]
+    fn test_invalid_condition_reports_its_path() {
+        let body = r#"{
+            "id": "j1",
+            "conditions": [
+                { "type": "power", "condition": { "check_charging": true } },
+                { "type": "power", "condition": { "threshold": "high" } }
+            ],
+            "tasks": []
+        }"#;
+        let error = parse_json::<JobScheme>(body.as_bytes()).unwrap_err();
+        assert_eq!(error.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(error.details[0].path, "conditions[1].condition.threshold");
+
+        let error = parse_json::<JobScheme>(b"{").unwrap_err();
+        assert_eq!(error.kind, "malformed_json");
+    }
+
+    #[test]
+    fn test_autopilot_errors_map_to_statuses() {
+        let error = ApiError::from(AutoPilotError::JobNotFound("j1".to_string()));
+        assert_eq!(
+            (error.status, error.kind),
+            (StatusCode::NOT_FOUND, "not_found")
+        );
+        let error = ApiError::from(AutoPilotError::Condition("bad".to_string()));
+        assert_eq!(error.status, StatusCode::UNPROCESSABLE_ENTITY);
+        let error = ApiError::from(AutoPilotError::InvalidJobField {
+            path: "tasks[0].command".to_string(),
+            message: "invalid type".to_string(),
+        });
+        assert_eq!(error.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(error.details[0].path, "tasks[0].command");
+        let error = ApiError::from(AutoPilotError::JobExists("j1".to_string()));
+        assert_eq!(
+            (error.status, error.kind),
+            (StatusCode::CONFLICT, "conflict")
+        );
+        let error = ApiError::from(AutoPilotError::Job {
+            message: "Failed to read job file".to_string(),
+            source: std::io::ErrorKind::NotFound.into(),
+        });
+        assert_eq!(error.status, StatusCode::NOT_FOUND);
+        let error = ApiError::from(AutoPilotError::Job {
+            message: "Failed to write job file".to_string(),
+            source: std::io::ErrorKind::StorageFull.into(),
+        });
+        assert_eq!(
+            (error.status, error.kind),
+            (StatusCode::INTERNAL_SERVER_ERROR, "storage")
+        );
+    }
+}
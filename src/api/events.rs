@@ -0,0 +1,164 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::ws::{Message, WebSocket, WebSocketUpgrade, rejection::WebSocketUpgradeRejection},
+    http::StatusCode,
+    response::{
+        Response,
+        sse::{Event as SseEvent, KeepAlive, Sse},
+    },
+};
+use futures::Stream;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::broadcast::{Receiver, error::RecvError};
+use utoipa::IntoParams;
+
+use crate::{
+    api::error::{ApiError, ApiQuery, ErrorResponse},
+    autopilot::events::{Event, EventFilter, EventKind, subscribe},
+};
+
+/// `?job=a,b&type=run_started,run_finished`, both optional
+#[derive(Deserialize, IntoParams)]
+pub struct EventsQuery {
+    /// Comma separated job ids
+    job: Option<String>,
+    /// Comma separated event types
+    #[serde(rename = "type")]
+    types: Option<String>,
+}
+
+impl EventsQuery {
+    fn into_filter(self) -> Result<EventFilter, ApiError> {
+        let split = |value: Option<String>| -> Vec<String> {
+            value
+                .unwrap_or_default()
+                .split(',')
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect()
+        };
+        let types = split(self.types);
+        if let Some(unknown) = types
+            .iter()
+            .find(|name| !EventKind::NAMES.contains(&name.as_str()))
+        {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "invalid_query",
+                format!("Unknown event type '{}'", unknown),
+            )
+            .with_detail(
+                "type",
+                format!("expected one of {}", EventKind::NAMES.join(", ")),
+            ));
+        }
+        Ok(EventFilter {
+            job_ids: split(self.job),
+            types,
+        })
+    }
+}
+
+/// A matching event, or how many events a slow subscriber missed
+enum Next {
+    Event(Event),
+    Lagged(u64),
+}
+
+async fn next(receiver: &mut Receiver<Event>, filter: &EventFilter) -> Option<Next> {
+    loop {
+        match receiver.recv().await {
+            Ok(event) if filter.matches(&event) => return Some(Next::Event(event)),
+            Ok(_) => continue,
+            Err(RecvError::Lagged(skipped)) => return Some(Next::Lagged(skipped)),
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
+
+fn lagged(skipped: u64) -> serde_json::Value {
+    json!({ "type": "lagged", "skipped": skipped })
+}
+
+/// `GET /events`, streams job lifecycle events as server-sent events named after their type
+#[utoipa::path(
+    get,
+    path = "/events",
+    tag = "events",
+    params(EventsQuery),
+    responses(
+        (status = 200, description = "Server-sent events", content_type = "text/event-stream", body = Event),
+        (status = 400, description = "Unknown event type", body = ErrorResponse)
+    )
+)]
+pub async fn events_sse(
+    ApiQuery(query): ApiQuery<EventsQuery>,
+) -> Result<Sse<impl Stream<Item = Result<SseEvent, Infallible>>>, ApiError> {
+    let filter = query.into_filter()?;
+    let stream =
+        futures::stream::unfold((subscribe(), filter), |(mut receiver, filter)| async move {
+            let event = match next(&mut receiver, &filter).await? {
+                Next::Event(event) => SseEvent::default()
+                    .event(event.kind.name())
+                    .json_data(&event),
+                Next::Lagged(skipped) => SseEvent::default()
+                    .event("lagged")
+                    .json_data(lagged(skipped)),
+            }
+            .unwrap_or_default();
+            Some((Ok(event), (receiver, filter)))
+        });
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+/// `GET /events/ws`, the same events as JSON text messages, enabled by `api.websocket`
+#[utoipa::path(
+    get,
+    path = "/events/ws",
+    tag = "events",
+    params(EventsQuery),
+    responses(
+        (status = 101, description = "Switches to a WebSocket carrying one JSON event per message"),
+        (status = 400, description = "Unknown event type", body = ErrorResponse)
+    )
+)]
+pub async fn events_ws(
+    ApiQuery(query): ApiQuery<EventsQuery>,
+    upgrade: Result<WebSocketUpgrade, WebSocketUpgradeRejection>,
+) -> Result<Response, ApiError> {
+    let upgrade = upgrade.map_err(|rejection| {
+        ApiError::new(
+            rejection.status(),
+            "websocket_upgrade",
+            rejection.body_text(),
+        )
+    })?;
+    let filter = query.into_filter()?;
+    // Subscribe before upgrading so nothing is missed during the handshake
+    let receiver = subscribe();
+    Ok(upgrade.on_upgrade(move |socket| forward(socket, receiver, filter)))
+}
+
+async fn forward(mut socket: WebSocket, mut receiver: Receiver<Event>, filter: EventFilter) {
+    loop {
+        tokio::select! {
+            next = next(&mut receiver, &filter) => {
+                let message = match next {
+                    Some(Next::Event(event)) => serde_json::to_string(&event).unwrap_or_default(),
+                    Some(Next::Lagged(skipped)) => lagged(skipped).to_string(),
+                    None => break,
+                };
+                if socket.send(Message::Text(message.into())).await.is_err() {
+                    break;
+                }
+            }
+            // Incoming messages are ignored, the socket is only watched for the client going away
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
@@ -1,36 +1,109 @@
-use std::{collections::HashMap, sync::atomic::Ordering};
+use std::sync::atomic::Ordering;
 
 use axum::{
     Json, Router,
+    body::Body,
     extract::{Path, State},
-    http::StatusCode,
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
     routing::{get, post},
 };
-use log::{error, info};
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
-use crate::{api::state::AppState, job::get::get_jobs};
-use crate::{job::JobScheme, status::JobStatusEnum};
 use crate::{
-    job::set::{add_job, remove_job},
-    status::get::get_status_log,
+    api::{
+        error::{ApiError, ApiJson, ApiQuery, ErrorResponse, parse_json},
+        listing::{Expand, JobsQuery},
+        state::AppState,
+    },
+    autopilot::metrics::render as render_metrics,
+    conditions::ConditionScheme,
+    job::{
+        JobScheme, RunEvent, RunMode,
+        bundle::{Bundle, ImportAction, Imported, OnConflict, export_jobs, import_jobs},
+        get::{
+            JobEntry, LoadError, get_job_by_id, get_job_path, get_job_scheme, job_entries,
+            load_job_schemes,
+        },
+        run_job_with,
+    },
+};
+use crate::{
+    error::AutoPilotError,
+    job::set::{add_job_scheme, job_etag, new_job_id, remove_job, set_job_enabled, update_job},
+    status::LastRun,
+    task::TaskScheme,
+    time::When,
+    utilities::jsonc_parser::jsonc_parse,
 };
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct JobsStatus {
     running: bool,
     job_count: usize,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct StartConfig {
     verbose: Option<bool>,
 }
 
-pub async fn health() -> Json<serde_json::Value> {
-    Json(serde_json::json!({ "status": "ok", "service": "autopilot-api" }))
+#[derive(Serialize, ToSchema)]
+pub struct HealthResponse {
+    status: String,
+    service: String,
+}
+
+/// Outcome of a call that changes something
+#[derive(Serialize, ToSchema)]
+pub struct MessageResponse {
+    success: bool,
+    message: String,
+    /// File of the job that was written
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+}
+
+impl MessageResponse {
+    fn new(message: impl Into<String>) -> Json<Self> {
+        Json(Self {
+            success: true,
+            message: message.into(),
+            path: None,
+        })
+    }
+
+    fn with_path(message: impl Into<String>, path: &std::path::Path) -> Json<Self> {
+        Json(Self {
+            success: true,
+            message: message.into(),
+            path: Some(path.to_string_lossy().to_string()),
+        })
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "daemon",
+    security(()),
+    responses((status = 200, body = HealthResponse))
+)]
+pub async fn health() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok".to_string(),
+        service: "autopilot-api".to_string(),
+    })
 }
 
+#[utoipa::path(
+    get,
+    path = "/status",
+    tag = "daemon",
+    responses((status = 200, body = JobsStatus))
+)]
 pub async fn jobs_status(State(state): State<AppState>) -> Json<JobsStatus> {
     let ap = state.auto_pilot.read().await;
     Json(JobsStatus {
@@ -39,238 +112,714 @@ pub async fn jobs_status(State(state): State<AppState>) -> Json<JobsStatus> {
     })
 }
 
+#[utoipa::path(
+    post,
+    path = "/start",
+    tag = "daemon",
+    request_body = Option<StartConfig>,
+    responses(
+        (status = 200, body = MessageResponse),
+        (status = 409, description = "Jobs are already running", body = ErrorResponse),
+        (status = 422, description = "Invalid request body", body = ErrorResponse)
+    )
+)]
 pub async fn jobs_start(
     State(state): State<AppState>,
-    Json(payload): Json<Option<StartConfig>>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+    ApiJson(payload): ApiJson<Option<StartConfig>>,
+) -> Result<Json<MessageResponse>, ApiError> {
     // Prevent double-start
     if state.started.swap(true, Ordering::Relaxed) {
-        return Err(StatusCode::CONFLICT);
+        return Err(ApiError::conflict("Jobs are already running"));
     }
 
     let mut ap = state.auto_pilot.write().await;
     ap.load_jobs();
-    ap.run_jobs();
+    // Kept so single jobs can be stopped when they are reloaded
+    ap.jobs_handles = ap.run_jobs();
 
     info!("Jobs started via API");
-    Ok(Json(
-        serde_json::json!({ "success": true, "message": "Jobs started" }),
-    ))
+    Ok(MessageResponse::new("Jobs started"))
 }
 
-pub async fn jobs_stop(
-    State(state): State<AppState>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+#[utoipa::path(
+    post,
+    path = "/stop",
+    tag = "daemon",
+    responses(
+        (status = 200, body = MessageResponse),
+        (status = 409, description = "Jobs are not running", body = ErrorResponse),
+        (status = 500, description = "The scheduler failed to shut down", body = ErrorResponse)
+    )
+)]
+pub async fn jobs_stop(State(state): State<AppState>) -> Result<Json<MessageResponse>, ApiError> {
     if !state.started.swap(false, Ordering::Relaxed) {
-        return Err(StatusCode::CONFLICT); // Already stopped
+        return Err(ApiError::conflict("Jobs are not running"));
     }
 
     let mut ap = state.auto_pilot.write().await;
     ap.stop_jobs()
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|e| ApiError::internal(format!("Failed to stop jobs: {}", e)))?;
 
     info!("Jobs stopped via API");
-    Ok(Json(
-        serde_json::json!({ "success": true, "message": "Jobs stopped" }),
-    ))
+    Ok(MessageResponse::new("Jobs stopped"))
 }
 
-pub async fn jobs_reload(
-    State(state): State<AppState>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+#[utoipa::path(
+    post,
+    path = "/reload",
+    tag = "daemon",
+    responses((status = 200, body = MessageResponse))
+)]
+pub async fn jobs_reload(State(state): State<AppState>) -> Result<Json<MessageResponse>, ApiError> {
     let mut ap = state.auto_pilot.write().await;
     ap.reload().await;
     // reload_config already calls run_jobs(), so ensure flag is set
     state.started.store(true, Ordering::Relaxed);
 
-    Ok(Json(
-        serde_json::json!({ "success": true, "message": "Config reloaded" }),
-    ))
+    Ok(MessageResponse::new("Config reloaded"))
+}
+
+/// POST /jobs/{id}/reload - Reread one job file, restarting only that job
+#[utoipa::path(
+    post,
+    path = "/jobs/{id}/reload",
+    tag = "jobs",
+    params(("id" = String, Path, description = "Job ID")),
+    responses(
+        (status = 200, body = MessageResponse),
+        (status = 409, description = "Jobs are not running", body = ErrorResponse),
+        (status = 422, description = "The job file doesn't parse", body = ErrorResponse)
+    )
+)]
+pub async fn jobs_reload_job(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<MessageResponse>, ApiError> {
+    if !state.started.load(Ordering::Relaxed) {
+        return Err(ApiError::conflict(
+            "Jobs are not running, the job is loaded when they start",
+        ));
+    }
+    let mut ap = state.auto_pilot.write().await;
+    match ap.reload_job(&id).await {
+        Ok(true) => Ok(MessageResponse::new(format!("Job {} reloaded", id))),
+        Ok(false) => Ok(MessageResponse::new(format!("Job {} unloaded", id))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// GET /metrics - Job and daemon metrics in the Prometheus text format
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "daemon",
+    responses((status = 200, content_type = "text/plain", body = String))
+)]
+pub async fn metrics(State(state): State<AppState>) -> Response {
+    let ap = state.auto_pilot.read().await;
+    let loaded: Vec<String> = ap.jobs.iter().map(|job| job.id.clone()).collect();
+    let scheduled = ap.jobs.iter().filter(|job| job.when.is_some()).count();
+    let body = render_metrics(&loaded, scheduled);
+    (
+        [(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        body,
+    )
+        .into_response()
 }
 
 // ============ Job CRUD Handlers ============
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct JobResponse {
     id: String,
     name: String,
     description: String,
     status: String,
+    enabled: bool,
+    tags: Vec<String>,
+    /// Type of the job's `when`, or `none` when it only waits for its conditions
+    schedule: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    when: Option<When>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    conditions: Option<Vec<ConditionScheme>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tasks: Option<Vec<TaskScheme>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_run: Option<LastRun>,
 }
 
-impl From<&crate::job::Job> for JobResponse {
-    fn from(job: &crate::job::Job) -> Self {
+impl JobResponse {
+    fn new(entry: JobEntry, expand: Expand) -> Self {
+        let name = entry.name();
+        let schedule = entry.schedule().to_string();
+        let JobEntry {
+            scheme,
+            status,
+            last_run,
+        } = entry;
         JobResponse {
-            id: job.id.clone(),
-            name: job.name.clone(),
-            description: job.description.clone(),
-            status: format!("{:?}", job.status),
+            id: scheme.id,
+            name,
+            description: scheme.description.unwrap_or_default(),
+            status: format!("{:?}", status),
+            enabled: scheme.enabled,
+            tags: scheme.tags,
+            schedule,
+            when: scheme.when.filter(|_| expand.when),
+            conditions: Some(scheme.conditions).filter(|_| expand.conditions),
+            tasks: Some(scheme.tasks).filter(|_| expand.tasks),
+            last_run: last_run.filter(|_| expand.last_run),
         }
     }
 }
-impl From<&crate::status::JobStatusStruct> for JobResponse {
-    fn from(job: &crate::status::JobStatusStruct) -> Self {
-        JobResponse {
-            id: job.id.clone(),
-            name: job.name.clone(),
-            //TODO
-            description: job.name.clone(),
-            status: format!("{:?}", job.status),
-        }
-    }
-}
-
-// GET /jobs - List all jobs
-// pub async fn jobs_list(
-//     State(state): State<AppState>,
-// ) -> Result<Json<Vec<JobResponse>>, StatusCode> {
-//     let ap = state.auto_pilot.read().await;
-//     // error!("api : {:p}", state.auto_pilot.as_ref());
-//     // dbg!(
-//     //     &ap.jobs
-//     //         .iter()
-//     //         .map(|job| job.status.clone())
-//     //         .collect::<Vec<JobStatusEnum>>()
-//     // );
-//     // let jobs: Vec<JobResponse> = ap.jobs.iter().map(JobResponse::from).collect();
-//     let jobs: Vec<JobResponse> = get_status_log()
-//         .statuses
-//         .iter()
-//         .map(JobResponse::from)
-//         .collect();
-//     // let jobs: Vec<JobResponse> = get_jobs(true).iter().map(JobResponse::from).collect();
-//     // let live_jobs =
-//     Ok(Json(jobs))
-// }
-
-pub async fn jobs_list(
-    State(state): State<AppState>,
-) -> Result<Json<Vec<JobResponse>>, StatusCode> {
-    let ap = state.auto_pilot.read().await;
-
-    // ۱. تبدیل live_jobs به HashMap با کلیدِ id
-    // این‌ها اولویت بالاتری دارن (نسخه‌های زنده)
-    let live_map: HashMap<String, JobResponse> = get_status_log()
-        .statuses
-        .iter()
-        .map(|j| {
-            let resp = JobResponse::from(j);
-            (resp.id.clone(), resp)
-        })
-        .collect();
-
-    // ۲. تبدیل jobs (مثلاً دیتابیس) به HashMap
-    let db_map: HashMap<String, JobResponse> = get_jobs(true)
-        .iter()
-        .map(|j| {
-            let resp = JobResponse::from(j);
-            (resp.id.clone(), resp)
-        })
-        .collect();
-
-    // ۳. شروع با لیست دیتابیس
-    let mut merged = db_map;
-
-    // ۴. آپدیت کردن با live_jobs
-    // اگر id تکراری باشه، مقدار live_map جایگزین میشه (Overwrite)
-    for (id, live_job) in live_map {
-        merged.insert(id, live_job);
-    }
 
-    // ۵. تبدیل نهایی HashMap به Vec برای ارسال به کاربر
-    let result: Vec<JobResponse> = merged.into_values().collect();
+#[derive(Serialize, ToSchema)]
+pub struct JobsPage {
+    jobs: Vec<JobResponse>,
+    /// Jobs matching the filters across all pages
+    total: usize,
+    /// Pass as `cursor` to get the next page, missing on the last one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_cursor: Option<String>,
+    /// Job files that were left out, because they don't parse or reuse an id
+    load_errors: Vec<LoadError>,
+}
 
-    Ok(Json(result))
+/// GET /jobs - List job files, filtered, sorted and a page at a time
+#[utoipa::path(
+    get,
+    path = "/jobs",
+    tag = "jobs",
+    params(JobsQuery),
+    responses(
+        (status = 200, body = JobsPage),
+        (status = 400, description = "Invalid query parameter", body = ErrorResponse)
+    )
+)]
+pub async fn jobs_list(ApiQuery(query): ApiQuery<JobsQuery>) -> Result<Json<JobsPage>, ApiError> {
+    let listing = query.into_listing()?;
+    let (schemes, load_errors) = load_job_schemes();
+    let page = listing.apply(job_entries(
+        schemes.into_iter().map(|(_, scheme)| scheme).collect(),
+    ));
+    Ok(Json(JobsPage {
+        jobs: page
+            .entries
+            .into_iter()
+            .map(|entry| JobResponse::new(entry, listing.expand))
+            .collect(),
+        total: page.total,
+        next_cursor: page.next_cursor,
+        load_errors,
+    }))
 }
 
-/// POST /jobs - Create a new job
+/// POST /jobs - Create a new job under a new id, the id in the body is not used
+#[utoipa::path(
+    post,
+    path = "/jobs",
+    tag = "jobs",
+    request_body = JobScheme,
+    responses(
+        (status = 200, body = MessageResponse),
+        (status = 400, description = "The body is not JSON", body = ErrorResponse),
+        (status = 422, description = "Invalid job definition", body = ErrorResponse),
+        (status = 500, description = "The job file could not be written", body = ErrorResponse)
+    )
+)]
 pub async fn jobs_create(
-    Json(payload): Json<JobScheme>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    match add_job(
-        payload.name.clone(),
-        payload.description.clone(),
-        payload.when.clone(),
-        payload.check_interval.clone(),
-        payload.conditions.clone(),
-        payload.tasks.clone(),
-    ) {
+    ApiJson(payload): ApiJson<JobScheme>,
+) -> Result<Json<MessageResponse>, ApiError> {
+    let scheme = JobScheme {
+        id: new_job_id(),
+        ..payload
+    };
+    match add_job_scheme(&scheme) {
         Ok(path) => {
             info!("Created job via API: {:?}", path);
-            Ok(Json(serde_json::json!({
-                "success": true,
-                "message": "Job created",
-                "path": path.to_string_lossy()
-            })))
+            Ok(MessageResponse::with_path("Job created", &path))
         }
         Err(e) => {
             info!("Failed to create job: {}", e);
-            Err(StatusCode::BAD_REQUEST)
+            Err(e.into())
         }
     }
 }
 
 /// GET /jobs/{id} - Get job by ID
-pub async fn jobs_getbyid(
-    State(state): State<AppState>,
-    Path(id): Path<String>,
-) -> Result<Json<JobResponse>, StatusCode> {
-    let ap = state.auto_pilot.read().await;
-    ap.jobs
-        .iter()
-        .find(|j| j.id == id)
-        .map(|job| Ok(Json(JobResponse::from(job))))
-        .unwrap_or(Err(StatusCode::NOT_FOUND))
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}",
+    tag = "jobs",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, body = JobResponse, headers(("ETag" = String, description = "Version of the job file, for If-Match"))),
+        (status = 404, description = "No job has this id", body = ErrorResponse)
+    )
+)]
+pub async fn jobs_getbyid(Path(id): Path<String>) -> Result<Response, ApiError> {
+    // Read from the file like updates are, so disabled jobs can be fetched for their ETag
+    let content = std::fs::read_to_string(get_job_path(&id)?).map_err(AutoPilotError::from)?;
+    let scheme: JobScheme = serde_json::from_str(&jsonc_parse(&content))
+        .map_err(|e| AutoPilotError::InvalidJob(e.to_string()))?;
+    let entry = job_entries(vec![scheme]).remove(0);
+    let mut response = Json(JobResponse::new(entry, Expand::all())).into_response();
+    set_etag(&mut response, &job_etag(&content));
+    Ok(response)
 }
 
 /// DELETE /jobs/{id} - Delete job by ID
-pub async fn jobs_delete(Path(id): Path<String>) -> Result<Json<serde_json::Value>, StatusCode> {
+#[utoipa::path(
+    delete,
+    path = "/jobs/{id}",
+    tag = "jobs",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, body = MessageResponse),
+        (status = 404, description = "No job has this id", body = ErrorResponse),
+        (status = 422, description = "A job file could not be parsed", body = ErrorResponse),
+        (status = 500, description = "The job file could not be removed", body = ErrorResponse)
+    )
+)]
+pub async fn jobs_delete(Path(id): Path<String>) -> Result<Json<MessageResponse>, ApiError> {
     match remove_job(Some(id), None) {
         Ok(()) => {
             info!("Deleted job via API");
-            Ok(Json(
-                serde_json::json!({ "success": true, "message": "Job deleted" }),
-            ))
+            Ok(MessageResponse::new("Job deleted"))
         }
         Err(e) => {
             info!("Failed to delete job: {}", e);
-            Err(StatusCode::NOT_FOUND)
+            Err(e.into())
         }
     }
 }
 
-/// PUT /jobs/{id} - Update job by ID
+/// PUT /jobs/{id} - Replace the definition of a job, rewriting its file in place
+#[utoipa::path(
+    put,
+    path = "/jobs/{id}",
+    tag = "jobs",
+    params(
+        ("id" = String, Path, description = "Job id"),
+        ("If-Match" = Option<String>, Header, description = "Only update if the job file still has this ETag")
+    ),
+    request_body = JobScheme,
+    responses(
+        (status = 200, body = MessageResponse, headers(("ETag" = String, description = "Version of the rewritten job file"))),
+        (status = 404, description = "No job has this id", body = ErrorResponse),
+        (status = 412, description = "The job file changed since the ETag was read", body = ErrorResponse),
+        (status = 422, description = "Invalid job definition", body = ErrorResponse),
+        (status = 500, description = "The job file could not be written", body = ErrorResponse)
+    )
+)]
 pub async fn jobs_update(
     State(state): State<AppState>,
     Path(id): Path<String>,
-    Json(payload): Json<JobScheme>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
-    // First delete the old job
-    if let Err(_) = remove_job(Some(id.clone()), None) {
-        return Err(StatusCode::NOT_FOUND);
+    headers: HeaderMap,
+    ApiJson(payload): ApiJson<JobScheme>,
+) -> Result<Response, ApiError> {
+    if payload.id != id {
+        return Err(ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid_body",
+            "The id of a job can't be changed",
+        )
+        .with_detail("id", format!("expected \"{}\"", id)));
+    }
+    let mut changes = match serde_json::to_value(&payload) {
+        Ok(serde_json::Value::Object(changes)) => changes,
+        _ => return Err(ApiError::internal("Failed to serialize the job")),
+    };
+    // Empty tags aren't serialized, but replacing the job drops the old ones all the same
+    changes.entry("tags").or_insert(serde_json::Value::Null);
+    write_update(&state, &id, &headers, &changes, "Job updated").await
+}
+
+/// PATCH /jobs/{id} - Change some fields of a job, rewriting its file in place
+#[utoipa::path(
+    patch,
+    path = "/jobs/{id}",
+    tag = "jobs",
+    params(
+        ("id" = String, Path, description = "Job id"),
+        ("If-Match" = Option<String>, Header, description = "Only update if the job file still has this ETag")
+    ),
+    request_body(
+        content = Object,
+        description = "Any of the JobScheme fields, `null` removes an optional field"
+    ),
+    responses(
+        (status = 200, body = MessageResponse, headers(("ETag" = String, description = "Version of the rewritten job file"))),
+        (status = 404, description = "No job has this id", body = ErrorResponse),
+        (status = 412, description = "The job file changed since the ETag was read", body = ErrorResponse),
+        (status = 422, description = "The patched job is invalid", body = ErrorResponse),
+        (status = 500, description = "The job file could not be written", body = ErrorResponse)
+    )
+)]
+pub async fn jobs_patch(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    ApiJson(patch): ApiJson<serde_json::Map<String, serde_json::Value>>,
+) -> Result<Response, ApiError> {
+    // Check the patched job here, so errors point into the request body
+    let current = get_job_scheme(&get_job_path(&id)?)?;
+    let Ok(serde_json::Value::Object(mut merged)) = serde_json::to_value(&current) else {
+        return Err(ApiError::internal("Failed to serialize the job"));
+    };
+    for (key, value) in &patch {
+        if value.is_null() {
+            merged.remove(key);
+        } else {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+    let merged = serde_json::to_vec(&merged).map_err(|e| ApiError::internal(e.to_string()))?;
+    let patched: JobScheme = parse_json(&merged)?;
+    if patched.id != id {
+        return Err(ApiError::new(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "invalid_body",
+            "The id of a job can't be changed",
+        )
+        .with_detail("id", format!("expected \"{}\"", id)));
+    }
+    write_update(&state, &id, &headers, &patch, "Job patched").await
+}
+
+async fn write_update(
+    state: &AppState,
+    id: &str,
+    headers: &HeaderMap,
+    changes: &serde_json::Map<String, serde_json::Value>,
+    message: &str,
+) -> Result<Response, ApiError> {
+    match update_job(id, changes, if_match(headers).as_deref()) {
+        Ok((path, etag)) => {
+            info!("Updated job {} via API: {:?}", id, path);
+            let message = reload_after_write(state, id, message).await;
+            let mut response = MessageResponse::with_path(message, &path).into_response();
+            set_etag(&mut response, &etag);
+            Ok(response)
+        }
+        Err(e) => {
+            info!("Failed to update job {}: {}", id, e);
+            Err(e.into())
+        }
+    }
+}
+
+/// Has a running daemon pick up a job file that was just written. The write stands either way, a
+/// failed reload is logged and added to `message`.
+async fn reload_after_write(state: &AppState, id: &str, message: &str) -> String {
+    if !state.started.load(Ordering::Relaxed) {
+        return message.to_string();
+    }
+    let mut ap = state.auto_pilot.write().await;
+    match ap.reload_job(id).await {
+        Ok(_) => message.to_string(),
+        Err(e) => {
+            warn!("Failed to reload job {}: {}", id, e);
+            format!("{}, but the daemon failed to load it: {}", message, e)
+        }
+    }
+}
+
+/// The ETag a request is conditional on, `None` when it isn't or accepts any version
+fn if_match(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get(header::IF_MATCH)?.to_str().ok()?.trim();
+    if value == "*" {
+        return None;
+    }
+    let first = value.split(',').next()?.trim();
+    Some(first.trim_start_matches("W/").trim_matches('"').to_string())
+}
+
+fn set_etag(response: &mut Response, etag: &str) {
+    if let Ok(value) = HeaderValue::from_str(&format!("\"{}\"", etag)) {
+        response.headers_mut().insert(header::ETAG, value);
     }
+}
+
+/// POST /jobs/{id}/enable - Enable job by ID
+#[utoipa::path(
+    post,
+    path = "/jobs/{id}/enable",
+    tag = "jobs",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, body = MessageResponse),
+        (status = 404, description = "No job has this id", body = ErrorResponse),
+        (status = 500, description = "The job file could not be written", body = ErrorResponse)
+    )
+)]
+pub async fn jobs_enable(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<MessageResponse>, ApiError> {
+    set_enabled(&state, id, true).await
+}
 
-    // Then create the new one
-    match add_job(
-        Some(format!("{}_updated", id)),
-        payload.description.clone(),
-        payload.when.clone(),
-        payload.check_interval.clone(),
-        payload.conditions.clone(),
-        payload.tasks.clone(),
-    ) {
+/// POST /jobs/{id}/disable - Disable job by ID
+#[utoipa::path(
+    post,
+    path = "/jobs/{id}/disable",
+    tag = "jobs",
+    params(("id" = String, Path, description = "Job id")),
+    responses(
+        (status = 200, body = MessageResponse),
+        (status = 404, description = "No job has this id", body = ErrorResponse),
+        (status = 500, description = "The job file could not be written", body = ErrorResponse)
+    )
+)]
+pub async fn jobs_disable(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<MessageResponse>, ApiError> {
+    set_enabled(&state, id, false).await
+}
+
+async fn set_enabled(
+    state: &AppState,
+    id: String,
+    enabled: bool,
+) -> Result<Json<MessageResponse>, ApiError> {
+    let action = if enabled { "enabled" } else { "disabled" };
+    match set_job_enabled(id.clone(), enabled) {
         Ok(path) => {
-            info!("Updated job via API: {:?}", path);
-            Ok(Json(serde_json::json!({
-                "success": true,
-                "message": "Job updated",
-                "path": path.to_string_lossy()
-            })))
+            info!("Job {} {} via API", id, action);
+            // Disabled jobs are unscheduled, enabled ones loaded, without waiting for a restart
+            let message = reload_after_write(state, &id, &format!("Job {}", action)).await;
+            Ok(MessageResponse::with_path(message, &path))
         }
         Err(e) => {
-            info!("Failed to update job: {}", e);
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            info!("Failed to set job {} {}: {}", id, action, e);
+            Err(e.into())
         }
     }
 }
+
+#[derive(Deserialize, IntoParams)]
+pub struct RunOptions {
+    /// Run the tasks without checking the conditions
+    #[serde(default)]
+    force: bool,
+    /// Only check the conditions
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// POST /jobs/{id}/run - Run job by ID now, streaming its progress as newline delimited JSON
+#[utoipa::path(
+    post,
+    path = "/jobs/{id}/run",
+    tag = "jobs",
+    params(("id" = String, Path, description = "Job id"), RunOptions),
+    responses(
+        (status = 200, description = "One event per line", content_type = "application/x-ndjson", body = RunEvent),
+        (status = 400, description = "Invalid query, e.g. force and dry_run were both set", body = ErrorResponse),
+        (status = 404, description = "No job has this id", body = ErrorResponse),
+        (status = 422, description = "The job file could not be parsed", body = ErrorResponse)
+    )
+)]
+pub async fn jobs_run(
+    Path(id): Path<String>,
+    ApiQuery(options): ApiQuery<RunOptions>,
+) -> Result<Response, ApiError> {
+    let mode = match (options.force, options.dry_run) {
+        (true, true) => {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "invalid_query",
+                "force and dry_run can't be combined",
+            )
+            .with_detail("dry_run", "conflicts with force"));
+        }
+        (true, false) => RunMode::Force,
+        (false, true) => RunMode::DryRun,
+        (false, false) => RunMode::Normal,
+    };
+    let job = get_job_by_id(&id).inspect_err(|e| info!("Failed to run job: {}", e))?;
+    info!("Running job {} via API ({:?})", id, mode);
+
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(run_job_with(job, mode, Some(sender)));
+    let stream = futures::stream::unfold(receiver, |mut receiver| async move {
+        let event = receiver.recv().await?;
+        let line = serde_json::to_string(&event).unwrap_or_default() + "\n";
+        Some((Ok::<_, std::convert::Infallible>(line), receiver))
+    });
+    Response::builder()
+        .header(header::CONTENT_TYPE, "application/x-ndjson")
+        .body(Body::from_stream(stream))
+        .map_err(|e| ApiError::internal(e.to_string()))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportOptions {
+    /// Comma separated ids of the jobs to export, every job when left out
+    ids: Option<String>,
+    /// Leave out conditions tied to this machine and disable the jobs that had them
+    #[serde(default)]
+    strip: bool,
+}
+
+/// GET /export - A bundle of job definitions, for `POST /import` on another machine
+#[utoipa::path(
+    get,
+    path = "/export",
+    tag = "jobs",
+    params(ExportOptions),
+    responses(
+        (status = 200, body = Bundle),
+        (status = 404, description = "One of the ids names no job", body = ErrorResponse)
+    )
+)]
+pub async fn jobs_export(
+    ApiQuery(options): ApiQuery<ExportOptions>,
+) -> Result<Json<Bundle>, ApiError> {
+    let ids: Vec<String> = options
+        .ids
+        .iter()
+        .flat_map(|ids| ids.split(','))
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect();
+    let (bundle, _) = export_jobs(&ids, options.strip)?;
+    Ok(Json(bundle))
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportOptions {
+    /// What to do with a job whose id is taken: `skip` (default), `overwrite` or `rename`
+    #[serde(default)]
+    #[param(inline)]
+    on_conflict: OnConflict,
+    /// Leave out conditions tied to the machine the bundle came from
+    #[serde(default)]
+    strip: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ImportReport {
+    jobs: Vec<Imported>,
+}
+
+/// POST /import - Add the jobs of a bundle, loading them right away when jobs are running
+#[utoipa::path(
+    post,
+    path = "/import",
+    tag = "jobs",
+    params(ImportOptions),
+    request_body = Bundle,
+    responses(
+        (status = 200, description = "What happened to each job, failures included", body = ImportReport),
+        (status = 400, description = "The body is not JSON", body = ErrorResponse),
+        (status = 422, description = "Not a bundle this build can read", body = ErrorResponse)
+    )
+)]
+pub async fn jobs_import(
+    State(state): State<AppState>,
+    ApiQuery(options): ApiQuery<ImportOptions>,
+    ApiJson(bundle): ApiJson<Bundle>,
+) -> Result<Json<ImportReport>, ApiError> {
+    let mut jobs = import_jobs(bundle, options.strip, |_| Ok(options.on_conflict))?;
+    info!("Imported {} jobs via API", jobs.len());
+    if state.started.load(Ordering::Relaxed) {
+        let mut ap = state.auto_pilot.write().await;
+        for job in &mut jobs {
+            if matches!(job.action, ImportAction::Skipped | ImportAction::Failed) {
+                continue;
+            }
+            if let Err(e) = ap.reload_job(&job.id).await {
+                warn!("Failed to load imported job {}: {}", job.id, e);
+                job.message = Some(format!("Written, but the daemon failed to load it: {}", e));
+            }
+        }
+    }
+    Ok(Json(ImportReport { jobs }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, atomic::AtomicBool};
+
+    use tokio::sync::RwLock;
+
+    use super::*;
+    use crate::{autopilot::AutoPilot, fs::TempJobsDir};
+
+    #[test]
+    fn test_create_keeps_tags_and_enabled() {
+        let _jobs = TempJobsDir::new();
+        let payload: JobScheme = serde_json::from_value(serde_json::json!({
+            "id": "ignored",
+            "enabled": false,
+            "tags": ["backup", "nightly"],
+            "conditions": [],
+            "tasks": [{ "command": "true" }]
+        }))
+        .unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let Json(created) = runtime.block_on(jobs_create(ApiJson(payload))).unwrap();
+
+        let content = std::fs::read_to_string(created.path.unwrap()).unwrap();
+        let scheme: JobScheme = serde_json::from_str(&content).unwrap();
+        assert_ne!(scheme.id, "ignored");
+        assert_eq!(scheme.tags, ["backup", "nightly"]);
+        assert!(!scheme.enabled);
+    }
+
+    #[test]
+    fn test_update_with_a_stale_etag_is_refused() {
+        let _jobs = TempJobsDir::new();
+        let job = |name: &str| -> JobScheme {
+            serde_json::from_value(serde_json::json!({
+                "id": "stale",
+                "name": name,
+                "conditions": [],
+                "tasks": [{ "command": "true" }]
+            }))
+            .unwrap()
+        };
+        let path = add_job_scheme(&job("stale")).unwrap();
+        let etag = job_etag(&std::fs::read_to_string(&path).unwrap());
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_MATCH,
+            HeaderValue::from_str(&format!("\"{}\"", etag)).unwrap(),
+        );
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let state = AppState {
+                auto_pilot: Arc::new(RwLock::new(AutoPilot::new().await)),
+                started: Arc::new(AtomicBool::new(false)),
+            };
+            let update = |name| {
+                jobs_update(
+                    State(state.clone()),
+                    Path("stale".to_string()),
+                    headers.clone(),
+                    ApiJson(job(name)),
+                )
+            };
+            update("first").await.unwrap();
+            let error = update("second").await.unwrap_err();
+            assert_eq!(error.status, StatusCode::PRECONDITION_FAILED);
+        });
+        assert!(std::fs::read_to_string(path).unwrap().contains("first"));
+    }
+}
@@ -0,0 +1,449 @@
+//! Filtering, sorting and cursor paging for `GET /jobs`
+
+use std::cmp::Ordering;
+
+use axum::http::StatusCode;
+use serde::Deserialize;
+use strum::IntoEnumIterator;
+use utoipa::IntoParams;
+
+use crate::{
+    api::error::ApiError, conditions::ConditionScheme, job::get::JobEntry, status::JobStatusEnum,
+};
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+const SCHEDULES: [&str; 7] = [
+    "once", "daily", "weekly", "monthly", "yearly", "cron", "none",
+];
+const EXPANSIONS: [&str; 5] = ["when", "conditions", "tasks", "last_run", "all"];
+
+/// Every parameter is optional; comma separated values match any of them
+#[derive(Default, Deserialize, IntoParams)]
+pub struct JobsQuery {
+    /// Comma separated statuses, e.g. `failed,running`
+    status: Option<String>,
+    /// Comma separated tags
+    tag: Option<String>,
+    /// Case insensitive part of the job name
+    name: Option<String>,
+    /// Comma separated schedule types: `once`, `daily`, `weekly`, `monthly`, `yearly`, `cron`, or
+    /// `none` for jobs that only wait for their conditions
+    schedule: Option<String>,
+    /// Comma separated condition types, also found inside logical conditions
+    condition: Option<String>,
+    /// `id` (default), `name`, `status` or `last_run`, prefixed with `-` for descending order
+    sort: Option<String>,
+    /// Jobs per page, 50 by default and 500 at most
+    limit: Option<usize>,
+    /// The `next_cursor` of the previous page
+    cursor: Option<String>,
+    /// Comma separated details to include: `when`, `conditions`, `tasks`, `last_run` or `all`
+    expand: Option<String>,
+}
+
+/// Optional parts of a job in the response
+#[derive(Clone, Copy, Default)]
+pub struct Expand {
+    pub when: bool,
+    pub conditions: bool,
+    pub tasks: bool,
+    pub last_run: bool,
+}
+
+impl Expand {
+    pub fn all() -> Self {
+        Self {
+            when: true,
+            conditions: true,
+            tasks: true,
+            last_run: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SortKey {
+    Id,
+    Name,
+    Status,
+    LastRun,
+}
+
+impl SortKey {
+    fn name(&self) -> &'static str {
+        match self {
+            SortKey::Id => "id",
+            SortKey::Name => "name",
+            SortKey::Status => "status",
+            SortKey::LastRun => "last_run",
+        }
+    }
+
+    fn value(&self, entry: &JobEntry) -> String {
+        match self {
+            SortKey::Id => entry.scheme.id.clone(),
+            SortKey::Name => entry.name().to_lowercase(),
+            SortKey::Status => format!("{:?}", entry.status),
+            // Jobs that never ran sort first
+            SortKey::LastRun => entry
+                .last_run
+                .as_ref()
+                .map(|run| run.finished.to_rfc3339())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// A validated [`JobsQuery`]
+pub struct JobListing {
+    statuses: Vec<String>,
+    tags: Vec<String>,
+    name: Option<String>,
+    schedules: Vec<String>,
+    conditions: Vec<String>,
+    sort: SortKey,
+    descending: bool,
+    limit: usize,
+    /// Sort value and id of the last job of the previous page
+    after: Option<(String, String)>,
+    pub expand: Expand,
+}
+
+/// One page of jobs and the cursor of the next one
+pub struct Page {
+    pub entries: Vec<JobEntry>,
+    pub total: usize,
+    pub next_cursor: Option<String>,
+}
+
+fn split(value: Option<String>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(|item| item.trim().to_lowercase())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn invalid(parameter: &str, message: String, expected: String) -> ApiError {
+    ApiError::new(StatusCode::BAD_REQUEST, "invalid_query", message)
+        .with_detail(parameter, expected)
+}
+
+/// Checks that every value is known, `known` being listed in the error otherwise
+fn check_known(parameter: &str, values: &[String], known: &[String]) -> Result<(), ApiError> {
+    match values.iter().find(|value| !known.contains(value)) {
+        Some(unknown) => Err(invalid(
+            parameter,
+            format!("Unknown {} '{}'", parameter, unknown),
+            format!("expected one of {}", known.join(", ")),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The `type` a condition has in job files
+fn condition_type(condition: &ConditionScheme) -> Option<String> {
+    serde_json::to_value(condition)
+        .ok()?
+        .get("type")?
+        .as_str()
+        .map(str::to_string)
+}
+
+fn condition_types(conditions: &[ConditionScheme], types: &mut Vec<String>) {
+    for condition in conditions {
+        if let Some(name) = condition_type(condition) {
+            types.push(name);
+        }
+        if let ConditionScheme::Logical(logical) = condition {
+            condition_types(&logical.conditions, types);
+        }
+    }
+}
+
+fn encode_cursor(sort: SortKey, value: &str, id: &str) -> String {
+    serde_json::to_string(&[sort.name(), value, id])
+        .unwrap_or_default()
+        .bytes()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn decode_cursor(cursor: &str) -> Option<(String, String, String)> {
+    let bytes = (0..cursor.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(cursor.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    let [sort, value, id]: [String; 3] = serde_json::from_slice(&bytes).ok()?;
+    Some((sort, value, id))
+}
+
+impl JobsQuery {
+    pub fn into_listing(self) -> Result<JobListing, ApiError> {
+        let statuses = split(self.status);
+        for status in &statuses {
+            // Accept `failed` as well as `Failed`
+            let mut chars = status.chars();
+            let variant: String = chars
+                .next()
+                .into_iter()
+                .flat_map(char::to_uppercase)
+                .chain(chars)
+                .collect();
+            if serde_json::from_value::<JobStatusEnum>(variant.into()).is_err() {
+                return Err(invalid(
+                    "status",
+                    format!("Unknown status '{}'", status),
+                    "expected a job status such as running, completed or failed".to_string(),
+                ));
+            }
+        }
+
+        let schedules = split(self.schedule);
+        check_known("schedule", &schedules, &SCHEDULES.map(str::to_string))?;
+
+        let conditions = split(self.condition);
+        let known_conditions: Vec<String> = ConditionScheme::iter()
+            .filter_map(|condition| condition_type(&condition))
+            .collect();
+        check_known("condition", &conditions, &known_conditions)?;
+
+        let expansions = split(self.expand);
+        check_known("expand", &expansions, &EXPANSIONS.map(str::to_string))?;
+        let expand = if expansions.iter().any(|item| item == "all") {
+            Expand::all()
+        } else {
+            let has = |name: &str| expansions.iter().any(|item| item == name);
+            Expand {
+                when: has("when"),
+                conditions: has("conditions"),
+                tasks: has("tasks"),
+                last_run: has("last_run"),
+            }
+        };
+
+        let sort = self.sort.unwrap_or_default();
+        let (descending, sort_name) = match sort.strip_prefix('-') {
+            Some(name) => (true, name),
+            None => (false, sort.as_str()),
+        };
+        let sort = match sort_name {
+            "" | "id" => SortKey::Id,
+            "name" => SortKey::Name,
+            "status" => SortKey::Status,
+            "last_run" => SortKey::LastRun,
+            other => {
+                return Err(invalid(
+                    "sort",
+                    format!("Unknown sort '{}'", other),
+                    "expected id, name, status or last_run, optionally prefixed with -".to_string(),
+                ));
+            }
+        };
+
+        let limit = self.limit.unwrap_or(DEFAULT_LIMIT);
+        if limit == 0 || limit > MAX_LIMIT {
+            return Err(invalid(
+                "limit",
+                format!("Invalid limit {}", limit),
+                format!("expected 1 to {}", MAX_LIMIT),
+            ));
+        }
+
+        let after = match self.cursor {
+            Some(cursor) => match decode_cursor(&cursor) {
+                Some((cursor_sort, value, id)) if cursor_sort == sort.name() => Some((value, id)),
+                Some(_) => {
+                    return Err(invalid(
+                        "cursor",
+                        "The cursor belongs to a listing with another sort".to_string(),
+                        format!("expected a cursor sorted by {}", sort.name()),
+                    ));
+                }
+                None => {
+                    return Err(invalid(
+                        "cursor",
+                        "Invalid cursor".to_string(),
+                        "expected the next_cursor of a previous page".to_string(),
+                    ));
+                }
+            },
+            None => None,
+        };
+
+        Ok(JobListing {
+            statuses,
+            tags: split(self.tag),
+            name: self.name.map(|name| name.to_lowercase()),
+            schedules,
+            conditions,
+            sort,
+            descending,
+            limit,
+            after,
+            expand,
+        })
+    }
+}
+
+impl JobListing {
+    fn matches(&self, entry: &JobEntry) -> bool {
+        let any =
+            |wanted: &[String], value: &str| wanted.is_empty() || wanted.iter().any(|w| w == value);
+        let mut types = vec![];
+        if !self.conditions.is_empty() {
+            condition_types(&entry.scheme.conditions, &mut types);
+        }
+        any(
+            &self.statuses,
+            &format!("{:?}", entry.status).to_lowercase(),
+        ) && (self.tags.is_empty()
+            || entry
+                .scheme
+                .tags
+                .iter()
+                .any(|tag| self.tags.contains(&tag.to_lowercase())))
+            && self
+                .name
+                .as_ref()
+                .is_none_or(|name| entry.name().to_lowercase().contains(name))
+            && any(&self.schedules, entry.schedule())
+            && (self.conditions.is_empty() || types.iter().any(|t| self.conditions.contains(t)))
+    }
+
+    /// Filters and sorts `entries`, returning the page after the cursor
+    pub fn apply(&self, entries: Vec<JobEntry>) -> Page {
+        let mut entries: Vec<(String, JobEntry)> = entries
+            .into_iter()
+            .filter(|entry| self.matches(entry))
+            .map(|entry| (self.sort.value(&entry), entry))
+            .collect();
+        // Ids are unique, so ties on the sort value still have a stable order
+        let order = |(value, entry): &(String, JobEntry), (other_value, other_id): (&str, &str)| {
+            value
+                .as_str()
+                .cmp(other_value)
+                .then_with(|| entry.scheme.id.as_str().cmp(other_id))
+        };
+        entries.sort_by(|a, b| order(a, (&b.0, &b.1.scheme.id)));
+        if self.descending {
+            entries.reverse();
+        }
+        let total = entries.len();
+
+        let after_cursor = |item: &(String, JobEntry)| match &self.after {
+            None => true,
+            Some((value, id)) => {
+                let ordering = order(item, (value, id));
+                if self.descending {
+                    ordering == Ordering::Less
+                } else {
+                    ordering == Ordering::Greater
+                }
+            }
+        };
+        let mut rest = entries.into_iter().filter(after_cursor).peekable();
+        let page: Vec<(String, JobEntry)> = rest.by_ref().take(self.limit).collect();
+        let next_cursor = match (rest.peek(), page.last()) {
+            (Some(_), Some((value, last))) => {
+                Some(encode_cursor(self.sort, value, &last.scheme.id))
+            }
+            _ => None,
+        };
+
+        Page {
+            entries: page.into_iter().map(|(_, entry)| entry).collect(),
+            total,
+            next_cursor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job::JobScheme;
+
+    fn entry(id: &str, name: &str, tags: &[&str], status: JobStatusEnum) -> JobEntry {
+        let scheme: JobScheme = serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": name,
+            "tags": tags,
+            "conditions": [{ "type": "logical", "condition": {
+                "operator": "And",
+                "conditions": [{ "type": "wifi", "condition": { "ssid": "home" } }]
+            }}],
+            "tasks": [],
+        }))
+        .unwrap();
+        JobEntry {
+            scheme,
+            status,
+            last_run: None,
+        }
+    }
+
+    fn entries() -> Vec<JobEntry> {
+        vec![
+            entry("c", "Backup photos", &["backup"], JobStatusEnum::Failed),
+            entry(
+                "a",
+                "Backup mail",
+                &["Backup", "mail"],
+                JobStatusEnum::Completed,
+            ),
+            entry("b", "Clean tmp", &[], JobStatusEnum::Failed),
+        ]
+    }
+
+    fn ids(page: &Page) -> Vec<&str> {
+        page.entries.iter().map(|e| e.scheme.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_job_listing_filters_and_pages_in_order() {
+        let listing = JobsQuery {
+            tag: Some("backup".to_string()),
+            condition: Some("wifi".to_string()),
+            sort: Some("-name".to_string()),
+            limit: Some(1),
+            ..Default::default()
+        }
+        .into_listing()
+        .unwrap();
+        let first = listing.apply(entries());
+        assert_eq!((ids(&first), first.total), (vec!["c"], 2));
+
+        let listing = JobsQuery {
+            tag: Some("backup".to_string()),
+            sort: Some("-name".to_string()),
+            cursor: first.next_cursor,
+            ..Default::default()
+        }
+        .into_listing()
+        .unwrap();
+        let second = listing.apply(entries());
+        assert_eq!(ids(&second), vec!["a"]);
+        assert!(second.next_cursor.is_none());
+
+        let listing = JobsQuery {
+            status: Some("failed".to_string()),
+            name: Some("TMP".to_string()),
+            ..Default::default()
+        }
+        .into_listing()
+        .unwrap();
+        assert_eq!(ids(&listing.apply(entries())), vec!["b"]);
+
+        let error = JobsQuery {
+            schedule: Some("hourly".to_string()),
+            ..Default::default()
+        }
+        .into_listing()
+        .err()
+        .unwrap();
+        assert_eq!(error.details[0].path, "schedule");
+    }
+}
@@ -1,3 +1,10 @@
+pub mod auth;
+pub mod client;
+pub mod error;
+pub mod events;
 pub mod handlers;
+pub mod listing;
+pub mod openapi;
 pub mod routes;
 pub mod state;
+pub mod tls;
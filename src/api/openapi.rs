@@ -0,0 +1,147 @@
+use axum::Json;
+use serde_json::{Map, Value, json};
+use utoipa::{
+    Modify, OpenApi,
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+};
+
+use crate::api::{events, handlers};
+
+/// OpenAPI description of the routes in [`crate::api::routes`], generated from the handler and payload types
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "AutoPilot API",
+        description = "Controls the autopilot-rs daemon and the jobs it runs"
+    ),
+    paths(
+        handlers::health,
+        handlers::jobs_status,
+        handlers::jobs_start,
+        handlers::jobs_stop,
+        handlers::jobs_reload,
+        handlers::metrics,
+        handlers::jobs_list,
+        handlers::jobs_create,
+        handlers::jobs_getbyid,
+        handlers::jobs_delete,
+        handlers::jobs_update,
+        handlers::jobs_patch,
+        handlers::jobs_enable,
+        handlers::jobs_disable,
+        handlers::jobs_run,
+        handlers::jobs_reload_job,
+        handlers::jobs_export,
+        handlers::jobs_import,
+        events::events_sse,
+        events::events_ws,
+    ),
+    modifiers(&BearerAuth),
+    security(("bearer" = [])),
+    tags(
+        (name = "daemon", description = "State of the daemon"),
+        (name = "jobs", description = "Job files and runs"),
+        (name = "events", description = "Live job lifecycle events")
+    )
+)]
+pub struct ApiDoc;
+
+/// Tokens from `api.tokens`, not needed when none are configured
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer",
+                SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+            );
+        }
+    }
+}
+
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// JSON Schema of a job file, `json_schema/schema.json`, built from the same types as the
+/// OpenAPI description so the two can't drift apart. `cargo xtask schema` writes it out.
+pub fn job_file_schema() -> Value {
+    let doc = serde_json::to_value(ApiDoc::openapi()).unwrap_or_default();
+    let schemas = &doc["components"]["schemas"];
+    let mut definitions = Map::new();
+    let mut pending = vec!["JobScheme".to_string()];
+    while let Some(name) = pending.pop() {
+        if definitions.contains_key(&name) {
+            continue;
+        }
+        let mut schema = schemas[&name].clone();
+        rewrite_refs(&mut schema, &mut pending);
+        definitions.insert(name, schema);
+    }
+    let Some(Value::Object(job)) = definitions.remove("JobScheme") else {
+        return Value::Null;
+    };
+    let mut schema = Map::new();
+    schema.insert(
+        "$schema".to_string(),
+        json!("https://json-schema.org/draft/2020-12/schema"),
+    );
+    schema.insert("title".to_string(), json!("AutoPilot job file"));
+    schema.extend(job);
+    schema.insert("$defs".to_string(), Value::Object(definitions));
+    Value::Object(schema)
+}
+
+/// Points component references at `$defs`, collecting the names they refer to
+fn rewrite_refs(value: &mut Value, found: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(reference)) = map.get_mut("$ref")
+                && let Some(name) = reference.strip_prefix("#/components/schemas/")
+            {
+                found.push(name.to_string());
+                *reference = format!("#/$defs/{}", name);
+            }
+            map.values_mut()
+                .for_each(|value| rewrite_refs(value, found));
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| rewrite_refs(value, found)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openapi_describes_job_payloads() {
+        let doc = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        let schemas = &doc["components"]["schemas"];
+        for name in [
+            "JobScheme",
+            "ConditionScheme",
+            "When",
+            "ErrorResponse",
+            "Event",
+        ] {
+            assert!(schemas.get(name).is_some(), "{} is missing", name);
+        }
+        assert!(doc["paths"]["/jobs/{id}/run"]["post"].is_object());
+    }
+
+    /// `cargo xtask schema` rewrites the file after a job type changed
+    #[test]
+    fn test_job_file_schema_is_generated_from_the_job_types() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/json_schema/schema.json");
+        let generated = serde_json::to_string_pretty(&job_file_schema()).unwrap() + "\n";
+        let on_disk = std::fs::read_to_string(path).unwrap();
+        assert!(
+            on_disk == generated,
+            "json_schema/schema.json is out of date, run `cargo xtask schema`"
+        );
+    }
+}
@@ -1,35 +1,147 @@
+use std::sync::Arc;
+
 use axum::{
-    Router,
-    routing::{delete, get, post, put},
+    Router, middleware,
+    routing::{delete, get, patch, post, put},
 };
-use log::info;
+use log::{error, info, warn};
 use ratatui::style::Stylize;
+use rustls::ServerConfig;
 use tokio::task::JoinHandle;
 
+use crate::api::auth::authenticate;
+use crate::api::error::{method_not_allowed, route_not_found};
+use crate::api::events::{events_sse, events_ws};
 use crate::api::handlers::*;
+use crate::api::openapi::openapi_json;
 use crate::api::state::AppState;
+use crate::api::tls::{TlsListener, load_server_config};
+use crate::config::{ListenAddress, get_config};
+use crate::error::AutoPilotError;
 
-pub async fn start_api(state: AppState) -> JoinHandle<()> {
-    let app = Router::new()
-        .route("/health", get(health))
+/// Serves the API on every address in `api.listen`
+pub async fn start_api(state: AppState) -> Result<Vec<JoinHandle<()>>, AutoPilotError> {
+    let api_config = get_config().api;
+    let mut app = Router::new()
         .route("/status", get(jobs_status))
         .route("/start", post(jobs_start))
         .route("/stop", post(jobs_stop))
         .route("/reload", post(jobs_reload))
+        .route("/metrics", get(metrics))
         .route("/jobs", get(jobs_list))
         .route("/jobs", post(jobs_create))
         .route("/jobs/{id}", get(jobs_getbyid))
         .route("/jobs/{id}", delete(jobs_delete))
         .route("/jobs/{id}", put(jobs_update))
+        .route("/jobs/{id}", patch(jobs_patch))
+        .route("/jobs/{id}/enable", post(jobs_enable))
+        .route("/jobs/{id}/disable", post(jobs_disable))
+        .route("/jobs/{id}/run", post(jobs_run))
+        .route("/jobs/{id}/reload", post(jobs_reload_job))
+        .route("/export", get(jobs_export))
+        .route("/import", post(jobs_import))
+        .route("/events", get(events_sse));
+    if api_config.websocket {
+        app = app.route("/events/ws", get(events_ws));
+    }
+    let app = app
+        .route_layer(middleware::from_fn(authenticate))
+        // Health checks and the API description stay reachable without a token
+        .route("/health", get(health))
+        .route("/openapi.json", get(openapi_json))
+        .fallback(route_not_found)
+        .method_not_allowed_fallback(method_not_allowed)
         .with_state(state.clone());
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000")
-        .await
-        .expect("Failed to bind API");
-    info!("{}", "Api server started on 0.0.0.0:3000".green());
-    // Spawn API server
-    let api_handle = tokio::spawn(async move {
-        axum::serve(listener, app).await.expect("API server failed");
-    });
-    api_handle
+    let listen = api_config.listen;
+    if listen.is_empty() {
+        warn!("api.listen is empty, the API is disabled");
+    }
+    let client_certificates = api_config
+        .tls
+        .as_ref()
+        .is_some_and(|tls| tls.client_ca.is_some());
+    if api_config.tokens.is_empty()
+        && !client_certificates
+        && listen.iter().any(|address| {
+            matches!(address, ListenAddress::Tcp(socket_address) if !socket_address.ip().is_loopback())
+        })
+    {
+        warn!("The API is reachable from other machines but api.tokens is empty, anyone can use it");
+    }
+    let tls = match &api_config.tls {
+        Some(tls_config) => Some(load_server_config(tls_config)?),
+        None => None,
+    };
+    let mut handles = vec![];
+    for address in &listen {
+        handles.push(serve_on(address, app.clone(), tls.clone()).await?);
+    }
+    Ok(handles)
+}
+
+/// Binds `address` and spawns the API server on it, TCP listeners use TLS if `tls` is given
+async fn serve_on(
+    address: &ListenAddress,
+    app: Router,
+    tls: Option<Arc<ServerConfig>>,
+) -> Result<JoinHandle<()>, AutoPilotError> {
+    let bind_error = |e: std::io::Error| {
+        AutoPilotError::Api(format!("Failed to bind API to {}: {}", address, e))
+    };
+    let handle = match address {
+        ListenAddress::Tcp(socket_address) => {
+            let listener = tokio::net::TcpListener::bind(socket_address)
+                .await
+                .map_err(bind_error)?;
+            let address = address.to_string();
+            match tls {
+                Some(tls) => {
+                    let listener = TlsListener::new(listener, tls).map_err(bind_error)?;
+                    info!("{}", format!("Api server uses TLS on {}", address).green());
+                    tokio::spawn(async move {
+                        if let Err(e) = axum::serve(listener, app).await {
+                            error!("API server on {} failed: {}", address, e);
+                        }
+                    })
+                }
+                None => tokio::spawn(async move {
+                    if let Err(e) = axum::serve(listener, app).await {
+                        error!("API server on {} failed: {}", address, e);
+                    }
+                }),
+            }
+        }
+        #[cfg(unix)]
+        ListenAddress::Unix(path) => {
+            use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+            // A socket left behind by a previous run can be replaced, a live one can't
+            if let Ok(metadata) = std::fs::symlink_metadata(path)
+                && metadata.file_type().is_socket()
+                && std::os::unix::net::UnixStream::connect(path).is_err()
+            {
+                std::fs::remove_file(path).map_err(bind_error)?;
+            }
+            let listener = tokio::net::UnixListener::bind(path).map_err(bind_error)?;
+            // Anyone who can connect can run commands, so keep it to the current user
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+                .map_err(bind_error)?;
+            let address = address.to_string();
+            tokio::spawn(async move {
+                if let Err(e) = axum::serve(listener, app).await {
+                    error!("API server on {} failed: {}", address, e);
+                }
+            })
+        }
+        #[cfg(not(unix))]
+        ListenAddress::Unix(_) => {
+            return Err(AutoPilotError::Api(format!(
+                "Can't listen on {}, Unix sockets are not supported on this platform",
+                address
+            )));
+        }
+    };
+    info!("{}", format!("Api server started on {}", address).green());
+    Ok(handle)
 }
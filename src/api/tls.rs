@@ -0,0 +1,256 @@
+//! HTTPS for the API's TCP listeners, with optional client certificate authentication.
+
+use std::{io, net::SocketAddr, sync::Arc, time::Duration};
+
+use log::debug;
+use rustls::{
+    ClientConfig, RootCertStore, ServerConfig,
+    pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+    server::WebPkiClientVerifier,
+};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_rustls::{TlsAcceptor, server::TlsStream};
+
+use crate::{config::TlsConfig, error::AutoPilotError};
+
+/// How long a client gets to finish the handshake before it's dropped
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Builds the rustls configuration from the PEM files named in `api.tls`
+pub fn load_server_config(config: &TlsConfig) -> Result<Arc<ServerConfig>, AutoPilotError> {
+    let certs = read_certificates(&config.cert)?;
+    let key = PrivateKeyDer::from_pem_file(&config.key).map_err(|e| {
+        AutoPilotError::Api(format!("Failed to read TLS key {}: {}", config.key, e))
+    })?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| AutoPilotError::Api(format!("Failed to set up TLS: {}", e)))?;
+    let builder = match &config.client_ca {
+        Some(client_ca) => {
+            let mut roots = RootCertStore::empty();
+            for cert in read_certificates(client_ca)? {
+                roots.add(cert).map_err(|e| {
+                    AutoPilotError::Api(format!("Invalid client CA in {}: {}", client_ca, e))
+                })?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .map_err(|e| {
+                    AutoPilotError::Api(format!("Invalid client CA in {}: {}", client_ca, e))
+                })?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let mut server_config = builder.with_single_cert(certs, key).map_err(|e| {
+        AutoPilotError::Api(format!(
+            "TLS certificate {} doesn't match key {}: {}",
+            config.cert, config.key, e
+        ))
+    })?;
+    // axum is built with HTTP/1 only
+    server_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    Ok(Arc::new(server_config))
+}
+
+/// Client side of [`load_server_config`] for local commands talking to the daemon, trusting the
+/// certificates in `api.tls.cert` so self-signed setups work without a CA. With `client_ca` set
+/// the daemon only lets them in with `client_cert` and `client_key`, or over a Unix socket.
+pub fn load_client_config(config: &TlsConfig) -> Result<Arc<ClientConfig>, AutoPilotError> {
+    let mut roots = RootCertStore::empty();
+    let (added, _) = roots.add_parsable_certificates(read_certificates(&config.cert)?);
+    if added == 0 {
+        return Err(AutoPilotError::Api(format!(
+            "No usable certificate in {}",
+            config.cert
+        )));
+    }
+    let builder =
+        ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| AutoPilotError::Api(format!("Failed to set up TLS: {}", e)))?
+            .with_root_certificates(roots);
+    let client_config = match (&config.client_cert, &config.client_key) {
+        (Some(cert), Some(key)) => {
+            let key = PrivateKeyDer::from_pem_file(key).map_err(|e| {
+                AutoPilotError::Api(format!("Failed to read TLS client key {}: {}", key, e))
+            })?;
+            builder
+                .with_client_auth_cert(read_certificates(cert)?, key)
+                .map_err(|e| {
+                    AutoPilotError::Api(format!(
+                        "TLS client certificate {} doesn't match its key: {}",
+                        cert, e
+                    ))
+                })?
+        }
+        _ => builder.with_no_client_auth(),
+    };
+    Ok(Arc::new(client_config))
+}
+
+fn read_certificates(path: &str) -> Result<Vec<CertificateDer<'static>>, AutoPilotError> {
+    let to_error = |e: rustls::pki_types::pem::Error| {
+        AutoPilotError::Api(format!("Failed to read {}: {}", path, e))
+    };
+    let certs = CertificateDer::pem_file_iter(path)
+        .map_err(to_error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(to_error)?;
+    if certs.is_empty() {
+        return Err(AutoPilotError::Api(format!(
+            "No certificates found in {}",
+            path
+        )));
+    }
+    Ok(certs)
+}
+
+/// Accepts TCP connections and yields the ones that completed a TLS handshake
+pub struct TlsListener {
+    local_addr: SocketAddr,
+    streams: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+}
+
+impl TlsListener {
+    pub fn new(listener: TcpListener, server_config: Arc<ServerConfig>) -> io::Result<Self> {
+        let local_addr = listener.local_addr()?;
+        let acceptor = TlsAcceptor::from(server_config);
+        let (sender, streams) = mpsc::channel(64);
+        tokio::spawn(async move {
+            while !sender.is_closed() {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(connection) => connection,
+                    Err(e) => {
+                        debug!("Failed to accept API connection: {}", e);
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                        continue;
+                    }
+                };
+                let acceptor = acceptor.clone();
+                let sender = sender.clone();
+                // Handshakes run on their own, so a slow client can't hold up the others
+                tokio::spawn(async move {
+                    match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                        Ok(Ok(stream)) => {
+                            let _ = sender.send((stream, peer)).await;
+                        }
+                        Ok(Err(e)) => debug!("TLS handshake with {} failed: {}", peer, e),
+                        Err(_) => debug!("TLS handshake with {} timed out", peer),
+                    }
+                });
+            }
+        });
+        Ok(Self {
+            local_addr,
+            streams,
+        })
+    }
+}
+
+impl axum::serve::Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.streams.recv().await {
+            Some(connection) => connection,
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use axum::{Router, routing::get};
+    use rcgen::{BasicConstraints, CertificateParams, CertifiedIssuer, IsCa, KeyPair};
+    use rustls::pki_types::ServerName;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_rustls::TlsConnector;
+
+    use super::*;
+
+    fn write_file(dir: &Path, name: &str, contents: &str) -> String {
+        let path = dir.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    async fn get_health(
+        address: SocketAddr,
+        client_config: Arc<ClientConfig>,
+    ) -> io::Result<String> {
+        let stream = TcpStream::connect(address).await?;
+        let mut stream = TlsConnector::from(client_config)
+            .connect(ServerName::try_from("localhost").unwrap(), stream)
+            .await?;
+        stream
+            .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        Ok(response)
+    }
+
+    #[tokio::test]
+    async fn test_tls_listener_requires_client_certificate() {
+        let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca = CertifiedIssuer::self_signed(ca_params, KeyPair::generate().unwrap()).unwrap();
+        let server_key = KeyPair::generate().unwrap();
+        let server_cert = CertificateParams::new(vec!["localhost".to_string()])
+            .unwrap()
+            .signed_by(&server_key, &ca)
+            .unwrap();
+        let client_key = KeyPair::generate().unwrap();
+        let client_cert = CertificateParams::new(vec!["dashboard".to_string()])
+            .unwrap()
+            .signed_by(&client_key, &ca)
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = TlsConfig {
+            // The chain holds the CA too, so local commands trust the daemon through it
+            cert: write_file(dir.path(), "server.pem", &(server_cert.pem() + &ca.pem())),
+            key: write_file(dir.path(), "server.key", &server_key.serialize_pem()),
+            client_ca: Some(write_file(dir.path(), "ca.pem", &ca.pem())),
+            client_cert: Some(write_file(dir.path(), "client.pem", &client_cert.pem())),
+            client_key: Some(write_file(
+                dir.path(),
+                "client.key",
+                &client_key.serialize_pem(),
+            )),
+        };
+        let listener = TlsListener::new(
+            TcpListener::bind("127.0.0.1:0").await.unwrap(),
+            load_server_config(&config).unwrap(),
+        )
+        .unwrap();
+        let address = listener.local_addr;
+        tokio::spawn(async move {
+            let app = Router::new().route("/health", get(|| async { "ok" }));
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let with_certificate = load_client_config(&config).unwrap();
+        let response = get_health(address, with_certificate).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        config.client_cert = None;
+        config.client_key = None;
+        let without_certificate = load_client_config(&config).unwrap();
+        let response = get_health(address, without_certificate).await;
+        assert!(!response.is_ok_and(|response| response.contains("200 OK")));
+    }
+}
@@ -0,0 +1,217 @@
+//! In-process bus of job lifecycle events, consumed by the API's event streams.
+
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+
+use crate::{
+    job::{RunEvent, RunMode},
+    logging::context::{JobLogContext, current_job_context},
+    status::JobStatusEnum,
+};
+
+/// Events kept for subscribers that fall behind before they start missing some
+const CAPACITY: usize = 1024;
+
+static EVENTS: OnceLock<broadcast::Sender<Event>> = OnceLock::new();
+
+fn sender() -> &'static broadcast::Sender<Event> {
+    EVENTS.get_or_init(|| broadcast::channel(CAPACITY).0)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+pub struct Event {
+    pub time: DateTime<Local>,
+    pub job_id: Option<String>,
+    pub run_id: Option<String>,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventKind {
+    /// A job was read from disk into the daemon
+    JobLoaded {
+        name: String,
+        scheduled: bool,
+    },
+    /// A top level condition of a running job was evaluated
+    ConditionEvaluated {
+        name: String,
+        satisfied: bool,
+    },
+    RunStarted {
+        mode: RunMode,
+    },
+    /// A line printed by a task (stdout and stderr are merged)
+    TaskOutput {
+        command: String,
+        line: String,
+    },
+    TaskFinished {
+        command: String,
+        success: bool,
+        error: Option<String>,
+    },
+    RunFinished {
+        status: JobStatusEnum,
+    },
+    /// The daemon reloaded its jobs
+    Reload {
+        jobs: usize,
+    },
+}
+
+impl EventKind {
+    pub const NAMES: [&str; 7] = [
+        "job_loaded",
+        "condition_evaluated",
+        "run_started",
+        "task_output",
+        "task_finished",
+        "run_finished",
+        "reload",
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::JobLoaded { .. } => "job_loaded",
+            EventKind::ConditionEvaluated { .. } => "condition_evaluated",
+            EventKind::RunStarted { .. } => "run_started",
+            EventKind::TaskOutput { .. } => "task_output",
+            EventKind::TaskFinished { .. } => "task_finished",
+            EventKind::RunFinished { .. } => "run_finished",
+            EventKind::Reload { .. } => "reload",
+        }
+    }
+
+    /// One line describing the event, for logs and the dashboard
+    pub fn summary(&self) -> String {
+        let mark = |success: bool| if success { "✓" } else { "✗" };
+        match self {
+            EventKind::JobLoaded { name, .. } => format!("── loaded {}", name),
+            EventKind::ConditionEvaluated { name, satisfied } => {
+                format!("{} condition {}", mark(*satisfied), name)
+            }
+            EventKind::RunStarted { mode } => format!("── run started ({:?})", mode),
+            EventKind::TaskOutput { line, .. } => format!("  {}", line),
+            EventKind::TaskFinished {
+                command,
+                success,
+                error,
+            } => match error {
+                Some(error) => format!("{} {}: {}", mark(*success), command, error),
+                None => format!("{} {}", mark(*success), command),
+            },
+            EventKind::RunFinished { status } => format!("── run finished: {:?}", status),
+            EventKind::Reload { jobs } => format!("── reloaded {} jobs", jobs),
+        }
+    }
+}
+
+impl From<RunEvent> for EventKind {
+    fn from(event: RunEvent) -> Self {
+        match event {
+            RunEvent::Condition { name, satisfied } => {
+                EventKind::ConditionEvaluated { name, satisfied }
+            }
+            RunEvent::TaskOutput { command, line } => EventKind::TaskOutput { command, line },
+            RunEvent::TaskFinished {
+                command,
+                success,
+                error,
+            } => EventKind::TaskFinished {
+                command,
+                success,
+                error,
+            },
+            RunEvent::Finished { status } => EventKind::RunFinished { status },
+        }
+    }
+}
+
+pub fn subscribe() -> broadcast::Receiver<Event> {
+    sender().subscribe()
+}
+
+/// Emits `kind` for the job run the current task belongs to, if any
+pub fn emit(kind: EventKind) {
+    emit_in(current_job_context().as_ref(), kind);
+}
+
+/// Emits `kind` for the job run described by `context`, for code running outside of it
+pub fn emit_in(context: Option<&JobLogContext>, kind: EventKind) {
+    publish(
+        context.map(|context| context.job_id.clone()),
+        context.map(|context| context.run_id.clone()),
+        kind,
+    );
+}
+
+/// Emits `kind` for a job outside of any run
+pub fn emit_for_job(job_id: &str, kind: EventKind) {
+    publish(Some(job_id.to_string()), None, kind);
+}
+
+fn publish(job_id: Option<String>, run_id: Option<String>, kind: EventKind) {
+    // Sending only fails when nobody is subscribed
+    let _ = sender().send(Event {
+        time: Local::now(),
+        job_id,
+        run_id,
+        kind,
+    });
+}
+
+/// Selects the events a subscriber gets, an empty filter lets everything through
+#[derive(Clone, Debug, Default)]
+pub struct EventFilter {
+    pub job_ids: Vec<String>,
+    pub types: Vec<String>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &Event) -> bool {
+        (self.job_ids.is_empty()
+            || event
+                .job_id
+                .as_ref()
+                .is_some_and(|job_id| self.job_ids.contains(job_id)))
+            && (self.types.is_empty() || self.types.iter().any(|name| name == event.kind.name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serializes_flat_and_filters_by_job_and_type() {
+        let event = Event {
+            time: Local::now(),
+            job_id: Some("backup".to_string()),
+            run_id: None,
+            kind: EventKind::Reload { jobs: 2 },
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "reload");
+        assert_eq!(json["jobs"], 2);
+        assert_eq!(json["job_id"], "backup");
+
+        assert!(EventFilter::default().matches(&event));
+        let filter = EventFilter {
+            job_ids: vec!["backup".to_string()],
+            types: vec!["reload".to_string(), "run_finished".to_string()],
+        };
+        assert!(filter.matches(&event));
+        let filter = EventFilter {
+            job_ids: vec!["other".to_string()],
+            types: vec![],
+        };
+        assert!(!filter.matches(&event));
+    }
+}
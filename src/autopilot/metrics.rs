@@ -0,0 +1,325 @@
+//! Counters and histograms recorded while jobs run, rendered for `/metrics` in the Prometheus
+//! text format. They live as long as the daemon, a restart starts them over.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use chrono::Local;
+
+use crate::status::JobStatusEnum;
+
+/// Upper bounds in seconds of the task duration buckets
+const TASK_BUCKETS: [f64; 10] = [0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 300.0, 900.0, 3600.0];
+/// Upper bounds in seconds of the condition evaluation buckets
+const CONDITION_BUCKETS: [f64; 9] = [0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0];
+
+static METRICS: Mutex<Metrics> = Mutex::new(Metrics {
+    jobs: BTreeMap::new(),
+    conditions: BTreeMap::new(),
+});
+
+/// Runs waiting for a `concurrency.max_running_jobs` slot
+static WAITING_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+struct Metrics {
+    jobs: BTreeMap<String, JobMetrics>,
+    conditions: BTreeMap<String, Histogram>,
+}
+
+struct JobMetrics {
+    runs: u64,
+    successes: u64,
+    failures: u64,
+    unsatisfied_checks: u64,
+    /// Unix time of the last completed run
+    last_success: Option<i64>,
+    task_duration: Histogram,
+}
+
+impl Default for JobMetrics {
+    fn default() -> Self {
+        Self {
+            runs: 0,
+            successes: 0,
+            failures: 0,
+            unsatisfied_checks: 0,
+            last_success: None,
+            task_duration: Histogram::new(&TASK_BUCKETS),
+        }
+    }
+}
+
+struct Histogram {
+    bounds: &'static [f64],
+    /// Cumulative, `counts[i]` is the number of observations up to `bounds[i]`
+    counts: Vec<u64>,
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: vec![0; bounds.len()],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    fn observe(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (bound, count) in self.bounds.iter().zip(self.counts.iter_mut()) {
+            if seconds <= *bound {
+                *count += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+
+    fn render(&self, out: &mut String, name: &str, label: &str, value: &str) {
+        let value = escape(value);
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            let _ = writeln!(
+                out,
+                "{}_bucket{{{}=\"{}\",le=\"{}\"}} {}",
+                name, label, value, bound, count
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{}_bucket{{{}=\"{}\",le=\"+Inf\"}} {}",
+            name, label, value, self.count
+        );
+        let _ = writeln!(out, "{}_sum{{{}=\"{}\"}} {}", name, label, value, self.sum);
+        let _ = writeln!(
+            out,
+            "{}_count{{{}=\"{}\"}} {}",
+            name, label, value, self.count
+        );
+    }
+}
+
+/// A per job counter and how to read it
+struct Counter {
+    name: &'static str,
+    help: &'static str,
+    value: fn(&JobMetrics) -> u64,
+}
+
+const COUNTERS: [Counter; 4] = [
+    Counter {
+        name: "autopilot_job_runs_total",
+        help: "Finished runs of a job",
+        value: |job| job.runs,
+    },
+    Counter {
+        name: "autopilot_job_successes_total",
+        help: "Runs of a job that completed",
+        value: |job| job.successes,
+    },
+    Counter {
+        name: "autopilot_job_failures_total",
+        help: "Runs of a job with a failed task",
+        value: |job| job.failures,
+    },
+    Counter {
+        name: "autopilot_job_unsatisfied_checks_total",
+        help: "Checks of a job's conditions that were not satisfied",
+        value: |job| job.unsatisfied_checks,
+    },
+];
+
+fn with_job(job_id: &str, update: impl FnOnce(&mut JobMetrics)) {
+    if let Ok(mut metrics) = METRICS.lock() {
+        update(metrics.jobs.entry(job_id.to_string()).or_default());
+    }
+}
+
+/// Counts a finished run of a job by how it ended
+pub fn record_run(job_id: &str, status: &JobStatusEnum) {
+    with_job(job_id, |job| {
+        job.runs += 1;
+        match status {
+            JobStatusEnum::Completed => {
+                job.successes += 1;
+                job.last_success = Some(Local::now().timestamp());
+            }
+            JobStatusEnum::Failed => job.failures += 1,
+            _ => {}
+        }
+    });
+}
+
+/// Counts a check of a job's conditions that came back unsatisfied
+pub fn record_unsatisfied_check(job_id: &str) {
+    with_job(job_id, |job| job.unsatisfied_checks += 1);
+}
+
+pub fn record_task_duration(job_id: &str, duration: Duration) {
+    with_job(job_id, |job| job.task_duration.observe(duration));
+}
+
+/// Records how long a condition of the given type took to evaluate
+pub fn record_condition(condition: &str, duration: Duration) {
+    if let Ok(mut metrics) = METRICS.lock() {
+        metrics
+            .conditions
+            .entry(condition.to_string())
+            .or_insert_with(|| Histogram::new(&CONDITION_BUCKETS))
+            .observe(duration);
+    }
+}
+
+/// Counts a run as waiting for a slot until the returned guard is dropped
+pub fn queued_run() -> QueuedRun {
+    WAITING_RUNS.fetch_add(1, Ordering::Relaxed);
+    QueuedRun
+}
+
+pub struct QueuedRun;
+
+impl Drop for QueuedRun {
+    fn drop(&mut self) {
+        WAITING_RUNS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Label values are quoted, so backslashes, quotes and newlines need escaping
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Renders every metric; `loaded_jobs` get zeroed series even before their first run, so
+/// alerts on them have something to look at
+pub fn render(loaded_jobs: &[String], scheduled_jobs: usize) -> String {
+    let mut out = String::new();
+    header(
+        &mut out,
+        "autopilot_jobs_loaded",
+        "gauge",
+        "Jobs loaded by the daemon",
+    );
+    let _ = writeln!(out, "autopilot_jobs_loaded {}", loaded_jobs.len());
+    header(
+        &mut out,
+        "autopilot_jobs_scheduled",
+        "gauge",
+        "Loaded jobs waiting in the scheduler for their next time",
+    );
+    let _ = writeln!(out, "autopilot_jobs_scheduled {}", scheduled_jobs);
+    header(
+        &mut out,
+        "autopilot_runs_waiting",
+        "gauge",
+        "Runs waiting for a concurrency.max_running_jobs slot",
+    );
+    let _ = writeln!(
+        out,
+        "autopilot_runs_waiting {}",
+        WAITING_RUNS.load(Ordering::Relaxed)
+    );
+
+    let Ok(mut metrics) = METRICS.lock() else {
+        return out;
+    };
+    for job_id in loaded_jobs {
+        metrics.jobs.entry(job_id.clone()).or_default();
+    }
+
+    for counter in COUNTERS {
+        header(&mut out, counter.name, "counter", counter.help);
+        for (job_id, job) in &metrics.jobs {
+            let _ = writeln!(
+                out,
+                "{}{{job=\"{}\"}} {}",
+                counter.name,
+                escape(job_id),
+                (counter.value)(job)
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "autopilot_job_last_success_timestamp_seconds",
+        "gauge",
+        "Unix time of the last completed run of a job",
+    );
+    for (job_id, job) in &metrics.jobs {
+        if let Some(last_success) = job.last_success {
+            let _ = writeln!(
+                out,
+                "autopilot_job_last_success_timestamp_seconds{{job=\"{}\"}} {}",
+                escape(job_id),
+                last_success
+            );
+        }
+    }
+
+    header(
+        &mut out,
+        "autopilot_task_duration_seconds",
+        "histogram",
+        "How long the tasks of a job took",
+    );
+    for (job_id, job) in &metrics.jobs {
+        job.task_duration
+            .render(&mut out, "autopilot_task_duration_seconds", "job", job_id);
+    }
+
+    header(
+        &mut out,
+        "autopilot_condition_evaluation_seconds",
+        "histogram",
+        "How long conditions of a type took to evaluate",
+    );
+    for (condition, histogram) in &metrics.conditions {
+        histogram.render(
+            &mut out,
+            "autopilot_condition_evaluation_seconds",
+            "condition",
+            condition,
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counts_runs_and_buckets_durations() {
+        record_run("metrics \"test\"", &JobStatusEnum::Completed);
+        record_run("metrics \"test\"", &JobStatusEnum::Failed);
+        record_task_duration("metrics \"test\"", Duration::from_millis(700));
+
+        let out = render(&["metrics_idle".to_string()], 0);
+        assert!(out.contains("autopilot_job_runs_total{job=\"metrics \\\"test\\\"\"} 2"));
+        assert!(out.contains("autopilot_job_failures_total{job=\"metrics \\\"test\\\"\"} 1"));
+        assert!(out.contains("autopilot_job_runs_total{job=\"metrics_idle\"} 0"));
+        assert!(out.contains(
+            "autopilot_task_duration_seconds_bucket{job=\"metrics \\\"test\\\"\",le=\"0.5\"} 0"
+        ));
+        assert!(out.contains(
+            "autopilot_task_duration_seconds_bucket{job=\"metrics \\\"test\\\"\",le=\"1\"} 1"
+        ));
+    }
+}
@@ -5,14 +5,31 @@ use tokio::task::JoinHandle;
 use tokio_cron_scheduler::JobScheduler;
 
 use crate::{
+    autopilot::events::{EventKind, emit, emit_for_job},
     cli::status::check_if_running,
     error::AutoPilotError,
-    job::{Job, get::get_jobs},
-    logging::init_logging,
-    status::set::set_status_initial,
-    time::init::init_time_check,
+    job::{
+        Job,
+        get::{get_job_path, get_job_scheme, get_jobs},
+    },
+    logging::{
+        context::{JobLogContext, with_job_context},
+        init_logging,
+    },
+    service::notify::{notify, schedule_watchdog},
+    status::{
+        JobStatusEnum,
+        set::{set_status_entry, set_status_initial},
+    },
+    time::{
+        add::{clear_scheduled, unschedule_job},
+        init::init_time_check,
+    },
 };
 
+pub mod events;
+pub mod metrics;
+
 // #[derive(Clone)]
 pub struct AutoPilot {
     pub started: bool,
@@ -25,12 +42,21 @@ impl AutoPilot {
     pub async fn new() -> Self {
         Self {
             started: false,
-            scheduler: init_time_check().await.expect("failed to init cron"),
+            scheduler: Self::start_scheduler().await,
             jobs: Vec::new(),
             jobs_handles: Vec::new(),
         }
     }
 
+    /// A running scheduler, which also carries the service manager's watchdog pings
+    async fn start_scheduler() -> JobScheduler {
+        let scheduler = init_time_check().await.expect("failed to init cron");
+        if let Err(e) = schedule_watchdog(&scheduler).await {
+            error!("Failed to schedule the watchdog: {}", e);
+        }
+        scheduler
+    }
+
     pub fn init(&mut self, verbose: bool) -> Result<(), AutoPilotError> {
         Self::prepare_logging(verbose);
         if Self::check_instance() {
@@ -43,14 +69,80 @@ impl AutoPilot {
         Ok(())
     }
     pub async fn reload(&mut self) {
+        notify("RELOADING=1\nSTATUS=Reloading jobs");
         self.stop_jobs().await.expect("failed to stop jobs");
+        // A scheduler that was shut down never ticks again
+        self.scheduler = Self::start_scheduler().await;
         info!("{}", "Reloading Autopilot...".yellow());
         Self::init_status().expect("failed to init status");
         self.load_jobs();
 
         self.start(false);
 
-        info!("{}", "Autopilot reloaded successfully!".green())
+        info!("{}", "Autopilot reloaded successfully!".green());
+        emit(EventKind::Reload {
+            jobs: self.jobs.len(),
+        });
+        self.notify_ready();
+    }
+    /// Rereads a single job file and restarts that job while the others keep running. A job whose
+    /// file is gone or disabled is only stopped. Returns whether the job is loaded afterwards.
+    pub async fn reload_job(&mut self, id: &str) -> Result<bool, AutoPilotError> {
+        // Parse first, so a broken file leaves the running job alone
+        let scheme = match get_job_path(id) {
+            Ok(path) => Some(get_job_scheme(&path)?),
+            Err(AutoPilotError::JobNotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+
+        if let Some(index) = self.jobs.iter().position(|job| job.id == id) {
+            self.jobs.remove(index);
+            if index < self.jobs_handles.len() {
+                self.jobs_handles.remove(index).abort();
+            }
+        }
+        unschedule_job(id, &self.scheduler).await?;
+
+        let job = scheme.map(Job::from_scheme);
+        let status = job.as_ref().map(|job| {
+            let status = if job.enabled {
+                JobStatusEnum::Unknown
+            } else {
+                JobStatusEnum::Disabled
+            };
+            (job.name.clone(), status)
+        });
+        set_status_entry(id, status).map_err(AutoPilotError::State)?;
+        let Some(job) = job.filter(|job| job.enabled) else {
+            info!("Unloaded job: {}", id);
+            return Ok(false);
+        };
+
+        info!("Reloaded job: {}", job.name);
+        emit_for_job(
+            &job.id,
+            EventKind::JobLoaded {
+                name: job.name.clone(),
+                scheduled: job.when.is_some(),
+            },
+        );
+        self.jobs.push(job.clone());
+        let scheduler = self.scheduler.clone();
+        let mut job = job;
+        self.jobs_handles.push(tokio::task::spawn(async move {
+            let context = JobLogContext::new(&job);
+            with_job_context(context, job.run(&scheduler, false)).await;
+        }));
+        Ok(true)
+    }
+    /// Tells the service manager the jobs are loaded, along with a short summary of them
+    pub fn notify_ready(&self) {
+        let scheduled = self.jobs.iter().filter(|job| job.when.is_some()).count();
+        notify(&format!(
+            "READY=1\nSTATUS={} jobs loaded, {} scheduled",
+            self.jobs.len(),
+            scheduled
+        ));
     }
     pub fn start(&mut self, verbose: bool) {
         // Self::prepare_logging(verbose);
@@ -78,7 +170,8 @@ impl AutoPilot {
         for mut job in self.jobs.clone() {
             let scheduler = self.scheduler.clone();
             handles.push(tokio::task::spawn(async move {
-                job.run(&scheduler, false).await;
+                let context = JobLogContext::new(&job);
+                with_job_context(context, job.run(&scheduler, false)).await;
             }))
         }
 
@@ -86,6 +179,15 @@ impl AutoPilot {
     }
     pub fn load_jobs(&mut self) {
         self.jobs = get_jobs(false);
+        for job in &self.jobs {
+            emit_for_job(
+                &job.id,
+                EventKind::JobLoaded {
+                    name: job.name.clone(),
+                    scheduled: job.when.is_some(),
+                },
+            );
+        }
     }
 
     /// Stop all jobs (graceful shutdown of scheduler)
@@ -97,6 +199,7 @@ impl AutoPilot {
             handle.abort();
         });
         self.scheduler.shutdown().await?;
+        clear_scheduled();
         self.jobs = vec![];
         self.jobs_handles = vec![];
 
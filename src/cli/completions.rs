@@ -0,0 +1,93 @@
+//! Shell completions. The script `completions <shell>` prints calls back into autopilot-rs with
+//! `COMPLETE` set, so job ids are read from the jobs directory at the time they are completed.
+
+use std::path::Path;
+
+use clap_complete::{
+    engine::CompletionCandidate,
+    env::{CompleteEnv, Shells},
+};
+
+use crate::{
+    config::{ConfigOverrides, init_config},
+    fs::{get_jobs_path, set_autopilot_path},
+    job::get::load_job_schemes,
+};
+
+/// Environment variable the shell sets when it asks for completions
+const COMPLETE_VAR: &str = "COMPLETE";
+const BIN: &str = "autopilot-rs";
+pub const SHELLS: [&str; 5] = ["bash", "zsh", "fish", "powershell", "elvish"];
+
+/// Answers a completion request from the shell and exits, does nothing on a regular run
+pub fn complete_if_requested(command: fn() -> clap::Command) {
+    CompleteEnv::with_factory(command)
+        .var(COMPLETE_VAR)
+        .bin(BIN)
+        .complete();
+}
+
+/// Prints the script that registers completions with `shell`
+pub fn completions(shell: &str) {
+    let shells = Shells::builtins();
+    let Some(completer) = shells.completer(shell) else {
+        eprintln!("Unknown shell '{}'", shell);
+        std::process::exit(1);
+    };
+    // The script calls this same binary back, wherever it is installed
+    let program = std::env::current_exe()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|_| BIN.to_string());
+    if let Err(e) =
+        completer.write_registration(COMPLETE_VAR, BIN, BIN, &program, &mut std::io::stdout())
+    {
+        eprintln!("Failed to write completions: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Ids of the jobs, with their names as help
+pub fn job_ids() -> Vec<CompletionCandidate> {
+    job_names()
+        .into_iter()
+        .map(|(id, name)| CompletionCandidate::new(id).help(name.map(Into::into)))
+        .collect()
+}
+
+/// Ids and names of the jobs, for commands that take either
+pub fn job_ids_and_names() -> Vec<CompletionCandidate> {
+    job_names()
+        .into_iter()
+        .flat_map(|(id, name)| {
+            let by_name = name
+                .clone()
+                .map(|name| CompletionCandidate::new(name).help(Some(id.clone().into())));
+            std::iter::once(CompletionCandidate::new(id).help(name.map(Into::into))).chain(by_name)
+        })
+        .collect()
+}
+
+fn job_names() -> Vec<(String, Option<String>)> {
+    // Completion runs before the command line is parsed, so --config-path is looked up by hand
+    let args: Vec<String> = std::env::args().collect();
+    let config_path =
+        args.iter()
+            .enumerate()
+            .find_map(|(index, arg)| match arg.strip_prefix("--config-path") {
+                Some("") => args.get(index + 1).cloned(),
+                Some(value) => value.strip_prefix('=').map(str::to_string),
+                None => None,
+            });
+    if set_autopilot_path(config_path).is_err() || init_config(ConfigOverrides::default()).is_err()
+    {
+        return vec![];
+    }
+    if !Path::new(&get_jobs_path()).is_dir() {
+        return vec![];
+    }
+    load_job_schemes()
+        .0
+        .into_iter()
+        .map(|(_, scheme)| (scheme.id, scheme.name))
+        .collect()
+}
@@ -1,26 +1,189 @@
 use chrono::{NaiveDate, NaiveTime};
+use clap::{ArgGroup, Args};
 use colored::Colorize;
 use dialoguer::Editor;
 use dialoguer::{Confirm, Input, Select, theme::ColorfulTheme};
+use serde_json::{Map, Value, json};
+use std::io::Read;
 use std::path::PathBuf;
 use strum::IntoEnumIterator;
 
 use crate::conditions::{Condition, ConditionScheme};
 use crate::cross_platform::get::get_supported_editors;
 use crate::error::AutoPilotError;
-use crate::job::set::add_job;
+use crate::job::JobScheme;
+use crate::job::set::{add_job, add_job_scheme, new_job_id, validate_job_id};
 use crate::task::TaskScheme;
-use crate::time::{DateTimeScheme, TimeScheme, When};
+use crate::time::{DateTimeScheme, TimeScheme, When, next_run, parse_cron};
+use crate::utilities::jsonc_parser::jsonc_parse;
 
-pub fn create() {
-    match create_interactive() {
+/// Where `create` takes the job from; without any of these it asks interactively
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("flags").multiple(true)))]
+pub struct CreateArgs {
+    /// Read the job from a JSON or JSONC file
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["stdin", "flags"])]
+    from_file: Option<PathBuf>,
+    /// Read the job from standard input
+    #[arg(long, conflicts_with = "flags")]
+    stdin: bool,
+    #[arg(long, group = "flags")]
+    name: Option<String>,
+    #[arg(long, group = "flags")]
+    description: Option<String>,
+    /// Cron expression with seconds in UTC, e.g. "0 30 9 * * *" for 9:30 UTC every day
+    #[arg(long, group = "flags")]
+    cron: Option<String>,
+    /// Milliseconds between checks of unsatisfied conditions
+    #[arg(long, value_name = "MS", group = "flags")]
+    check_interval: Option<String>,
+    /// Condition as `type:field=value,field=value`, e.g. `wifi:ssid=Home`, can be repeated
+    #[arg(long = "condition", value_name = "CONDITION", group = "flags")]
+    conditions: Vec<String>,
+    /// Command to run, can be repeated
+    #[arg(long = "task", value_name = "COMMAND", group = "flags")]
+    tasks: Vec<String>,
+    /// Tag of the job, can be repeated
+    #[arg(long = "tag", group = "flags")]
+    tags: Vec<String>,
+}
+
+pub fn create(args: &CreateArgs) {
+    let result = if let Some(path) = &args.from_file {
+        std::fs::read_to_string(path)
+            .map_err(|e| {
+                AutoPilotError::InvalidJob(format!("Failed to read {}: {}", path.display(), e))
+            })
+            .and_then(|content| create_from_jsonc(&content))
+    } else if args.stdin {
+        let mut content = String::new();
+        std::io::stdin()
+            .read_to_string(&mut content)
+            .map_err(|e| AutoPilotError::InvalidJob(format!("Failed to read stdin: {}", e)))
+            .and_then(|_| create_from_jsonc(&content))
+    } else if args.name.is_some()
+        || args.description.is_some()
+        || args.cron.is_some()
+        || args.check_interval.is_some()
+        || !args.conditions.is_empty()
+        || !args.tasks.is_empty()
+        || !args.tags.is_empty()
+    {
+        create_from_flags(args)
+    } else {
+        create_interactive()
+    };
+    match result {
         Ok(job_file_path) => {
             println!("Job created successfully at: {}", job_file_path.display());
         }
         Err(e) => {
             eprintln!("Failed to create job: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Checks a job the way the daemon will load it, pointing at the field that is wrong
+fn parse_job(json: &str) -> Result<JobScheme, AutoPilotError> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let job: JobScheme = serde_path_to_error::deserialize(&mut deserializer)
+        .map_err(|e| AutoPilotError::InvalidJob(format!("{}: {}", e.path(), e.inner())))?;
+    validate_job_id(&job.id)?;
+    if job.tasks.is_empty() {
+        return Err(AutoPilotError::InvalidJob(
+            "tasks: at least one task is required".to_string(),
+        ));
+    }
+    if let Some(when) = &job.when {
+        next_run(when).map_err(|e| AutoPilotError::InvalidJob(format!("when: {}", e)))?;
+    }
+    Ok(job)
+}
+
+fn create_from_jsonc(content: &str) -> Result<PathBuf, AutoPilotError> {
+    add_job_scheme(&parse_job(&jsonc_parse(content))?)
+}
+
+fn create_from_flags(args: &CreateArgs) -> Result<PathBuf, AutoPilotError> {
+    let conditions = args
+        .conditions
+        .iter()
+        .map(|condition| parse_condition(condition))
+        .collect::<Result<Vec<_>, _>>()?;
+    let job = json!({
+        "id": new_job_id(),
+        "name": args.name,
+        "description": args.description,
+        "tags": args.tags,
+        "when": args.cron.as_ref().map(|cron| json!({ "type": "cron", "trigger": cron })),
+        "check_interval": args.check_interval,
+        "conditions": conditions,
+        "tasks": args.tasks.iter().map(|command| json!({ "command": command })).collect::<Vec<_>>(),
+    });
+    add_job_scheme(&parse_job(&job.to_string())?)
+}
+
+/// Turns `wifi:ssid=Home,other=1` into a condition object. Values that are valid JSON, like
+/// numbers and booleans, are used as they are unless the field wants a string, so `ssid=123`
+/// still names a network
+fn parse_condition(condition: &str) -> Result<Value, AutoPilotError> {
+    let invalid = |message: String| {
+        AutoPilotError::InvalidJob(format!("--condition '{}': {}", condition, message))
+    };
+    let (kind, fields) = condition.split_once(':').unwrap_or((condition, ""));
+    let mut parsed: Vec<(String, String, Option<Value>)> = vec![];
+    for field in fields.split(',').filter(|field| !field.trim().is_empty()) {
+        let (key, value) = field
+            .split_once('=')
+            .ok_or_else(|| invalid(format!("expected field=value, got '{}'", field)))?;
+        let typed = serde_json::from_str::<Value>(value.trim())
+            .ok()
+            .filter(|value| !value.is_string());
+        parsed.push((key.trim().to_string(), value.trim().to_string(), typed));
+    }
+    // Conditions have a handful of fields, so trying every typed/string mix is cheap
+    let typed_fields = parsed
+        .iter()
+        .filter(|field| field.2.is_some())
+        .count()
+        .min(8);
+    let mut first_error = None;
+    for strings in 0..(1u32 << typed_fields) {
+        let mut object = Map::new();
+        let mut typed_index = 0;
+        for (key, raw, typed) in &parsed {
+            let value = match typed {
+                Some(typed) => {
+                    let as_string = strings & (1 << typed_index) != 0;
+                    typed_index += 1;
+                    if as_string {
+                        Value::String(raw.clone())
+                    } else {
+                        typed.clone()
+                    }
+                }
+                None => Value::String(raw.clone()),
+            };
+            object.insert(key.clone(), value);
+        }
+        let value = json!({ "type": kind.trim(), "condition": object });
+        // Checked on its own so the error names the flag instead of `conditions[2]`
+        match serde_path_to_error::deserialize::<_, ConditionScheme>(&value) {
+            Ok(_) => return Ok(value),
+            Err(e) if first_error.is_none() => first_error = Some(e),
+            Err(_) => {}
         }
     }
+    let error = first_error.expect("the loop runs at least once");
+    let path = error.path().to_string();
+    let message = error.into_inner().to_string();
+    // The fields of a condition are buffered while its type is read, so errors about them
+    // have no path of their own, but the message names the field already
+    Err(match path.trim_start_matches("condition.") {
+        "." | "condition" => invalid(message),
+        path => invalid(format!("{}: {}", path, message)),
+    })
 }
 
 fn create_interactive() -> Result<PathBuf, AutoPilotError> {
@@ -101,7 +264,7 @@ fn create_interactive() -> Result<PathBuf, AutoPilotError> {
             1..=4 => {
                 // Daily, Weekly, Monthly, Yearly (all use TimeScheme)
                 let time_input: String = Input::with_theme(&ColorfulTheme::default())
-                    .with_prompt("Enter time of day (HH:MM):")
+                    .with_prompt("Enter time of day in UTC (HH:MM):")
                     .interact_text()
                     .map_err(|err| {
                         AutoPilotError::InvalidJob(format!("Failed to get time: {}", err))
@@ -124,7 +287,7 @@ fn create_interactive() -> Result<PathBuf, AutoPilotError> {
             5 => {
                 // Cron Expression
                 let cron_exp: String = Input::with_theme(&ColorfulTheme::default())
-                    .with_prompt("Enter cron expression (e.g., 0 30 9 * * * for 9:30 AM daily):")
+                    .with_prompt("Enter cron expression (UTC, e.g., 0 30 9 * * * for 9:30 AM UTC daily):")
                     .interact_text()
                     .map_err(|err| {
                         AutoPilotError::InvalidJob(format!(
@@ -133,12 +296,13 @@ fn create_interactive() -> Result<PathBuf, AutoPilotError> {
                         ))
                     })?;
 
-                // Basic validation, actual validation is done during job creation
-                if cron_exp.split_whitespace().count() < 5 {
-                    return Err(AutoPilotError::InvalidJob(
-                        "Cron expression must have at least 5 fields (minute hour day-of-month month day-of-week)".to_string(),
-                    ));
-                }
+                // Same parser as the scheduler, which also wants the seconds field
+                parse_cron(&cron_exp).map_err(|e| {
+                    AutoPilotError::InvalidJob(format!(
+                        "{}, it needs 6 fields (second minute hour day-of-month month day-of-week)",
+                        e
+                    ))
+                })?;
 
                 Some(When::Cron(cron_exp))
             }
@@ -272,3 +436,38 @@ fn create_interactive() -> Result<PathBuf, AutoPilotError> {
         tasks,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_condition_mini_syntax() {
+        let condition = parse_condition("power:threshold=20, check_charging=true").unwrap();
+        assert_eq!(
+            condition,
+            json!({ "type": "power", "condition": { "threshold": 20, "check_charging": true } })
+        );
+        let condition = parse_condition("wifi:ssid=123").unwrap();
+        assert_eq!(condition["condition"]["ssid"], "123");
+        assert!(parse_condition("wifi:ssid=Home").is_ok());
+        assert!(parse_condition("wifi:sid=Home").is_err());
+    }
+
+    #[test]
+    fn test_parse_job_reports_invalid_fields() {
+        let job = |id: &str, tasks: Value, when: Value| {
+            json!({ "id": id, "when": when, "conditions": [], "tasks": tasks }).to_string()
+        };
+        let error = |json: String| parse_job(&json).unwrap_err().to_string();
+        let task = json!([{ "command": "true" }]);
+
+        assert!(parse_job(&job("backup-1", task.clone(), Value::Null)).is_ok());
+        let bad_id = error(job("../../backup", task.clone(), Value::Null));
+        assert!(bad_id.contains("id: '../../backup' may only contain"));
+        let no_tasks = error(job("backup", json!([]), Value::Null));
+        assert!(no_tasks.contains("tasks: at least one task is required"));
+        let five_fields = json!({ "type": "cron", "trigger": "0 0 * * *" });
+        assert!(error(job("backup", task, five_fields)).contains("when: "));
+    }
+}
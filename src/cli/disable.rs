@@ -0,0 +1,21 @@
+use colored::Colorize;
+
+use crate::{cli::edit::reload_in_daemon, job::set::set_job_enabled};
+
+pub async fn disable(id: String) {
+    match set_job_enabled(id.clone(), false) {
+        Ok(job_file_path) => {
+            println!(
+                "Job {} disabled at: {}",
+                id.yellow(),
+                job_file_path.display()
+            );
+        }
+        Err(e) => {
+            eprintln!("Failed to disable job: {}", e);
+            std::process::exit(1);
+        }
+    }
+    // Otherwise the daemon keeps running the job until it next loads jobs
+    reload_in_daemon(&[id], true).await;
+}
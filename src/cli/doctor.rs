@@ -0,0 +1,603 @@
+//! `doctor` checks what AutoPilot needs from the machine: its directories, the programs and files
+//! conditions read on this platform, the API address and the daemon. Conditions whose program is
+//! missing silently evaluate to false, which is what this is meant to catch.
+
+use std::{
+    fs::OpenOptions,
+    io::ErrorKind,
+    net::TcpListener,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use display_info::DisplayInfo;
+use serde::Serialize;
+
+use crate::{
+    api::client::ApiClient,
+    cli::{
+        output::{Output, Table},
+        status::check_if_running,
+    },
+    conditions::{ConditionScheme, desktop_env_condition::get_current_de},
+    config::{ConfigOverrides, ListenAddress, LogSink, get_config, init_config},
+    cross_platform::get::is_installed,
+    fs::{
+        get_autopilot_path, get_config_path, get_history_path, get_jobs_path, get_logs_path,
+        get_status_path, set_autopilot_path,
+    },
+    job::{JobScheme, get::load_job_schemes},
+    logging::{journald_writer, syslog_writer},
+    task::runner::shell_command_line,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Outcome {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Serialize)]
+struct Check {
+    area: &'static str,
+    check: String,
+    outcome: Outcome,
+    detail: String,
+}
+
+#[derive(Serialize)]
+struct Report {
+    checks: Vec<Check>,
+    passed: usize,
+    warnings: usize,
+    failures: usize,
+}
+
+/// Something a condition reads on this platform
+#[derive(Clone, Copy)]
+enum Need {
+    Program(&'static str),
+    File(&'static str),
+}
+
+/// A condition backend, working when any one of `needs` is present
+struct Backend {
+    condition: &'static str,
+    uses: fn(&ConditionScheme) -> bool,
+    needs: &'static [Need],
+}
+
+fn is_wifi(condition: &ConditionScheme) -> bool {
+    matches!(condition, ConditionScheme::Wifi(_))
+}
+
+fn is_bluetooth(condition: &ConditionScheme) -> bool {
+    matches!(condition, ConditionScheme::Bluetooth(_))
+}
+
+fn is_internet(condition: &ConditionScheme) -> bool {
+    matches!(condition, ConditionScheme::Internet(_))
+}
+
+fn is_power(condition: &ConditionScheme) -> bool {
+    matches!(condition, ConditionScheme::Power(_))
+}
+
+#[cfg(target_os = "linux")]
+fn is_charging(condition: &ConditionScheme) -> bool {
+    matches!(condition, ConditionScheme::Power(power) if power.check_charging == Some(true))
+}
+
+#[cfg(target_os = "linux")]
+fn is_battery_level(condition: &ConditionScheme) -> bool {
+    is_power(condition) && !is_charging(condition)
+}
+
+#[cfg(target_os = "linux")]
+const BACKENDS: &[Backend] = &[
+    Backend {
+        condition: "wifi",
+        uses: is_wifi,
+        needs: &[Need::Program("nmcli"), Need::Program("iwgetid")],
+    },
+    Backend {
+        condition: "bluetooth",
+        uses: is_bluetooth,
+        needs: &[Need::Program("bluetoothctl"), Need::Program("hcitool")],
+    },
+    Backend {
+        condition: "internet",
+        uses: is_internet,
+        needs: &[Need::Program("ping")],
+    },
+    Backend {
+        condition: "power (charging)",
+        uses: is_charging,
+        needs: &[
+            Need::File("/sys/class/power_supply/AC/online"),
+            Need::File("/sys/class/power_supply/ACAD/online"),
+        ],
+    },
+    Backend {
+        condition: "power (battery level)",
+        uses: is_battery_level,
+        needs: &[Need::File("/sys/class/power_supply/BAT0/capacity")],
+    },
+];
+
+#[cfg(target_os = "macos")]
+const BACKENDS: &[Backend] = &[
+    Backend {
+        condition: "wifi",
+        uses: is_wifi,
+        needs: &[
+            Need::Program("networksetup"),
+            Need::File(
+                "/System/Library/PrivateFrameworks/Apple80211.framework/Versions/Current/Resources/airport",
+            ),
+        ],
+    },
+    Backend {
+        condition: "bluetooth",
+        uses: is_bluetooth,
+        needs: &[Need::Program("system_profiler"), Need::Program("defaults")],
+    },
+    Backend {
+        condition: "internet",
+        uses: is_internet,
+        needs: &[Need::Program("ping")],
+    },
+    Backend {
+        condition: "power",
+        uses: is_power,
+        needs: &[Need::Program("pmset")],
+    },
+];
+
+#[cfg(target_os = "windows")]
+const BACKENDS: &[Backend] = &[
+    Backend {
+        condition: "wifi",
+        uses: is_wifi,
+        needs: &[Need::Program("netsh")],
+    },
+    Backend {
+        condition: "bluetooth",
+        uses: is_bluetooth,
+        needs: &[Need::Program("powershell")],
+    },
+    Backend {
+        condition: "internet",
+        uses: is_internet,
+        needs: &[Need::Program("ping")],
+    },
+    Backend {
+        condition: "power",
+        uses: is_power,
+        needs: &[Need::Program("powershell")],
+    },
+];
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+const BACKENDS: &[Backend] = &[];
+
+/// Conditions that only use the crate's own system information
+const BUILT_IN: &str = "process, resource, diskspace, externaldevice, file, variable";
+
+/// Prints a pass/warn/fail report, exiting with 1 when something fails
+pub async fn doctor(config_path: Option<String>, output: Output) {
+    let checks = run_checks(config_path).await;
+    let count = |outcome| checks.iter().filter(|c| c.outcome == outcome).count();
+    let report = Report {
+        passed: count(Outcome::Pass),
+        warnings: count(Outcome::Warn),
+        failures: count(Outcome::Fail),
+        checks,
+    };
+
+    let printed = output.print(&report, || {
+        Table::new(&["AREA", "CHECK", "RESULT", "DETAIL"]).rows(report.checks.iter().map(|check| {
+            vec![
+                check.area.to_string(),
+                check.check.clone(),
+                format!("{:?}", check.outcome).to_lowercase(),
+                check.detail.clone(),
+            ]
+        }))
+    });
+    if !printed {
+        print_report(&report);
+    }
+    if report.failures > 0 {
+        std::process::exit(1);
+    }
+}
+
+fn print_report(report: &Report) {
+    let mut area = "";
+    for check in &report.checks {
+        if check.area != area {
+            area = check.area;
+            println!("{}", area.bold());
+        }
+        let outcome = match check.outcome {
+            Outcome::Pass => "pass".green(),
+            Outcome::Warn => "warn".yellow(),
+            Outcome::Fail => "fail".red(),
+        };
+        println!("  {} {:<24} {}", outcome, check.check, check.detail);
+    }
+    println!(
+        "\n{} passed, {} warnings, {} failed",
+        report.passed, report.warnings, report.failures
+    );
+}
+
+async fn run_checks(config_path: Option<String>) -> Vec<Check> {
+    let mut checks = vec![];
+    let mut check = |area, name: &str, outcome, detail: String| {
+        checks.push(Check {
+            area,
+            check: name.to_string(),
+            outcome,
+            detail,
+        })
+    };
+
+    // Setup is done here rather than before the command, so a broken configuration is reported
+    // instead of ending the program
+    if let Err(e) = set_autopilot_path(config_path) {
+        check("directories", "autopilot", Outcome::Fail, e.to_string());
+    }
+    let config = init_config(ConfigOverrides::default());
+    match &config {
+        Ok(()) if !Path::new(&get_config_path()).exists() => check(
+            "configuration",
+            "autopilot.jsonc",
+            Outcome::Pass,
+            "missing, the defaults apply".to_string(),
+        ),
+        Ok(()) => check(
+            "configuration",
+            "autopilot.jsonc",
+            Outcome::Pass,
+            get_config_path(),
+        ),
+        Err(e) => check(
+            "configuration",
+            "autopilot.jsonc",
+            Outcome::Fail,
+            format!("{}, the defaults are used below", e),
+        ),
+    }
+
+    for (name, path) in [
+        ("autopilot", get_autopilot_path()),
+        ("jobs", get_jobs_path()),
+        ("logs", get_logs_path()),
+    ] {
+        let (outcome, detail) = check_dir(Path::new(&path));
+        check("directories", name, outcome, detail);
+    }
+    for (name, path) in [
+        ("status.jsonc", get_status_path()),
+        ("history.jsonl", get_history_path()),
+    ] {
+        let (outcome, detail) = check_file(Path::new(&path));
+        check("directories", name, outcome, detail);
+    }
+
+    let (schemes, load_errors) = load_job_schemes();
+    let schemes: Vec<JobScheme> = schemes.into_iter().map(|(_, scheme)| scheme).collect();
+    match load_errors.len() {
+        0 => check(
+            "jobs",
+            "job files",
+            Outcome::Pass,
+            format!("{} jobs load", schemes.len()),
+        ),
+        skipped => check(
+            "jobs",
+            "job files",
+            Outcome::Warn,
+            format!(
+                "{} files are skipped, `autopilot-rs validate` shows why",
+                skipped
+            ),
+        ),
+    }
+
+    let shell = shell_command_line("").remove(0);
+    let (outcome, detail) = match is_installed(&shell) {
+        true => (Outcome::Pass, format!("tasks run with {}", shell)),
+        false => (
+            severity(!schemes.is_empty()),
+            format!(
+                "{} is missing, tasks and command conditions can't run",
+                shell
+            ),
+        ),
+    };
+    check("conditions", "shell", outcome, detail);
+    for backend in BACKENDS {
+        let users = users_of(&schemes, backend.uses);
+        let (outcome, detail) = check_backend(backend.needs, &users);
+        check("conditions", backend.condition, outcome, detail);
+    }
+    let users = users_of(&schemes, |c| matches!(c, ConditionScheme::DesktopEnv(_)));
+    let (outcome, detail) = match get_current_de() {
+        Some(desktop) => (Outcome::Pass, format!("detected {}", desktop)),
+        None => (
+            severity(!users.is_empty()),
+            unavailable("no desktop environment detected", &users),
+        ),
+    };
+    check("conditions", "desktopenv", outcome, detail);
+    let users = users_of(&schemes, |c| matches!(c, ConditionScheme::Screen(_)));
+    let (outcome, detail) = match DisplayInfo::all() {
+        Ok(displays) if !displays.is_empty() => {
+            (Outcome::Pass, format!("{} displays found", displays.len()))
+        }
+        Ok(_) => (
+            severity(!users.is_empty()),
+            unavailable("no displays found", &users),
+        ),
+        Err(e) => (
+            severity(!users.is_empty()),
+            unavailable(&format!("displays can't be read ({})", e), &users),
+        ),
+    };
+    check("conditions", "screen", outcome, detail);
+    check(
+        "conditions",
+        "built in",
+        Outcome::Pass,
+        format!("{} need nothing installed", BUILT_IN),
+    );
+
+    let logging = get_config().logging;
+    for sink in &logging.sinks {
+        let (name, result) = match sink {
+            LogSink::File => ("file", Ok(())),
+            LogSink::Stdout => ("stdout", Ok(())),
+            LogSink::Journald => ("journald", journald_writer().map(|_| ())),
+            LogSink::Syslog => ("syslog", syslog_writer(logging.syslog_facility).map(|_| ())),
+        };
+        match result {
+            Ok(()) => check("logging", name, Outcome::Pass, "available".to_string()),
+            Err(e) => check(
+                "logging",
+                name,
+                Outcome::Warn,
+                format!("not available, {}", e),
+            ),
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    let running = if is_installed("pgrep") {
+        let running = check_if_running();
+        match running {
+            true => check("daemon", "process", Outcome::Pass, "running".to_string()),
+            false => check(
+                "daemon",
+                "process",
+                Outcome::Warn,
+                "not running, jobs only run while `autopilot-rs serve` does".to_string(),
+            ),
+        }
+        Some(running)
+    } else {
+        check(
+            "daemon",
+            "process",
+            Outcome::Fail,
+            "pgrep is missing, `status` and `stop` can't find the daemon".to_string(),
+        );
+        None
+    };
+    #[cfg(target_os = "windows")]
+    let running: Option<bool> = None;
+
+    if let Err(e) = ApiClient::from_config() {
+        check("daemon", "api client", Outcome::Fail, e.to_string());
+    }
+    let listen = get_config().api.listen;
+    if listen.is_empty() {
+        check(
+            "daemon",
+            "api",
+            Outcome::Warn,
+            "api.listen is empty, `edit`, `logs --follow` and `tui` can't reach the daemon"
+                .to_string(),
+        );
+    }
+    for address in &listen {
+        // Every address is probed, a daemon holds all of them and not only the first
+        let reachable = match ApiClient::for_address(address.clone()) {
+            Ok(client) => client.is_reachable().await,
+            Err(_) => false,
+        };
+        let (outcome, detail) = check_address(address, reachable, running);
+        check("daemon", &format!("api {}", address), outcome, detail);
+    }
+
+    checks
+}
+
+/// A missing thing only fails the report when an enabled job depends on it
+fn severity(needed: bool) -> Outcome {
+    match needed {
+        true => Outcome::Fail,
+        false => Outcome::Warn,
+    }
+}
+
+/// Ids of the enabled jobs with a condition, however deeply nested, that `uses` matches
+fn users_of(schemes: &[JobScheme], uses: fn(&ConditionScheme) -> bool) -> Vec<String> {
+    fn any(conditions: &[ConditionScheme], uses: fn(&ConditionScheme) -> bool) -> bool {
+        conditions.iter().any(|condition| {
+            uses(condition)
+                || matches!(condition, ConditionScheme::Logical(logical) if any(&logical.conditions, uses))
+        })
+    }
+    schemes
+        .iter()
+        .filter(|scheme| scheme.enabled && any(&scheme.conditions, uses))
+        .map(|scheme| scheme.id.clone())
+        .collect()
+}
+
+fn unavailable(reason: &str, users: &[String]) -> String {
+    match users {
+        [] => format!("{}, no enabled job needs it", reason),
+        _ => format!("{}, needed by {}", reason, users.join(", ")),
+    }
+}
+
+fn check_backend(needs: &[Need], users: &[String]) -> (Outcome, String) {
+    let present = needs.iter().find(|need| match need {
+        Need::Program(program) => is_installed(program),
+        Need::File(path) => Path::new(path).exists(),
+    });
+    match present {
+        Some(Need::Program(program)) => (Outcome::Pass, format!("using {}", program)),
+        Some(Need::File(path)) => (Outcome::Pass, format!("using {}", path)),
+        None => {
+            let names: Vec<&str> = needs
+                .iter()
+                .map(|need| match need {
+                    Need::Program(name) | Need::File(name) => *name,
+                })
+                .collect();
+            (
+                severity(!users.is_empty()),
+                unavailable(&format!("missing {}", names.join(" and ")), users),
+            )
+        }
+    }
+}
+
+/// Whether a directory exists and a file can be created in it
+fn check_dir(path: &Path) -> (Outcome, String) {
+    if !path.exists() {
+        return (
+            Outcome::Warn,
+            format!(
+                "{} is missing, it is created on the next run",
+                path.display()
+            ),
+        );
+    }
+    if !path.is_dir() {
+        return (
+            Outcome::Fail,
+            format!("{} is not a directory", path.display()),
+        );
+    }
+    let probe: PathBuf = path.join(format!(".doctor-{}", std::process::id()));
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            (Outcome::Pass, path.display().to_string())
+        }
+        Err(e) => (
+            Outcome::Fail,
+            format!("{} is not writable: {}", path.display(), e),
+        ),
+    }
+}
+
+/// Whether a file the daemon appends to can be opened for writing, without changing it
+fn check_file(path: &Path) -> (Outcome, String) {
+    if !path.exists() {
+        return (
+            Outcome::Pass,
+            format!("{} is created when needed", path.display()),
+        );
+    }
+    match OpenOptions::new().append(true).open(path) {
+        Ok(_) => (Outcome::Pass, path.display().to_string()),
+        Err(e) => (
+            Outcome::Fail,
+            format!("{} is not writable: {}", path.display(), e),
+        ),
+    }
+}
+
+/// Whether `serve --api` can listen on an address, or the daemon already does
+fn check_address(
+    address: &ListenAddress,
+    reachable: bool,
+    running: Option<bool>,
+) -> (Outcome, String) {
+    if reachable {
+        return (Outcome::Pass, "the daemon answers here".to_string());
+    }
+    let not_serving = match running {
+        Some(true) => "the daemon runs without --api",
+        _ => "free",
+    };
+    match address {
+        ListenAddress::Tcp(address) => match TcpListener::bind(address) {
+            Ok(_) => (Outcome::Pass, not_serving.to_string()),
+            // The daemon may hold it but not answer, e.g. when the API client can't be built
+            Err(e) if e.kind() == ErrorKind::AddrInUse && running == Some(true) => (
+                Outcome::Warn,
+                "in use, probably by the daemon, but it doesn't answer here".to_string(),
+            ),
+            Err(e) if e.kind() == ErrorKind::AddrInUse => (
+                Outcome::Fail,
+                "in use by another program, `serve --api` can't listen here".to_string(),
+            ),
+            Err(e) => (Outcome::Fail, format!("can't listen here: {}", e)),
+        },
+        ListenAddress::Unix(path) if path.exists() => (
+            Outcome::Warn,
+            format!(
+                "{} exists, `serve --api` only replaces it when it is a stale socket",
+                path.display()
+            ),
+        ),
+        ListenAddress::Unix(path) => match path.parent().map(check_dir) {
+            Some((Outcome::Fail, detail)) => (Outcome::Fail, detail),
+            _ => (Outcome::Pass, not_serving.to_string()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_users_of_finds_nested_conditions_of_enabled_jobs() {
+        let scheme = |id: &str, enabled: bool| -> JobScheme {
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "enabled": enabled,
+                "conditions": [
+                    { "type": "logical", "condition": { "operator": "Or", "conditions": [
+                        { "type": "wifi", "condition": { "ssid": "home" } }
+                    ] } }
+                ],
+                "tasks": [{ "command": "true" }]
+            }))
+            .unwrap()
+        };
+        let schemes = vec![scheme("nested", true), scheme("off", false)];
+        assert_eq!(users_of(&schemes, is_wifi), vec!["nested".to_string()]);
+        assert!(users_of(&schemes, is_bluetooth).is_empty());
+    }
+
+    #[test]
+    fn test_an_address_in_use_only_fails_without_a_running_daemon() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = ListenAddress::Tcp(listener.local_addr().unwrap());
+        assert_eq!(check_address(&address, true, Some(true)).0, Outcome::Pass);
+        assert_eq!(check_address(&address, false, Some(true)).0, Outcome::Warn);
+        assert_eq!(check_address(&address, false, None).0, Outcome::Fail);
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use dialoguer::{Confirm, Select, theme::ColorfulTheme};
+
+use crate::{
+    api::client::ApiClient,
+    cli::{output::Output, validate::print_problem},
+    cross_platform::get::get_supported_editors,
+    error::AutoPilotError,
+    job::{
+        get::{get_job_path, get_job_scheme, get_jobs_paths, load_job_schemes},
+        validate::{Problem, validate_files},
+    },
+};
+
+/// Opens a job in an editor, checks it once saved and has a running daemon reload just that job
+pub async fn edit(target: String, output: Output) {
+    if let Err(e) = edit_job(&target, output).await {
+        eprintln!("Failed to edit job: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn edit_job(target: &str, output: Output) -> Result<(), AutoPilotError> {
+    let path = find_job(target)?;
+    let editor = choose_editor(output.interactive())?;
+    let original = std::fs::read_to_string(&path)?;
+    let previous_id = get_job_scheme(&path).ok().map(|scheme| scheme.id);
+
+    loop {
+        tokio::task::block_in_place(|| open_editor(&path, &editor))?;
+        if std::fs::read_to_string(&path)? == original {
+            println!("No changes made to {}", path.display());
+            return Ok(());
+        }
+        let problems = problems_of(&path);
+        if problems.is_empty() {
+            break;
+        }
+        for problem in &problems {
+            print_problem(&path, problem);
+        }
+        let reopen = output.interactive()
+            && Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt("Re-open the editor to fix it?")
+                .default(true)
+                .interact()
+                .map_err(AutoPilotError::Dialoguer)?;
+        if !reopen {
+            return Err(AutoPilotError::InvalidJob(format!(
+                "{} was saved with problems, a running daemon keeps the previous version",
+                path.display()
+            )));
+        }
+    }
+
+    let id = get_job_scheme(&path)?.id;
+    println!("Saved {}", path.display());
+    // A changed id unloads the job under its old one
+    let mut ids = vec![id];
+    ids.extend(previous_id.filter(|previous| *previous != ids[0]));
+    reload_in_daemon(&ids, true).await;
+    Ok(())
+}
+
+/// The file of the job with `target` as its id, or else as its name
+fn find_job(target: &str) -> Result<PathBuf, AutoPilotError> {
+    if let Ok(path) = get_job_path(target) {
+        return Ok(path);
+    }
+    let named: Vec<(PathBuf, String)> = load_job_schemes()
+        .0
+        .into_iter()
+        .filter(|(_, scheme)| {
+            scheme
+                .name
+                .as_deref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(target))
+        })
+        .map(|(path, scheme)| (path, scheme.id))
+        .collect();
+    match named.as_slice() {
+        [] => Err(AutoPilotError::JobNotFound(target.to_string())),
+        [(path, _)] => Ok(path.clone()),
+        _ => {
+            let ids: Vec<&str> = named.iter().map(|(_, id)| id.as_str()).collect();
+            Err(AutoPilotError::InvalidJob(format!(
+                "Several jobs are named '{}', use one of their ids: {}",
+                target,
+                ids.join(", ")
+            )))
+        }
+    }
+}
+
+/// Problems of one job file, including an id another file already uses
+fn problems_of(path: &Path) -> Vec<Problem> {
+    validate_files(&get_jobs_paths())
+        .into_iter()
+        .find(|report| report.path == path)
+        .map(|report| report.problems)
+        .unwrap_or_default()
+}
+
+/// `$VISUAL` or `$EDITOR`, else one of the editors found on the system
+fn choose_editor(interactive: bool) -> Result<String, AutoPilotError> {
+    if let Some(editor) = default_editor() {
+        return Ok(editor);
+    }
+    let editors = get_supported_editors();
+    let choice = match editors.len() {
+        0 => {
+            return Err(AutoPilotError::Command(
+                "No editor found, set $EDITOR to edit jobs".to_string(),
+            ));
+        }
+        1 => 0,
+        _ if !interactive => 0,
+        _ => Select::with_theme(&ColorfulTheme::default())
+            .with_prompt("Choose your desired editor:")
+            .default(0)
+            .items(&editors)
+            .interact()
+            .map_err(AutoPilotError::Dialoguer)?,
+    };
+    Ok(editors[choice].to_string())
+}
+
+/// The editor the user set in `$VISUAL` or `$EDITOR`
+pub fn default_editor() -> Option<String> {
+    std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+}
+
+/// Runs `editor` on `path` and waits for it to exit
+pub fn open_editor(path: &Path, editor: &str) -> Result<(), AutoPilotError> {
+    // $EDITOR may carry arguments, e.g. `code --wait`
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or_default();
+    let status = Command::new(program).args(words).arg(path).status()?;
+    if !status.success() {
+        return Err(AutoPilotError::Command(format!(
+            "{} exited with {}",
+            program, status
+        )));
+    }
+    Ok(())
+}
+
+/// Has a running daemon reload the given jobs, the others keep running undisturbed. Without
+/// `report` only failures are printed, to stderr.
+pub async fn reload_in_daemon(ids: &[String], report: bool) {
+    let client = match ApiClient::from_config() {
+        Ok(Some(client)) if client.is_reachable().await => client,
+        _ => {
+            if report {
+                println!(
+                    "The daemon is not reachable, it picks the changes up when it next loads jobs"
+                );
+            }
+            return;
+        }
+    };
+    for id in ids {
+        let path = format!("/jobs/{}/reload", id);
+        match client.request("POST", &path, None).await {
+            Ok(response) if report => {
+                println!("{}", response["message"].as_str().unwrap_or("Reloaded"))
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("The daemon did not reload {}: {}", id, e),
+        }
+    }
+}
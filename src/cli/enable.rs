@@ -0,0 +1,16 @@
+use colored::Colorize;
+
+use crate::{cli::edit::reload_in_daemon, job::set::set_job_enabled};
+
+pub async fn enable(id: String) {
+    match set_job_enabled(id.clone(), true) {
+        Ok(job_file_path) => {
+            println!("Job {} enabled at: {}", id.green(), job_file_path.display());
+        }
+        Err(e) => {
+            eprintln!("Failed to enable job: {}", e);
+            std::process::exit(1);
+        }
+    }
+    reload_in_daemon(&[id], true).await;
+}
@@ -0,0 +1,166 @@
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+
+use crate::{
+    cli::output::{Output, Table, cell},
+    error::AutoPilotError,
+    job::{
+        explain::{ConditionExplanation, JobExplanation, explain_job},
+        get::{get_job_path, get_job_scheme},
+    },
+};
+
+pub fn explain(target: String, output: Output) {
+    match explain_target(&target) {
+        Ok(explanation) => {
+            let printed = output.print(&explanation, || {
+                let mut rows = vec![];
+                condition_rows(&explanation.conditions, 0, &mut rows);
+                Table::new(&["CONDITION", "SATISFIED", "OBSERVED"]).rows(rows)
+            });
+            if !printed {
+                print_explanation(&explanation);
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to explain job: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Explains a job file, or the job with the given id when no such file exists
+pub fn explain_target(target: &str) -> Result<JobExplanation, AutoPilotError> {
+    let path = if Path::new(target).is_file() {
+        PathBuf::from(target)
+    } else {
+        get_job_path(target)?
+    };
+    let scheme = get_job_scheme(&path)?;
+    Ok(explain_job(&scheme, Some(path)))
+}
+
+fn print_explanation(explanation: &JobExplanation) {
+    println!(
+        "{} : {} ({})",
+        "Job".yellow(),
+        explanation.name.as_deref().unwrap_or("unnamed").green(),
+        explanation.id
+    );
+    if let Some(file) = &explanation.file {
+        println!("{} : {}", "File".yellow(), file.display());
+    }
+    if let Some(description) = &explanation.description {
+        println!("{} : {}", "Description".yellow(), description);
+    }
+    if !explanation.enabled {
+        println!("{}", "This job is disabled and will not be loaded".red());
+    }
+
+    match &explanation.schedule {
+        Some(schedule) => {
+            println!("{} : {}", "Schedule".yellow(), schedule.description);
+            if let Some(cron) = &schedule.cron {
+                println!("  cron expression: {}", cron);
+            }
+            match (&schedule.next_run, &schedule.error) {
+                (_, Some(error)) => println!("  {}", error.red()),
+                (Some(next_run), None) => println!("  next run: {}", next_run),
+                (None, None) => println!("  {}", "the scheduled time is in the past".red()),
+            }
+        }
+        None => println!(
+            "{} : once when AutoPilot starts{}",
+            "Schedule".yellow(),
+            explanation
+                .check_interval
+                .as_ref()
+                .map(|interval| format!(", rechecking conditions every {}ms", interval))
+                .unwrap_or_default()
+        ),
+    }
+
+    println!("{}", "Conditions".yellow());
+    if explanation.conditions.is_empty() {
+        println!("  none, the tasks always run");
+    }
+    for condition in &explanation.conditions {
+        print_condition(condition, 1);
+    }
+
+    if explanation.satisfied {
+        println!("{}", "Tasks (would run now)".yellow());
+    } else {
+        println!("{}", "Tasks (would not run now)".yellow());
+    }
+    for task in &explanation.tasks {
+        let program: Vec<String> = task.program.iter().map(|arg| shell_quote(arg)).collect();
+        println!("  $ {}", program.join(" "));
+    }
+    // Read in this process, the daemon spawning the tasks may have another directory and env
+    if let Some(task) = explanation.tasks.first() {
+        println!(
+            "{}",
+            "Environment of this shell (the daemon's may differ)".yellow()
+        );
+        if let Some(cwd) = &task.caller_cwd {
+            println!("  cwd: {}", cwd.display());
+        }
+        for (key, value) in &task.caller_env {
+            println!("  {}={}", key, value);
+        }
+    }
+}
+
+fn shell_quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains(|c: char| c.is_whitespace() || "'\"$&;|<>()`\\".contains(c))
+    {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+fn print_condition(condition: &ConditionExplanation, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let mark = if condition.satisfied {
+        "✓".green()
+    } else {
+        "✗".red()
+    };
+    let mut line = format!("{}{} {}", indent, mark, condition.name);
+    if let Some(operator) = condition.operator {
+        line += &format!(" {}", operator.name());
+    }
+    if condition
+        .parameters
+        .as_object()
+        .is_some_and(|parameters| !parameters.is_empty())
+    {
+        line += &format!(" {}", condition.parameters);
+    }
+    println!("{}", line);
+    if let Some(observed) = &condition.observed {
+        println!("{}    observed: {}", indent, observed.dimmed());
+    }
+    for child in &condition.children {
+        print_condition(child, depth + 1);
+    }
+}
+
+/// One row per condition, nested ones indented under their logical condition
+fn condition_rows(conditions: &[ConditionExplanation], depth: usize, rows: &mut Vec<Vec<String>>) {
+    for condition in conditions {
+        let name = match condition.operator {
+            Some(operator) => format!("{} {}", condition.name, operator.name()),
+            None => condition.name.clone(),
+        };
+        rows.push(vec![
+            format!("{}{}", "  ".repeat(depth), name),
+            condition.satisfied.to_string(),
+            cell(condition.observed.as_ref()),
+        ]);
+        condition_rows(&condition.children, depth + 1, rows);
+    }
+}
@@ -0,0 +1,26 @@
+use crate::job::bundle::export_jobs;
+
+/// Prints a bundle of the given jobs, or of every job, as JSON
+pub fn export(ids: &[String], strip: bool) {
+    let (bundle, stripped) = match export_jobs(ids, strip) {
+        Ok(exported) => exported,
+        Err(e) => {
+            eprintln!("Failed to export jobs: {}", e);
+            std::process::exit(1);
+        }
+    };
+    match serde_json::to_string_pretty(&bundle) {
+        Ok(json) => println!("{}", json),
+        Err(e) => {
+            eprintln!("Failed to export jobs: {}", e);
+            std::process::exit(1);
+        }
+    }
+    // stdout is usually redirected to the bundle file, so notes go to stderr
+    for id in stripped {
+        eprintln!(
+            "Removed machine-specific conditions from {}, it is exported disabled",
+            id
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use colored::Colorize;
+
+use crate::{
+    cli::output::{Output, Table, cell},
+    status::{HistoryEntry, JobStatusEnum, get::get_history},
+};
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Prints the latest finished runs, newest first
+pub fn history(id: Option<String>, limit: usize, output: Output) {
+    let entries = get_history(id.as_deref(), limit);
+    let printed = output.print(&entries, || {
+        Table::new(&["FINISHED", "JOB", "STATUS", "DURATION", "RUN ID"]).rows(entries.iter().map(
+            |entry| {
+                vec![
+                    entry.run.finished.format(TIME_FORMAT).to_string(),
+                    entry.job_id.clone(),
+                    format!("{:?}", entry.run.status),
+                    duration(entry),
+                    cell(entry.run.run_id.as_ref()),
+                ]
+            },
+        ))
+    });
+    if printed {
+        return;
+    }
+
+    if entries.is_empty() {
+        println!("No runs recorded yet");
+    }
+    for entry in &entries {
+        let status = format!("{:?}", entry.run.status);
+        let status = match entry.run.status {
+            JobStatusEnum::Completed => status.green(),
+            JobStatusEnum::Failed => status.red(),
+            _ => status.yellow(),
+        };
+        println!(
+            "{} {} {} in {}",
+            entry.run.finished.format(TIME_FORMAT),
+            entry.job_id.yellow(),
+            status,
+            duration(entry)
+        );
+    }
+}
+
+fn duration(entry: &HistoryEntry) -> String {
+    let duration = entry.run.finished - entry.run.started;
+    format!("{:.1}s", duration.num_milliseconds() as f64 / 1000.0)
+}
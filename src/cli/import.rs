@@ -0,0 +1,126 @@
+use std::io::Read;
+
+use colored::Colorize;
+use dialoguer::{Select, theme::ColorfulTheme};
+
+use crate::{
+    cli::{
+        edit::reload_in_daemon,
+        output::{Output, Table, cell},
+    },
+    error::AutoPilotError,
+    job::{
+        JobScheme,
+        bundle::{Bundle, ImportAction, Imported, OnConflict, import_jobs},
+    },
+    utilities::jsonc_parser::jsonc_parse,
+};
+
+/// Imports a bundle written by `export`, from a file or `-` for stdin. Without `on_conflict` each
+/// job whose id is taken is asked about, or skipped when prompts are off.
+pub async fn import(file: &str, on_conflict: Option<OnConflict>, strip: bool, output: Output) {
+    let imported = match read_bundle(file)
+        .and_then(|bundle| import_jobs(bundle, strip, |scheme| decide(scheme, on_conflict, output)))
+    {
+        Ok(imported) => imported,
+        Err(e) => {
+            eprintln!("Failed to import jobs: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let printed = output.print(&imported, || {
+        Table::new(&["ID", "ACTION", "RENAMED FROM", "STRIPPED", "MESSAGE"]).rows(
+            imported.iter().map(|job| {
+                vec![
+                    job.id.clone(),
+                    format!("{:?}", job.action).to_lowercase(),
+                    cell(job.renamed_from.as_ref()),
+                    job.stripped.to_string(),
+                    cell(job.message.as_ref()),
+                ]
+            }),
+        )
+    });
+    if !printed {
+        for job in &imported {
+            print_imported(job);
+        }
+    }
+
+    let written: Vec<String> = imported
+        .iter()
+        .filter(|job| !matches!(job.action, ImportAction::Skipped | ImportAction::Failed))
+        .map(|job| job.id.clone())
+        .collect();
+    if !written.is_empty() {
+        reload_in_daemon(&written, output.format.is_none()).await;
+    }
+    if imported
+        .iter()
+        .any(|job| job.action == ImportAction::Failed)
+    {
+        std::process::exit(1);
+    }
+}
+
+fn read_bundle(file: &str) -> Result<Bundle, AutoPilotError> {
+    let content = if file == "-" {
+        let mut content = String::new();
+        std::io::stdin().read_to_string(&mut content)?;
+        content
+    } else {
+        std::fs::read_to_string(file)?
+    };
+    serde_json::from_str(&jsonc_parse(&content))
+        .map_err(|e| AutoPilotError::InvalidJob(format!("{} is not a job bundle: {}", file, e)))
+}
+
+fn decide(
+    scheme: &JobScheme,
+    on_conflict: Option<OnConflict>,
+    output: Output,
+) -> Result<OnConflict, AutoPilotError> {
+    if let Some(on_conflict) = on_conflict {
+        return Ok(on_conflict);
+    }
+    if !output.interactive() {
+        return Ok(OnConflict::Skip);
+    }
+    let choices = [OnConflict::Skip, OnConflict::Overwrite, OnConflict::Rename];
+    let choice = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(format!(
+            "A job with id '{}' already exists, what should be done with the imported one?",
+            scheme.id
+        ))
+        .default(0)
+        .items([
+            "Skip it",
+            "Overwrite the existing job",
+            "Import it under a new id",
+        ])
+        .interact()
+        .map_err(AutoPilotError::Dialoguer)?;
+    Ok(choices[choice])
+}
+
+fn print_imported(job: &Imported) {
+    let action = format!("{:?}", job.action).to_lowercase();
+    let action = match job.action {
+        ImportAction::Created | ImportAction::Overwritten | ImportAction::Renamed => action.green(),
+        ImportAction::Skipped => action.yellow(),
+        ImportAction::Failed => action.red(),
+    };
+    let mut notes = vec![];
+    if let Some(previous) = &job.renamed_from {
+        notes.push(format!("was {}", previous));
+    }
+    if job.stripped {
+        notes.push("machine-specific conditions removed, disabled".to_string());
+    }
+    notes.extend(job.message.clone());
+    match notes.is_empty() {
+        true => println!("{} {}", action, job.id),
+        false => println!("{} {} ({})", action, job.id, notes.join(", ")),
+    }
+}
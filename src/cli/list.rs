@@ -1,23 +1,116 @@
 // use log::info;
 
+use chrono::{DateTime, Local};
 use colored::*;
 use dialoguer::{Confirm, Select, theme::ColorfulTheme};
+use serde::Serialize;
 
 use crate::{
+    cli::output::{Output, OutputFormat, Table, cell},
     error::AutoPilotError,
     fs::get_autopilot_path,
-    job::set::remove_job,
-    status::{get::get_status_log, set::set_status_initial},
+    job::{
+        get::{LoadError, job_entries, load_job_schemes},
+        set::remove_job,
+    },
+    status::{JobStatusEnum, LastRun, get::get_status_log, set::set_status_initial},
+    time::{When, next_run},
 };
 
-pub fn list() {
-    match list_interactive() {
-        Ok(_) => {}
-        Err(err) => eprintln!("Error: {}", err),
+#[derive(Serialize)]
+struct JobList {
+    jobs: Vec<ListedJob>,
+    /// Job files that were skipped, because they don't parse or reuse an id
+    load_errors: Vec<LoadError>,
+}
+
+#[derive(Serialize)]
+struct ListedJob {
+    id: String,
+    name: String,
+    description: Option<String>,
+    status: JobStatusEnum,
+    enabled: bool,
+    tags: Vec<String>,
+    when: Option<When>,
+    next_run: Option<DateTime<Local>>,
+    last_run: Option<LastRun>,
+}
+
+pub fn list(output: Output) {
+    if output.interactive() {
+        if let Err(err) = list_interactive() {
+            eprintln!("Error: {}", err);
+            std::process::exit(1);
+        }
+        return;
     }
+
+    let (schemes, load_errors) = load_job_schemes();
+    let mut jobs: Vec<ListedJob> =
+        job_entries(schemes.into_iter().map(|(_, scheme)| scheme).collect())
+            .into_iter()
+            .map(|entry| ListedJob {
+                name: entry.name(),
+                id: entry.scheme.id,
+                description: entry.scheme.description,
+                status: entry.status,
+                enabled: entry.scheme.enabled,
+                tags: entry.scheme.tags,
+                next_run: entry
+                    .scheme
+                    .when
+                    .as_ref()
+                    .filter(|_| entry.scheme.enabled)
+                    .and_then(|when| next_run(when).ok().flatten()),
+                when: entry.scheme.when,
+                last_run: entry.last_run,
+            })
+            .collect();
+    jobs.sort_by(|a, b| a.id.cmp(&b.id));
+    let list = JobList { jobs, load_errors };
+
+    // Without a format, --no-interactive still lists the jobs instead of opening the menu
+    let output = Output {
+        format: output.format.or(Some(OutputFormat::Table)),
+        ..output
+    };
+    output.print(&list, || {
+        for error in &list.load_errors {
+            eprintln!("{} was skipped: {}", error.path.display(), error.message);
+        }
+        Table::new(&[
+            "ID", "NAME", "STATUS", "ENABLED", "SCHEDULE", "NEXT RUN", "LAST RUN",
+        ])
+        .rows(list.jobs.iter().map(|job| {
+            vec![
+                job.id.clone(),
+                job.name.clone(),
+                format!("{:?}", job.status),
+                job.enabled.to_string(),
+                cell(job.when.as_ref().map(When::describe)),
+                cell(job.next_run.map(|time| time.format("%Y-%m-%d %H:%M:%S"))),
+                cell(job.last_run.as_ref().map(|run| {
+                    format!(
+                        "{:?} {}",
+                        run.status,
+                        run.finished.format("%Y-%m-%d %H:%M:%S")
+                    )
+                })),
+            ]
+        }))
+    });
 }
 
 pub fn list_interactive() -> Result<(), AutoPilotError> {
+    for error in load_job_schemes().1 {
+        eprintln!(
+            "{} {} was skipped: {}",
+            "Warning:".yellow(),
+            error.path.display(),
+            error.message
+        );
+    }
     loop {
         // set_status_initial().expect("failed to reset status");
         let status_log = get_status_log();
@@ -0,0 +1,265 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone};
+use clap::Args;
+use clap_complete::engine::ArgValueCandidates;
+use colored::{ColoredString, Colorize};
+
+use crate::{
+    api::client::ApiClient,
+    autopilot::events::Event,
+    cli::{
+        completions::job_ids,
+        output::{Output, OutputFormat, Table, cell},
+    },
+    error::AutoPilotError,
+    logging::read::{LEVELS, LogRecord, all_records},
+};
+
+/// Which log records `logs` shows
+#[derive(Args, Debug)]
+pub struct LogsArgs {
+    /// Only records of this job
+    #[arg(long, add = ArgValueCandidates::new(job_ids))]
+    job: Option<String>,
+    /// Only records of this run, by the run id the log and `history` show
+    #[arg(long)]
+    run: Option<String>,
+    /// Records from this long ago (`30s`, `10m`, `1h`, `2d`) or since a local time
+    /// (`2026-10-18` or `2026-10-18 08:00`)
+    #[arg(long)]
+    since: Option<String>,
+    /// Least severe level to show: trace, debug, info, warn or error
+    #[arg(long)]
+    level: Option<String>,
+    /// Number of the most recent records to show
+    #[arg(short = 'n', long, default_value_t = 100)]
+    lines: usize,
+    /// Keep following the running daemon's events after the records
+    #[arg(short, long)]
+    follow: bool,
+}
+
+pub async fn logs(args: &LogsArgs, output: Output) {
+    if let Err(e) = show_logs(args, output).await {
+        eprintln!("Failed to read logs: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn show_logs(args: &LogsArgs, output: Output) -> Result<(), AutoPilotError> {
+    let since = args
+        .since
+        .as_deref()
+        .map(|since| parse_since(since, Local::now()))
+        .transpose()?;
+    let severity = args
+        .level
+        .as_deref()
+        .map(|level| {
+            LEVELS
+                .iter()
+                .position(|known| known.eq_ignore_ascii_case(level))
+                .ok_or_else(|| {
+                    AutoPilotError::Command(format!(
+                        "Unknown level '{}', expected one of: {}",
+                        level,
+                        LEVELS.join(", ").to_lowercase()
+                    ))
+                })
+        })
+        .transpose()?;
+    // Connect before printing anything, so a missing daemon fails early
+    let client = if args.follow {
+        match ApiClient::from_config()? {
+            Some(client) if client.is_reachable().await => Some(client),
+            _ => {
+                return Err(AutoPilotError::Api(
+                    "The daemon is not reachable, --follow needs it running with the API"
+                        .to_string(),
+                ));
+            }
+        }
+    } else {
+        None
+    };
+
+    let mut records: Vec<LogRecord> = all_records()
+        .into_iter()
+        .filter(|record| args.job.is_none() || record.job_id == args.job)
+        .filter(|record| args.run.is_none() || record.run_id == args.run)
+        .filter(|record| severity.is_none_or(|min| record.severity().is_some_and(|s| s >= min)))
+        .filter(|record| since.is_none_or(|since| record.time().is_some_and(|time| time >= since)))
+        .collect();
+    records.drain(..records.len().saturating_sub(args.lines));
+
+    let printed = match (args.follow, output.format) {
+        // One object per line, so the events that follow can be appended
+        (true, Some(OutputFormat::Json)) => {
+            for record in &records {
+                println!("{}", serde_json::to_string(record).unwrap_or_default());
+            }
+            true
+        }
+        _ => output.print(&records, || {
+            Table::new(&["TIME", "LEVEL", "JOB", "RUN", "MESSAGE"]).rows(records.iter().map(
+                |record| {
+                    vec![
+                        local_time(record),
+                        record.level.clone(),
+                        cell(record.job_id.as_ref()),
+                        cell(record.run_id.as_ref()),
+                        record.message.replace('\n', " "),
+                    ]
+                },
+            ))
+        }),
+    };
+    if !printed {
+        for record in &records {
+            print_record(record);
+        }
+    }
+
+    if let Some(client) = client {
+        follow(&client, args, output).await?;
+    }
+    Ok(())
+}
+
+fn print_record(record: &LogRecord) {
+    let context = match (&record.job_id, &record.run_id) {
+        (Some(job), Some(run)) => format!(" [{} {}]", job, run),
+        (Some(job), None) => format!(" [{}]", job),
+        _ => String::new(),
+    };
+    println!(
+        "{} {}{} {}",
+        local_time(record).dimmed(),
+        level(&record.level),
+        context.dimmed(),
+        record.message
+    );
+}
+
+fn level(level: &str) -> ColoredString {
+    let padded = format!("{:<5}", level);
+    match level {
+        "ERROR" => padded.red(),
+        "WARN" => padded.yellow(),
+        "INFO" => padded.green(),
+        _ => padded.dimmed(),
+    }
+}
+
+fn local_time(record: &LogRecord) -> String {
+    match record.time() {
+        Some(time) => time
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string(),
+        None => record.timestamp.clone(),
+    }
+}
+
+/// Prints the daemon's events until it goes away
+async fn follow(client: &ApiClient, args: &LogsArgs, output: Output) -> Result<(), AutoPilotError> {
+    let path = match &args.job {
+        Some(job) => format!("/events?job={}", job),
+        None => "/events".to_string(),
+    };
+    let mut lines = client.lines("GET", &path).await?;
+    while let Some(line) = lines.next_line().await? {
+        let Some(data) = line.strip_prefix("data:") else {
+            continue;
+        };
+        let Ok(event) = serde_json::from_str::<Event>(data.trim_start()) else {
+            continue;
+        };
+        if args.run.is_some() && event.run_id != args.run {
+            continue;
+        }
+        match output.format {
+            Some(OutputFormat::Json) => println!("{}", data.trim_start()),
+            Some(OutputFormat::Yaml) => {
+                print!(
+                    "---\n{}",
+                    serde_yaml_ng::to_string(&event).unwrap_or_default()
+                )
+            }
+            _ => {
+                let context = match (&event.job_id, &event.run_id) {
+                    (Some(job), Some(run)) => format!(" [{} {}]", job, run),
+                    (Some(job), None) => format!(" [{}]", job),
+                    _ => String::new(),
+                };
+                println!(
+                    "{} {}{} {}",
+                    event.time.format("%Y-%m-%d %H:%M:%S").to_string().dimmed(),
+                    "EVENT".cyan(),
+                    context.dimmed(),
+                    event.kind.summary()
+                );
+            }
+        }
+    }
+    Err(AutoPilotError::Api(
+        "The daemon closed the event stream".to_string(),
+    ))
+}
+
+/// `30s`, `10m`, `1h` or `2d` before `now`, or a local date with an optional time
+fn parse_since(since: &str, now: DateTime<Local>) -> Result<DateTime<Local>, AutoPilotError> {
+    let invalid = || {
+        AutoPilotError::Command(format!(
+            "Invalid --since '{}', use e.g. 30s, 10m, 1h, 2d or 2026-10-18 08:00",
+            since
+        ))
+    };
+    let since = since.trim();
+    if let Some(unit) = since.chars().last().filter(char::is_ascii_alphabetic)
+        && let Ok(amount) = since[..since.len() - 1].parse::<i64>()
+    {
+        let duration = match unit {
+            's' => Duration::seconds(amount),
+            'm' => Duration::minutes(amount),
+            'h' => Duration::hours(amount),
+            'd' => Duration::days(amount),
+            _ => return Err(invalid()),
+        };
+        return Ok(now - duration);
+    }
+    let naive = NaiveDateTime::parse_from_str(since, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(since, "%Y-%m-%d %H:%M"))
+        .or_else(|_| {
+            NaiveDate::parse_from_str(since, "%Y-%m-%d")
+                .map(|date| date.and_hms_opt(0, 0, 0).unwrap_or_default())
+        })
+        .map_err(|_| invalid())?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_since_takes_durations_and_times() {
+        let now = Local.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap();
+        assert_eq!(
+            parse_since("90m", now).unwrap(),
+            Local.with_ymd_and_hms(2026, 10, 18, 10, 30, 0).unwrap()
+        );
+        assert_eq!(
+            parse_since("2d", now).unwrap(),
+            Local.with_ymd_and_hms(2026, 10, 16, 12, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse_since("2026-10-18 08:00", now).unwrap(),
+            Local.with_ymd_and_hms(2026, 10, 18, 8, 0, 0).unwrap()
+        );
+        assert!(parse_since("soon", now).is_err());
+        assert!(parse_since("3w", now).is_err());
+    }
+}
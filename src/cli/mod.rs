@@ -1,16 +1,60 @@
-use clap::{Parser, Subcommand};
+use std::str::FromStr;
+
+use clap::{CommandFactory, Parser, Subcommand, builder::PossibleValuesParser};
+use clap_complete::engine::ArgValueCandidates;
 
 use crate::{
-    cli::{create::create, list::list, remove::remove, serve::serve, status::status, stop::stop},
+    cli::{
+        completions::{SHELLS, complete_if_requested, completions, job_ids, job_ids_and_names},
+        create::{CreateArgs, create},
+        disable::disable,
+        doctor::doctor,
+        edit::edit,
+        enable::enable,
+        explain::explain,
+        export::export,
+        history::history,
+        import::import,
+        list::list,
+        logs::{LogsArgs, logs},
+        next::next,
+        output::{Output, OutputFormat},
+        remove::remove,
+        run::run,
+        serve::serve,
+        service::{ServiceCommands, service},
+        status::status,
+        stop::stop,
+        tui::tui,
+        validate::validate,
+    },
+    config::{ConfigOverrides, ListenAddress, init_config},
     fs::{set_all_paths, set_autopilot_path},
+    job::bundle::OnConflict,
 };
 
+pub mod completions;
 pub mod create;
+pub mod disable;
+pub mod doctor;
+pub mod edit;
+pub mod enable;
+pub mod explain;
+pub mod export;
+pub mod history;
+pub mod import;
 pub mod list;
+pub mod logs;
+pub mod next;
+pub mod output;
 pub mod remove;
+pub mod run;
 pub mod serve;
+pub mod service;
 pub mod status;
 pub mod stop;
+pub mod tui;
+pub mod validate;
 
 #[derive(Parser)]
 #[command(name = "AutoPilot-rs")]
@@ -23,6 +67,14 @@ struct Cli {
     #[arg(short, long)]
     verbose: bool,
 
+    /// Print results as JSON, YAML or a plain table, for scripts
+    #[arg(long, global = true, value_enum)]
+    output: Option<OutputFormat>,
+
+    /// Never prompt, commands that would ask take their answers from arguments
+    #[arg(long, global = true)]
+    no_interactive: bool,
+
     /// Subcommand
     #[command(subcommand)]
     command: Option<Commands>,
@@ -34,50 +86,220 @@ enum Commands {
     Serve {
         #[arg(long, default_value_t = false)]
         api: bool,
+        /// Address the API listens on (IP:port or unix:<path>), can be repeated, overrides `api.listen`
+        #[arg(long, value_parser = ListenAddress::from_str)]
+        listen: Vec<ListenAddress>,
+        /// Log level, overrides `logging.level`
+        #[arg(long)]
+        log_level: Option<String>,
     },
     /// Stop AutoPilot-rs
     Stop,
-    /// Create a new Job
-    Create,
+    /// Create a new Job, interactively or from flags, a file or stdin
+    Create(CreateArgs),
     /// Remove a Job
-    Remove,
+    Remove {
+        /// Id of the job to remove, picked from a menu when left out
+        #[arg(add = ArgValueCandidates::new(job_ids))]
+        id: Option<String>,
+    },
     /// List Jobs
     List,
     /// Status of AutoPilot-rs
     Status,
+    /// Show the latest finished runs, newest first
+    History {
+        /// Only show runs of this job
+        #[arg(add = ArgValueCandidates::new(job_ids))]
+        id: Option<String>,
+        /// Number of runs to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Show when scheduled jobs run next
+    Next,
+    /// Show the daemon and job logs, and follow the daemon's events
+    Logs(LogsArgs),
+    /// Open a Job in your editor, check it once saved and reload it in the daemon
+    Edit {
+        /// Id or name of the job to edit
+        #[arg(add = ArgValueCandidates::new(job_ids_and_names))]
+        job: String,
+    },
+    /// Enable a Job
+    Enable {
+        /// Id of the job to enable
+        #[arg(add = ArgValueCandidates::new(job_ids))]
+        id: String,
+    },
+    /// Disable a Job without removing it
+    Disable {
+        /// Id of the job to disable
+        #[arg(add = ArgValueCandidates::new(job_ids))]
+        id: String,
+    },
+    /// Run a Job right now
+    Run {
+        /// Id of the job to run
+        #[arg(add = ArgValueCandidates::new(job_ids))]
+        id: String,
+        /// Skip the conditions and run the tasks unconditionally
+        #[arg(long, conflicts_with = "dry_run")]
+        force: bool,
+        /// Only evaluate the conditions, without running any task
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Explain what a Job would do, without running it
+    Explain {
+        /// Path of a job file or id of a job
+        #[arg(add = ArgValueCandidates::new(job_ids))]
+        job: String,
+    },
+    /// Check job files for mistakes, exiting with 1 if any are found
+    Validate {
+        /// Job files or directories of them, the jobs directory when none are given
+        paths: Vec<String>,
+    },
+    /// Print a bundle of jobs as JSON, to move them to another machine with `import`
+    Export {
+        /// Ids of the jobs to export, every job when left out
+        #[arg(long, num_args = 1.., value_delimiter = ',', add = ArgValueCandidates::new(job_ids))]
+        ids: Vec<String>,
+        /// Leave out conditions tied to this machine (wifi, bluetooth, external devices, screens,
+        /// desktop environment) and disable the jobs that had them
+        #[arg(long)]
+        strip: bool,
+    },
+    /// Add the jobs of a bundle made by `export`
+    Import {
+        /// Bundle file, `-` reads it from stdin
+        file: String,
+        /// What to do with a job whose id is taken, asked for each one when left out
+        #[arg(long, value_enum)]
+        on_conflict: Option<OnConflict>,
+        /// Leave out conditions tied to the machine the bundle came from, like `export --strip`
+        #[arg(long)]
+        strip: bool,
+    },
+    /// Live dashboard of the jobs, through the daemon when it is running
+    Tui,
+    /// Check the directories, condition backends, API address and daemon, reporting what's missing
+    Doctor,
+    /// Print the script that sets up completions, job ids included, for a shell
+    Completions {
+        #[arg(value_parser = PossibleValuesParser::new(SHELLS))]
+        shell: String,
+    },
+    /// Manage the systemd user service
+    Service {
+        #[command(subcommand)]
+        command: ServiceCommands,
+    },
 }
 
 pub async fn handle_cli() {
+    complete_if_requested(Cli::command);
     let cli = Cli::parse();
-    handle_dir(cli.config_path.clone());
+    let output = Output {
+        format: cli.output,
+        no_interactive: cli.no_interactive,
+    };
+    // Checks the setup itself, so it must not fail where the other commands would
+    if let Some(Commands::Doctor) = &cli.command {
+        doctor(cli.config_path.clone(), output).await;
+        return;
+    }
+    let overrides = match &cli.command {
+        Some(Commands::Serve {
+            listen, log_level, ..
+        }) => ConfigOverrides {
+            api_listen: (!listen.is_empty()).then(|| listen.clone()),
+            log_level: log_level.clone(),
+        },
+        _ => ConfigOverrides::default(),
+    };
+    handle_dir(cli.config_path.clone(), overrides);
     match &cli.command {
-        Some(Commands::Serve { api }) => {
+        Some(Commands::Serve { api, .. }) => {
             serve(cli.verbose, *api).await;
         }
-        Some(Commands::Create) => {
-            create();
+        Some(Commands::Create(args)) => {
+            create(args);
         }
-        Some(Commands::Remove) => {
-            remove();
+        Some(Commands::Remove { id }) => {
+            remove(id.clone(), output);
         }
 
         Some(Commands::Stop) => {
             stop(false);
         }
         Some(Commands::List) => {
-            list();
+            list(output);
         }
         Some(Commands::Status) => {
-            status();
+            status(output);
+        }
+        Some(Commands::History { id, limit }) => {
+            history(id.clone(), *limit, output);
+        }
+        Some(Commands::Next) => {
+            next(output);
+        }
+        Some(Commands::Logs(args)) => {
+            logs(args, output).await;
+        }
+        Some(Commands::Edit { job }) => {
+            edit(job.clone(), output).await;
+        }
+        Some(Commands::Enable { id }) => {
+            enable(id.clone()).await;
+        }
+        Some(Commands::Disable { id }) => {
+            disable(id.clone()).await;
+        }
+        Some(Commands::Run { id, force, dry_run }) => {
+            run(id.clone(), *force, *dry_run).await;
+        }
+        Some(Commands::Explain { job }) => {
+            explain(job.clone(), output);
+        }
+        Some(Commands::Validate { paths }) => {
+            validate(paths);
+        }
+        Some(Commands::Export { ids, strip }) => {
+            export(ids, *strip);
+        }
+        Some(Commands::Import {
+            file,
+            on_conflict,
+            strip,
+        }) => {
+            import(file, *on_conflict, *strip, output).await;
+        }
+        Some(Commands::Tui) => {
+            tui().await;
+        }
+        Some(Commands::Doctor) => {}
+        Some(Commands::Completions { shell }) => {
+            completions(shell);
+        }
+        Some(Commands::Service { command }) => {
+            service(command);
         }
         None => {}
     }
 }
 
-fn handle_dir(config_path: Option<String>) {
+fn handle_dir(config_path: Option<String>, overrides: ConfigOverrides) {
     // println!("cli config path : {}", config_path.clone().unwrap());
 
     set_autopilot_path(config_path.clone()).expect("Failed to setup dirs");
+    // Paths may come from the configuration, so it has to be loaded first
+    if let Err(e) = init_config(overrides) {
+        eprintln!("Invalid configuration: {}", e);
+        std::process::exit(1);
+    }
     if let Err(e) = set_all_paths(false) {
         eprintln!("Failed to set up directories: {}", e);
         std::process::exit(1);
@@ -0,0 +1,64 @@
+use chrono::{DateTime, Local};
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::{
+    cli::output::{Output, Table},
+    job::get::{get_job_schemes, job_entries},
+    time::next_run,
+};
+
+#[derive(Serialize)]
+struct UpcomingRun {
+    id: String,
+    name: String,
+    schedule: String,
+    timezone: &'static str,
+    next_run: DateTime<Local>,
+}
+
+/// Prints when each enabled, scheduled job runs next, soonest first
+pub fn next(output: Output) {
+    let mut runs: Vec<UpcomingRun> = job_entries(get_job_schemes())
+        .into_iter()
+        .filter(|entry| entry.scheme.enabled)
+        .filter_map(|entry| {
+            let when = entry.scheme.when.as_ref()?;
+            Some(UpcomingRun {
+                next_run: next_run(when).ok().flatten()?,
+                schedule: when.describe(),
+                timezone: when.timezone(),
+                name: entry.name(),
+                id: entry.scheme.id,
+            })
+        })
+        .collect();
+    runs.sort_by(|a, b| a.next_run.cmp(&b.next_run).then_with(|| a.id.cmp(&b.id)));
+
+    let printed = output.print(&runs, || {
+        Table::new(&["NEXT RUN", "ID", "NAME", "SCHEDULE"]).rows(runs.iter().map(|run| {
+            vec![
+                run.next_run.format("%Y-%m-%d %H:%M:%S").to_string(),
+                run.id.clone(),
+                run.name.clone(),
+                run.schedule.clone(),
+            ]
+        }))
+    });
+    if printed {
+        return;
+    }
+
+    if runs.is_empty() {
+        println!("No scheduled runs");
+    }
+    for run in &runs {
+        println!(
+            "{} {} ({}), {}",
+            run.next_run.format("%Y-%m-%d %H:%M:%S"),
+            run.name.green(),
+            run.id,
+            run.schedule
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! How commands print what they found: colored text for people, or a stable structure for
+//! scripts when `--output` asks for one.
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Json,
+    Yaml,
+    /// Plain aligned columns without colors
+    Table,
+}
+
+/// The global `--output` and `--no-interactive` flags
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Output {
+    pub format: Option<OutputFormat>,
+    pub no_interactive: bool,
+}
+
+impl Output {
+    /// Whether prompts may be shown. Structured output is meant for scripts, so it never prompts.
+    pub fn interactive(&self) -> bool {
+        !self.no_interactive && self.format.is_none()
+    }
+
+    /// Prints `value` as JSON or YAML, or `table` for the table format. Returns `false` without
+    /// printing anything when no format was asked for, leaving it to the human output.
+    pub fn print<T: Serialize>(&self, value: &T, table: impl FnOnce() -> Table) -> bool {
+        match self.format {
+            Some(OutputFormat::Json) => match serde_json::to_string_pretty(value) {
+                Ok(json) => println!("{}", json),
+                Err(e) => fail(e),
+            },
+            Some(OutputFormat::Yaml) => match serde_yaml_ng::to_string(value) {
+                Ok(yaml) => print!("{}", yaml),
+                Err(e) => fail(e),
+            },
+            Some(OutputFormat::Table) => print!("{}", table()),
+            None => return false,
+        }
+        true
+    }
+}
+
+fn fail(e: impl std::fmt::Display) {
+    eprintln!("Failed to serialize output: {}", e);
+    std::process::exit(1);
+}
+
+/// Rows printed under a header, each column as wide as its widest cell
+pub struct Table {
+    headers: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn new(headers: &[&'static str]) -> Self {
+        Self {
+            headers: headers.to_vec(),
+            rows: vec![],
+        }
+    }
+
+    pub fn row(mut self, cells: Vec<String>) -> Self {
+        self.rows.push(cells);
+        self
+    }
+
+    pub fn rows(self, rows: impl IntoIterator<Item = Vec<String>>) -> Self {
+        rows.into_iter().fold(self, Self::row)
+    }
+}
+
+impl std::fmt::Display for Table {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut widths: Vec<usize> = self.headers.iter().map(|header| header.len()).collect();
+        for row in &self.rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let headers = self.headers.iter().map(|header| header.to_string());
+        for row in std::iter::once(headers.collect()).chain(self.rows.iter().cloned()) {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect();
+            writeln!(f, "{}", cells.join("  ").trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// `-` for missing table cells
+pub fn cell<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_aligns_columns() {
+        let table = Table::new(&["ID", "NAME"])
+            .row(vec!["backup".to_string(), "Nightly".to_string()])
+            .row(vec!["j1".to_string(), cell(None::<String>)]);
+        assert_eq!(
+            table.to_string(),
+            "ID      NAME\nbackup  Nightly\nj1      -\n"
+        );
+    }
+}
@@ -2,31 +2,82 @@ use std::path::PathBuf;
 
 use colored::Colorize;
 use dialoguer::{Confirm, Select, theme::ColorfulTheme};
+use serde::Serialize;
 
 use crate::{
+    cli::output::{Output, Table},
     error::AutoPilotError,
     fs::get_autopilot_path,
     job::{
-        get::{get_job, get_jobs_paths},
+        get::{get_job, get_job_path, get_jobs_paths},
         set::remove_job,
     },
 };
 
-pub fn remove() {
-    match remove_interactive() {
-        Ok(job_file_path) => {
-            println!("Job removed successfully at: {}", job_file_path.display());
+#[derive(Serialize)]
+struct Removed {
+    id: String,
+    path: PathBuf,
+}
+
+/// Removes the job with the given id, or one picked from a menu without an id
+pub fn remove(id: Option<String>, output: Output) {
+    let Some(id) = id else {
+        if !output.interactive() {
+            eprintln!("Failed to remove job\nA job id is required without prompts");
+            std::process::exit(1);
+        }
+        match remove_interactive() {
+            Ok(job_file_path) => {
+                println!("Job removed successfully at: {}", job_file_path.display());
+            }
+            Err(e) => {
+                eprintln!("Failed to remove job\n{}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    };
+
+    match remove_by_id(&id, output.interactive()) {
+        Ok(Some(path)) => {
+            let removed = Removed { id, path };
+            let printed = output.print(&removed, || {
+                Table::new(&["ID", "PATH"])
+                    .row(vec![removed.id.clone(), removed.path.display().to_string()])
+            });
+            if !printed {
+                println!("Job removed successfully at: {}", removed.path.display());
+            }
         }
+        Ok(None) => println!("Job not removed"),
         Err(e) => {
             eprintln!("Failed to remove job\n{}", e);
+            std::process::exit(1);
         }
     }
 }
 
+/// Removes a job's file, after asking when `confirm` is set. `None` when the user declined.
+fn remove_by_id(id: &str, confirm: bool) -> Result<Option<PathBuf>, AutoPilotError> {
+    let path = get_job_path(id)?;
+    if confirm
+        && !Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!("Are you sure you want to remove {} ?", id))
+            .default(false)
+            .interact()
+            .map_err(AutoPilotError::Dialoguer)?
+    {
+        return Ok(None);
+    }
+    remove_job(Some(id.to_string()), None)?;
+    Ok(Some(path))
+}
+
 pub fn remove_interactive() -> Result<PathBuf, AutoPilotError> {
     println!(
         "{}",
-        "This subcommand is now deprecated\nPlease use autopilot list instead".red()
+        "Removing without an id is deprecated\nPlease use autopilot list or autopilot remove <id> instead".red()
     );
     let job_paths = get_jobs_paths();
     let options: Vec<String> = job_paths
@@ -54,7 +105,9 @@ pub fn remove_interactive() -> Result<PathBuf, AutoPilotError> {
     if options.is_empty() {
         // eprintln!("No jobs found");
 
-        return Err(AutoPilotError::Job("No jobs exist to remove!".to_string()));
+        return Err(AutoPilotError::JobNotFound(
+            "No jobs exist to remove!".to_string(),
+        ));
     }
     loop {
         selection = Select::with_theme(&ColorfulTheme::default())
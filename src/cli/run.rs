@@ -0,0 +1,75 @@
+use colored::Colorize;
+use tokio::sync::mpsc::unbounded_channel;
+
+use crate::{
+    job::{RunEvent, RunMode, get::get_job_by_id, run_job_with},
+    status::JobStatusEnum,
+};
+
+pub async fn run(id: String, force: bool, dry_run: bool) {
+    let mode = if dry_run {
+        RunMode::DryRun
+    } else if force {
+        RunMode::Force
+    } else {
+        RunMode::Normal
+    };
+    let job = match get_job_by_id(&id) {
+        Ok(job) => job,
+        Err(e) => {
+            eprintln!("Failed to run job: {}", e);
+            std::process::exit(1);
+        }
+    };
+    println!("{} : {}", "Running job".yellow(), job.name);
+
+    let (sender, mut receiver) = unbounded_channel();
+    let handle = tokio::spawn(run_job_with(job, mode, Some(sender)));
+    while let Some(event) = receiver.recv().await {
+        print_event(event);
+    }
+    match handle.await {
+        Ok(JobStatusEnum::Failed) | Ok(JobStatusEnum::Unsatisfied) => std::process::exit(1),
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("Failed to run job: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn print_event(event: RunEvent) {
+    match event {
+        RunEvent::Condition { name, satisfied } => {
+            if satisfied {
+                println!("{} {}", "✓".green(), name);
+            } else {
+                println!("{} {}", "✗".red(), name);
+            }
+        }
+        RunEvent::TaskOutput { command, line } => {
+            println!("{} {}", format!("[{}]", command).dimmed(), line);
+        }
+        RunEvent::TaskFinished {
+            command,
+            success,
+            error,
+        } => {
+            if success {
+                println!("{} : {}", "Task succeeded".green(), command);
+            } else {
+                println!(
+                    "{} : {} ({})",
+                    "Task failed".red(),
+                    command,
+                    error.unwrap_or_default()
+                );
+            }
+        }
+        RunEvent::Finished { status } => match status {
+            JobStatusEnum::Completed => println!("{} : {:?}", "Job finished".green(), status),
+            JobStatusEnum::Pending => println!("{} : {:?}", "Job would run".green(), status),
+            _ => println!("{} : {:?}", "Job finished".red(), status),
+        },
+    }
+}
@@ -7,6 +7,9 @@ use std::{
 use crate::{
     api::{routes::start_api, state::AppState},
     autopilot::AutoPilot,
+    config::reload_config,
+    logging::reload_logging,
+    service::notify::notify,
     status::set::set_status_initial,
 };
 use log::{error, info, warn};
@@ -29,7 +32,11 @@ pub async fn serve(verbose: bool, api: bool) {
         auto_pilot: auto_pilot.clone(),
         started: Arc::new(AtomicBool::new(!api)),
     };
-    start_api(state).await;
+    if let Err(e) = start_api(state).await {
+        error!("{}", e);
+        std::process::exit(1);
+    }
+    auto_pilot.read().await.notify_ready();
     // Keep the daemon running until Ctrl+C is pressed
     // Handle SIGTERM signal
     #[cfg(unix)]
@@ -49,6 +56,7 @@ pub async fn serve(verbose: bool, api: bool) {
             use log::info;
 
             sigterm.recv().await;
+            notify("STOPPING=1");
             info!("Received SIGTERM, resetting status...");
             if let Err(e) = set_status_initial() {
                 error!("Failed to initialize status: {}", e);
@@ -68,12 +76,18 @@ pub async fn serve(verbose: bool, api: bool) {
         let sighup_handle = tokio::spawn(async move {
             use log::info;
 
-            sighup.recv().await;
-            info!("Received SIGHUP, resetting status...");
-            if let Err(e) = set_status_initial() {
-                error!("Failed to initialize status: {}", e);
+            while sighup.recv().await.is_some() {
+                info!("Received SIGHUP, reloading configuration...");
+                match reload_config() {
+                    Ok(_) => reload_logging(verbose),
+                    Err(e) => error!("Keeping the previous configuration: {}", e),
+                }
+                info!("Resetting status...");
+                if let Err(e) = set_status_initial() {
+                    error!("Failed to initialize status: {}", e);
+                }
+                auto_pilot.write().await.reload().await;
             }
-            auto_pilot.write().await.reload().await;
             // std::process::exit(0);
             // serve().await;
         });
@@ -84,6 +98,7 @@ pub async fn serve(verbose: bool, api: bool) {
                 error!("Failed to listen for ctrl+c: {}", e);
                 std::process::exit(1);
             }
+            notify("STOPPING=1");
             warn!("Received SIGINT, resetting status...");
             if let Err(e) = set_status_initial() {
                 error!("Failed to initialize status: {}", e);
@@ -0,0 +1,59 @@
+use clap::Subcommand;
+use colored::Colorize;
+
+use crate::service::{SERVICE_NAME, get_unit_path, install, systemctl, uninstall};
+
+#[derive(Subcommand)]
+pub enum ServiceCommands {
+    /// Install and start a systemd user service running `autopilot serve`
+    Install {
+        /// Serve the HTTP API as well
+        #[arg(long, default_value_t = false)]
+        api: bool,
+    },
+    /// Stop and remove the systemd user service
+    Uninstall,
+    /// Show the state of the systemd user service
+    Status,
+}
+
+pub fn service(command: &ServiceCommands) {
+    match command {
+        ServiceCommands::Install { api } => match install(*api) {
+            Ok(unit_path) => {
+                println!(
+                    "{} {} at: {}",
+                    SERVICE_NAME.green(),
+                    "installed and started".green(),
+                    unit_path.display()
+                );
+            }
+            Err(e) => {
+                eprintln!("Failed to install service: {}", e);
+                std::process::exit(1);
+            }
+        },
+        ServiceCommands::Uninstall => match uninstall() {
+            Ok(unit_path) => {
+                println!("{} removed from: {}", SERVICE_NAME, unit_path.display());
+            }
+            Err(e) => {
+                eprintln!("Failed to uninstall service: {}", e);
+                std::process::exit(1);
+            }
+        },
+        ServiceCommands::Status => {
+            match get_unit_path() {
+                Ok(unit_path) if unit_path.exists() => {
+                    println!("Unit file: {}", unit_path.display());
+                }
+                _ => println!("{}", format!("{} is not installed", SERVICE_NAME).yellow()),
+            }
+            // `systemctl status` exits non-zero for inactive units, its output is still useful
+            match systemctl(&["status", "--no-pager", SERVICE_NAME]) {
+                Ok(output) => println!("{}", output),
+                Err(e) => println!("{}", e),
+            }
+        }
+    }
+}
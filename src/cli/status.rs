@@ -1,6 +1,39 @@
+use serde::Serialize;
 
-pub fn status() {
-    match check_if_running() {
+use crate::{
+    cli::output::{Output, Table},
+    job::get::load_job_schemes,
+};
+
+#[derive(Serialize)]
+struct Status {
+    running: bool,
+    jobs: usize,
+    enabled: usize,
+    /// Job files that were skipped, because they don't parse or reuse an id
+    load_errors: usize,
+}
+
+pub fn status(output: Output) {
+    let (schemes, load_errors) = load_job_schemes();
+    let status = Status {
+        running: check_if_running(),
+        jobs: schemes.len(),
+        enabled: schemes.iter().filter(|(_, scheme)| scheme.enabled).count(),
+        load_errors: load_errors.len(),
+    };
+    let printed = output.print(&status, || {
+        Table::new(&["RUNNING", "JOBS", "ENABLED", "LOAD ERRORS"]).row(vec![
+            status.running.to_string(),
+            status.jobs.to_string(),
+            status.enabled.to_string(),
+            status.load_errors.to_string(),
+        ])
+    });
+    if printed {
+        return;
+    }
+    match status.running {
         true => {
             println!("Autopilot is running");
         }
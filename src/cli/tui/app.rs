@@ -0,0 +1,339 @@
+//! State of the dashboard. Anything slow (requests, condition checks, log reading) runs on a
+//! background task and comes back as an [`Update`], so the screen never waits on it.
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::KeyCode;
+use ratatui::widgets::TableState;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+use crate::{
+    api::client::ApiClient,
+    autopilot::events::{Event, EventKind},
+    cli::{
+        explain::explain_target,
+        tui::source::{JobView, Source},
+    },
+    job::{RunMode, explain::JobExplanation},
+    logging::read::job_records,
+};
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+/// Conditions can be slow to check, so they are re-evaluated less often than the list
+const EXPLAIN_INTERVAL: Duration = Duration::from_secs(5);
+/// Lines kept per job in the log pane
+const LOG_LINES: usize = 500;
+
+pub enum Update {
+    Source(Source),
+    Jobs(Result<Vec<JobView>, String>),
+    Paused(Option<bool>),
+    Explanation(String, Result<Box<JobExplanation>, String>),
+    Log(String, Vec<String>),
+    Event(Event),
+    Message(String),
+}
+
+pub struct App {
+    pub source: Source,
+    pub jobs: Vec<JobView>,
+    pub table: TableState,
+    /// Whether the daemon's scheduler is stopped, `None` without a daemon
+    pub paused: Option<bool>,
+    /// Live condition results of the selected job
+    pub explanation: Option<(String, Result<Box<JobExplanation>, String>)>,
+    /// Log file records of the selected job
+    pub file_log: (String, Vec<String>),
+    /// Progress of runs seen since the dashboard started, by job
+    pub live_log: HashMap<String, Vec<String>>,
+    pub message: Option<String>,
+    pub confirm_delete: bool,
+    /// Set by the edit key, the terminal has to be handed over before the editor can open
+    pub pending_edit: Option<String>,
+    pub exit: bool,
+    updates: UnboundedSender<Update>,
+    receiver: UnboundedReceiver<Update>,
+    events: UnboundedSender<Event>,
+    refreshing: bool,
+    explaining: bool,
+    last_refresh: Option<Instant>,
+    last_explain: Option<Instant>,
+}
+
+impl App {
+    pub fn new(source: Source) -> Self {
+        let (updates, receiver) = unbounded_channel();
+        let (events, mut event_receiver) = unbounded_channel::<Event>();
+        let forward = updates.clone();
+        tokio::spawn(async move {
+            while let Some(event) = event_receiver.recv().await {
+                if forward.send(Update::Event(event)).is_err() {
+                    break;
+                }
+            }
+        });
+        // Follows the daemon even when it isn't up yet, it may come up later on
+        if let Ok(Some(client)) = ApiClient::from_config() {
+            tokio::spawn(Source::follow_events(client, events.clone()));
+        }
+        Self {
+            source,
+            jobs: vec![],
+            table: TableState::default(),
+            paused: None,
+            explanation: None,
+            file_log: (String::new(), vec![]),
+            live_log: HashMap::new(),
+            message: None,
+            confirm_delete: false,
+            pending_edit: None,
+            exit: false,
+            updates,
+            receiver,
+            events,
+            refreshing: false,
+            explaining: false,
+            last_refresh: None,
+            last_explain: None,
+        }
+    }
+
+    pub fn selected_job(&self) -> Option<&JobView> {
+        self.table.selected().and_then(|index| self.jobs.get(index))
+    }
+
+    fn selected_id(&self) -> Option<String> {
+        self.selected_job().map(|job| job.id.clone())
+    }
+
+    /// Applies finished background work and starts whatever is due
+    pub fn tick(&mut self) {
+        while let Ok(update) = self.receiver.try_recv() {
+            self.apply(update);
+        }
+        let due =
+            |last: Option<Instant>, interval| last.is_none_or(|last| last.elapsed() >= interval);
+        if !self.refreshing && due(self.last_refresh, REFRESH_INTERVAL) {
+            self.refresh();
+        }
+        if !self.explaining && due(self.last_explain, EXPLAIN_INTERVAL) {
+            self.explain();
+        }
+    }
+
+    fn apply(&mut self, update: Update) {
+        match update {
+            Update::Source(source) => {
+                self.message = Some(if source.is_daemon() {
+                    "Connected to the daemon".to_string()
+                } else {
+                    "The daemon stopped answering, reading the job files".to_string()
+                });
+                self.source = source;
+            }
+            Update::Jobs(Ok(jobs)) => {
+                self.refreshing = false;
+                let selected = self.selected_id();
+                self.jobs = jobs;
+                // Keep the selection on the same job, wherever it moved to
+                let index = selected
+                    .and_then(|id| self.jobs.iter().position(|job| job.id == id))
+                    .unwrap_or(0);
+                self.table
+                    .select((!self.jobs.is_empty()).then(|| index.min(self.jobs.len() - 1)));
+                self.load_log();
+            }
+            Update::Jobs(Err(e)) => {
+                self.refreshing = false;
+                self.message = Some(format!("✗ {}", e));
+            }
+            Update::Paused(paused) => self.paused = paused,
+            Update::Explanation(id, explanation) => {
+                self.explaining = false;
+                if self.selected_id().as_deref() == Some(&id) {
+                    self.explanation = Some((id, explanation));
+                }
+            }
+            Update::Log(id, lines) => self.file_log = (id, lines),
+            Update::Event(event) => self.on_event(event),
+            Update::Message(message) => {
+                self.message = Some(message);
+                // Whatever was done likely changed the list
+                self.last_refresh = None;
+            }
+        }
+    }
+
+    fn on_event(&mut self, event: Event) {
+        let Some(job_id) = event.job_id else {
+            // Reloads change the list
+            self.last_refresh = None;
+            return;
+        };
+        match event.kind {
+            EventKind::JobLoaded { .. } | EventKind::Reload { .. } => return,
+            EventKind::RunFinished { .. } => self.last_refresh = None,
+            _ => {}
+        }
+        let line = event.kind.summary();
+        let lines = self.live_log.entry(job_id).or_default();
+        lines.push(format!("{} {}", event.time.format("%H:%M:%S"), line));
+        let excess = lines.len().saturating_sub(LOG_LINES);
+        lines.drain(..excess);
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) {
+        if self.confirm_delete {
+            match key {
+                KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+                    self.confirm_delete = false;
+                    if let Some(id) = self.selected_id() {
+                        self.action(move |source| async move { source.delete(&id).await });
+                    }
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                    self.confirm_delete = false;
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match key {
+            KeyCode::Down | KeyCode::Char('j') => self.select_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.select_by(-1),
+            KeyCode::Char('r') => self.run(RunMode::Normal),
+            KeyCode::Char('f') => self.run(RunMode::Force),
+            KeyCode::Char('c') => self.run(RunMode::DryRun),
+            KeyCode::Char('p') => {
+                let paused = self.paused != Some(true);
+                self.action(move |source| async move { source.set_paused(paused).await });
+            }
+            KeyCode::Char('t') => {
+                if let Some(job) = self.selected_job() {
+                    let (id, enabled) = (job.id.clone(), !job.enabled);
+                    self.action(
+                        move |source| async move { source.set_enabled(&id, enabled).await },
+                    );
+                }
+            }
+            KeyCode::Char('e') => self.pending_edit = self.selected_id(),
+            KeyCode::Char('d') | KeyCode::Delete => {
+                self.confirm_delete = self.selected_job().is_some();
+            }
+            KeyCode::Esc => self.message = None,
+            KeyCode::Char('q') => self.exit = true,
+            _ => {}
+        }
+    }
+
+    fn select_by(&mut self, offset: isize) {
+        if self.jobs.is_empty() {
+            return;
+        }
+        let current = self.table.selected().unwrap_or(0);
+        let next = current
+            .saturating_add_signed(offset)
+            .min(self.jobs.len() - 1);
+        if next != current {
+            self.table.select(Some(next));
+            self.explanation = None;
+            self.last_explain = None;
+            self.load_log();
+        }
+    }
+
+    fn run(&mut self, mode: RunMode) {
+        let Some(id) = self.selected_id() else {
+            return;
+        };
+        let events = self.events.clone();
+        self.message = Some(format!("Running {}…", id));
+        self.action(move |source| async move {
+            let status = source.run(&id, mode, events).await?;
+            Ok(format!("{} finished: {}", id, status))
+        });
+    }
+
+    /// Runs `action` against the current source, reporting its outcome in the status bar
+    fn action<F, Fut>(&self, action: F)
+    where
+        F: FnOnce(Source) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<String, crate::error::AutoPilotError>> + Send,
+    {
+        let source = self.source.clone();
+        let updates = self.updates.clone();
+        tokio::spawn(async move {
+            let message = match action(source).await {
+                Ok(message) => format!("✓ {}", message),
+                Err(e) => format!("✗ {}", e),
+            };
+            let _ = updates.send(Update::Message(message));
+        });
+    }
+
+    /// Reports the outcome of editing a job file, reloading the daemon if it took
+    pub fn edited(&mut self, id: String, result: Result<(), String>) {
+        match result {
+            Ok(()) => self.action(move |source| async move {
+                source.reload(&id).await?;
+                Ok(format!("Saved {}", id))
+            }),
+            Err(e) => self.message = Some(format!("✗ {}", e)),
+        }
+        self.last_explain = None;
+    }
+
+    fn refresh(&mut self) {
+        self.refreshing = true;
+        self.last_refresh = Some(Instant::now());
+        let source = self.source.clone();
+        let updates = self.updates.clone();
+        tokio::spawn(async move {
+            // Switch over whenever the daemon comes or goes
+            let current = match &source {
+                Source::Daemon(client) if !client.is_reachable().await => Source::Files,
+                Source::Files => Source::detect().await,
+                daemon => daemon.clone(),
+            };
+            if current.is_daemon() != source.is_daemon() {
+                let _ = updates.send(Update::Source(current.clone()));
+            }
+            let _ = updates.send(Update::Paused(
+                current.scheduler_running().await.map(|running| !running),
+            ));
+            let jobs = current.jobs().await.map_err(|e| e.to_string());
+            let _ = updates.send(Update::Jobs(jobs));
+        });
+    }
+
+    fn explain(&mut self) {
+        let Some(id) = self.selected_id() else {
+            return;
+        };
+        self.explaining = true;
+        self.last_explain = Some(Instant::now());
+        let updates = self.updates.clone();
+        tokio::task::spawn_blocking(move || {
+            let explanation = explain_target(&id).map(Box::new).map_err(|e| e.to_string());
+            let _ = updates.send(Update::Explanation(id, explanation));
+        });
+    }
+
+    fn load_log(&self) {
+        let Some(id) = self.selected_id() else {
+            return;
+        };
+        let updates = self.updates.clone();
+        tokio::task::spawn_blocking(move || {
+            let lines = job_records(&id, LOG_LINES)
+                .iter()
+                .map(|record| record.short())
+                .collect();
+            let _ = updates.send(Update::Log(id, lines));
+        });
+    }
+}
@@ -0,0 +1,98 @@
+//! `autopilot tui`, a live dashboard of the jobs with their conditions and output.
+
+use std::{
+    io::{self, Stdout},
+    time::Duration,
+};
+
+use crossterm::{
+    event::{self, Event, KeyEventKind},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use ratatui::{Terminal, backend::CrosstermBackend};
+
+use crate::{
+    cli::{
+        edit::{default_editor, open_editor},
+        tui::{app::App, source::Source},
+    },
+    cross_platform::get::get_supported_editors,
+    error::AutoPilotError,
+    job::get::{get_job_path, get_job_scheme},
+};
+
+pub mod app;
+pub mod source;
+pub mod ui;
+
+type Backend = CrosstermBackend<Stdout>;
+
+pub async fn tui() {
+    let source = Source::detect().await;
+    if let Err(e) = tokio::task::block_in_place(|| run_tui(source)) {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run_tui(source: Source) -> Result<(), AutoPilotError> {
+    let mut terminal = enter()?;
+    let result = run(&mut App::new(source), &mut terminal);
+    leave(&mut terminal)?;
+    result
+}
+
+fn enter() -> Result<Terminal<Backend>, AutoPilotError> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    Ok(Terminal::new(CrosstermBackend::new(stdout))?)
+}
+
+fn leave(terminal: &mut Terminal<Backend>) -> Result<(), AutoPilotError> {
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    Ok(())
+}
+
+fn run(app: &mut App, terminal: &mut Terminal<Backend>) -> Result<(), AutoPilotError> {
+    while !app.exit {
+        app.tick();
+        terminal.draw(|f| ui::render(app, f))?;
+
+        if event::poll(Duration::from_millis(100))?
+            && let Event::Key(key) = event::read()?
+            && key.kind == KeyEventKind::Press
+        {
+            app.handle_key(key.code);
+        }
+
+        if let Some(id) = app.pending_edit.take() {
+            let result = edit(&id, terminal).map_err(|e| e.to_string());
+            app.edited(id, result);
+        }
+    }
+    Ok(())
+}
+
+/// Hands the terminal to an editor on the job's file and checks the file still parses afterwards
+fn edit(id: &str, terminal: &mut Terminal<Backend>) -> Result<(), AutoPilotError> {
+    let path = get_job_path(id)?;
+    leave(terminal)?;
+    let edited = default_editor()
+        .or_else(|| {
+            get_supported_editors()
+                .first()
+                .map(|editor| editor.to_string())
+        })
+        .ok_or_else(|| {
+            AutoPilotError::Command("No editor found, set $EDITOR to edit jobs".to_string())
+        })
+        .and_then(|editor| open_editor(&path, &editor));
+    *terminal = enter()?;
+    terminal.clear()?;
+    edited?;
+    get_job_scheme(&path).map(|_| ())
+}
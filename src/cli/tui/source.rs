@@ -0,0 +1,235 @@
+//! Where the dashboard reads jobs from and sends its actions to: the daemon's API while it
+//! answers, the job files otherwise.
+
+use std::time::Duration;
+
+use chrono::Local;
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+
+use crate::{
+    api::client::ApiClient,
+    autopilot::events::{Event, EventKind},
+    error::AutoPilotError,
+    job::{
+        RunMode,
+        get::{get_job_by_id, get_job_schemes, job_entries},
+        run_job_with,
+        set::{remove_job, set_job_enabled},
+    },
+    status::LastRun,
+    time::When,
+};
+
+/// A row of the job table
+#[derive(Clone, Debug, Deserialize)]
+pub struct JobView {
+    pub id: String,
+    pub name: String,
+    pub status: String,
+    pub enabled: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub when: Option<When>,
+    pub last_run: Option<LastRun>,
+}
+
+#[derive(Clone)]
+pub enum Source {
+    Daemon(ApiClient),
+    Files,
+}
+
+impl Source {
+    /// The daemon when one answers on `api.listen`, else the files
+    pub async fn detect() -> Self {
+        match ApiClient::from_config() {
+            Ok(Some(client)) if client.is_reachable().await => Self::Daemon(client),
+            _ => Self::Files,
+        }
+    }
+
+    pub fn is_daemon(&self) -> bool {
+        matches!(self, Self::Daemon(_))
+    }
+
+    pub async fn jobs(&self) -> Result<Vec<JobView>, AutoPilotError> {
+        let Self::Daemon(client) = self else {
+            let mut entries = job_entries(get_job_schemes());
+            // Same order as the daemon lists them in
+            entries.sort_by(|a, b| a.scheme.id.cmp(&b.scheme.id));
+            return Ok(entries
+                .into_iter()
+                .map(|entry| JobView {
+                    name: entry.name(),
+                    id: entry.scheme.id,
+                    status: format!("{:?}", entry.status),
+                    enabled: entry.scheme.enabled,
+                    tags: entry.scheme.tags,
+                    when: entry.scheme.when,
+                    last_run: entry.last_run,
+                })
+                .collect());
+        };
+        let mut jobs = vec![];
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut path = "/jobs?expand=when,last_run&limit=500".to_string();
+            if let Some(cursor) = &cursor {
+                path += &format!("&cursor={}", cursor);
+            }
+            let mut page = client.request("GET", &path, None).await?;
+            let page_jobs: Vec<JobView> = serde_json::from_value(page["jobs"].take())
+                .map_err(|e| AutoPilotError::Api(format!("Unexpected job list: {}", e)))?;
+            jobs.extend(page_jobs);
+            match page["next_cursor"].as_str() {
+                Some(next) => cursor = Some(next.to_string()),
+                None => return Ok(jobs),
+            }
+        }
+    }
+
+    /// Whether the daemon's scheduler runs jobs, `None` without a daemon
+    pub async fn scheduler_running(&self) -> Option<bool> {
+        let Self::Daemon(client) = self else {
+            return None;
+        };
+        let status = client.request("GET", "/status", None).await.ok()?;
+        status["running"].as_bool()
+    }
+
+    /// Stops or starts the daemon's scheduler
+    pub async fn set_paused(&self, paused: bool) -> Result<String, AutoPilotError> {
+        let Self::Daemon(client) = self else {
+            return Err(AutoPilotError::Api(
+                "The daemon is not running, there is nothing to pause".to_string(),
+            ));
+        };
+        let response = if paused {
+            client.request("POST", "/stop", None).await?
+        } else {
+            client.request("POST", "/start", Some(&Value::Null)).await?
+        };
+        Ok(message_of(&response))
+    }
+
+    pub async fn set_enabled(&self, id: &str, enabled: bool) -> Result<String, AutoPilotError> {
+        match self {
+            Self::Daemon(client) => {
+                let action = if enabled { "enable" } else { "disable" };
+                let path = format!("/jobs/{}/{}", id, action);
+                Ok(message_of(&client.request("POST", &path, None).await?))
+            }
+            Self::Files => {
+                set_job_enabled(id.to_string(), enabled)?;
+                Ok(format!(
+                    "{} {}",
+                    if enabled { "Enabled" } else { "Disabled" },
+                    id
+                ))
+            }
+        }
+    }
+
+    pub async fn delete(&self, id: &str) -> Result<String, AutoPilotError> {
+        match self {
+            Self::Daemon(client) => {
+                client
+                    .request("DELETE", &format!("/jobs/{}", id), None)
+                    .await?;
+            }
+            Self::Files => remove_job(Some(id.to_string()), None)?,
+        }
+        Ok(format!("Deleted {}", id))
+    }
+
+    /// Makes a running daemon pick up an edited job file, restarting only that job; a paused one
+    /// reads it when resumed
+    pub async fn reload(&self, id: &str) -> Result<(), AutoPilotError> {
+        if let Self::Daemon(client) = self
+            && self.scheduler_running().await == Some(true)
+        {
+            let path = format!("/jobs/{}/reload", id);
+            client.request("POST", &path, None).await?;
+        }
+        Ok(())
+    }
+
+    /// Runs a job and returns how the run ended. The daemon reports progress on its event stream,
+    /// a run in this process sends it to `events` itself.
+    pub async fn run(
+        &self,
+        id: &str,
+        mode: RunMode,
+        events: UnboundedSender<Event>,
+    ) -> Result<String, AutoPilotError> {
+        match self {
+            Self::Daemon(client) => {
+                let query = match mode {
+                    RunMode::Normal => "",
+                    RunMode::Force => "?force=true",
+                    RunMode::DryRun => "?dry_run=true",
+                };
+                let path = format!("/jobs/{}/run{}", id, query);
+                let mut lines = client.lines("POST", &path).await?;
+                let mut status = None;
+                while let Some(line) = lines.next_line().await? {
+                    if let Ok(event) = serde_json::from_str::<Value>(&line)
+                        && event["event"] == "finished"
+                    {
+                        status = event["status"].as_str().map(str::to_string);
+                    }
+                }
+                status.ok_or_else(|| {
+                    AutoPilotError::Api(format!("The daemon didn't report how {} ended", id))
+                })
+            }
+            Self::Files => {
+                let job = get_job_by_id(id)?;
+                let send = |kind: EventKind| {
+                    let _ = events.send(Event {
+                        time: Local::now(),
+                        job_id: Some(id.to_string()),
+                        run_id: None,
+                        kind,
+                    });
+                };
+                send(EventKind::RunStarted { mode });
+                let (sender, mut receiver) = unbounded_channel();
+                let run = tokio::spawn(run_job_with(job, mode, Some(sender)));
+                while let Some(event) = receiver.recv().await {
+                    send(event.into());
+                }
+                let status = run
+                    .await
+                    .map_err(|e| AutoPilotError::JobExecution(e.to_string()))?;
+                Ok(format!("{:?}", status))
+            }
+        }
+    }
+
+    /// Forwards the daemon's events to `events` until it is dropped, reconnecting whenever the
+    /// stream breaks
+    pub async fn follow_events(client: ApiClient, events: UnboundedSender<Event>) {
+        while !events.is_closed() {
+            if let Ok(mut lines) = client.lines("GET", "/events").await {
+                while let Ok(Some(line)) = lines.next_line().await {
+                    let Some(data) = line.strip_prefix("data:") else {
+                        continue;
+                    };
+                    if let Ok(event) = serde_json::from_str::<Event>(data.trim_start())
+                        && events.send(event).is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(2)).await;
+        }
+    }
+}
+
+fn message_of(response: &Value) -> String {
+    response["message"].as_str().unwrap_or("Done").to_string()
+}
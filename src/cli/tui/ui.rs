@@ -0,0 +1,292 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Wrap},
+};
+
+use crate::{
+    cli::tui::{app::App, source::Source},
+    fs::get_autopilot_path,
+    job::explain::ConditionExplanation,
+    time::next_run,
+};
+
+const HINTS: &str = "↑↓/j/k select • r run • f force • c check • t enable/disable • e edit • d delete • p pause • q quit";
+
+pub fn render(app: &mut App, f: &mut Frame) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(40),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(f.area());
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(rows[1]);
+
+    render_jobs(app, f, rows[0]);
+    render_details(app, f, panes[0]);
+    render_log(app, f, panes[1]);
+    render_status_bar(app, f, rows[2]);
+    if app.confirm_delete {
+        render_confirm_delete(app, f);
+    }
+}
+
+fn status_color(status: &str) -> Color {
+    match status {
+        "Completed" => Color::Green,
+        "Failed" => Color::Red,
+        "Running" => Color::Cyan,
+        "Disabled" => Color::DarkGray,
+        _ => Color::Yellow,
+    }
+}
+
+fn render_jobs(app: &mut App, f: &mut Frame, area: Rect) {
+    let source = match (&app.source, app.paused) {
+        (Source::Daemon(_), Some(true)) => "daemon, paused".to_string(),
+        (Source::Daemon(_), _) => "daemon".to_string(),
+        (Source::Files, _) => format!("files in {}, daemon not running", get_autopilot_path()),
+    };
+    let rows = app.jobs.iter().map(|job| {
+        let next = match &job.when {
+            _ if !job.enabled => "—".to_string(),
+            Some(when) => match next_run(when) {
+                Ok(Some(next)) => next.format("%Y-%m-%d %H:%M").to_string(),
+                Ok(None) => "passed".to_string(),
+                Err(_) => "invalid".to_string(),
+            },
+            None => "on start".to_string(),
+        };
+        let last = match &job.last_run {
+            Some(run) => format!("{:?} {}", run.status, run.finished.format("%Y-%m-%d %H:%M")),
+            None => "never".to_string(),
+        };
+        let last_color = match &job.last_run {
+            Some(run) => status_color(&format!("{:?}", run.status)),
+            None => Color::DarkGray,
+        };
+        Row::new(vec![
+            Cell::from(job.id.clone()).style(Style::default().fg(Color::Yellow)),
+            Cell::from(job.name.clone()).style(Style::default().fg(Color::Green)),
+            Cell::from(job.status.clone()).style(Style::default().fg(status_color(&job.status))),
+            Cell::from(next),
+            Cell::from(last).style(Style::default().fg(last_color)),
+        ])
+    });
+    let header = Row::new(vec!["ID", "NAME", "STATUS", "NEXT RUN", "LAST RESULT"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(18),
+            Constraint::Percentage(24),
+            Constraint::Length(10),
+            Constraint::Length(16),
+            Constraint::Min(20),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::default()
+            .title(format!("Jobs [{}]", source))
+            .borders(Borders::ALL),
+    )
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+    .highlight_symbol("▸ ");
+    f.render_stateful_widget(table, area, &mut app.table);
+}
+
+fn label(text: &str) -> Span<'static> {
+    Span::styled(
+        format!("{}: ", text),
+        Style::default().add_modifier(Modifier::BOLD),
+    )
+}
+
+fn render_details(app: &App, f: &mut Frame, area: Rect) {
+    let block = Block::default().title("Details").borders(Borders::ALL);
+    let Some(job) = app.selected_job() else {
+        let hint = Paragraph::new(Span::styled(
+            "No jobs found",
+            Style::default().fg(Color::DarkGray),
+        ));
+        f.render_widget(hint.block(block), area);
+        return;
+    };
+
+    let mut lines = vec![Line::from(vec![
+        label("Job"),
+        Span::styled(job.name.clone(), Style::default().fg(Color::Green)),
+        Span::raw(format!(" ({})", job.id)),
+    ])];
+    if !job.tags.is_empty() {
+        lines.push(Line::from(vec![
+            label("Tags"),
+            Span::raw(job.tags.join(", ")),
+        ]));
+    }
+    if let Some(run) = &job.last_run {
+        lines.push(Line::from(vec![
+            label("Last run"),
+            Span::raw(format!(
+                "{:?}, {} → {}",
+                run.status,
+                run.started.format("%Y-%m-%d %H:%M:%S"),
+                run.finished.format("%H:%M:%S")
+            )),
+        ]));
+    }
+
+    match &app.explanation {
+        Some((id, Ok(explanation))) if *id == job.id => {
+            if let Some(schedule) = &explanation.schedule {
+                lines.push(Line::from(vec![
+                    label("Schedule"),
+                    Span::raw(schedule.description.clone()),
+                ]));
+            }
+            lines.push(Line::from(vec![
+                label("Conditions"),
+                Span::styled(
+                    "re-checked every few seconds",
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]));
+            if explanation.conditions.is_empty() {
+                lines.push(Line::raw("  none, the tasks always run"));
+            }
+            for condition in &explanation.conditions {
+                condition_lines(condition, 1, &mut lines);
+            }
+            lines.push(Line::from(label("Tasks")));
+            for task in &explanation.tasks {
+                lines.push(Line::raw(format!("  $ {}", task.command)));
+            }
+        }
+        Some((id, Err(e))) if *id == job.id => {
+            lines.push(Line::styled(e.clone(), Style::default().fg(Color::Red)));
+        }
+        _ => lines.push(Line::styled(
+            "Checking conditions…",
+            Style::default().fg(Color::DarkGray),
+        )),
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, area);
+}
+
+fn condition_lines(condition: &ConditionExplanation, depth: usize, lines: &mut Vec<Line>) {
+    let indent = "  ".repeat(depth);
+    let (mark, color) = if condition.satisfied {
+        ("✓", Color::Green)
+    } else {
+        ("✗", Color::Red)
+    };
+    let mut spans = vec![
+        Span::raw(indent.clone()),
+        Span::styled(mark, Style::default().fg(color)),
+        Span::raw(format!(" {}", condition.name)),
+    ];
+    if let Some(operator) = condition.operator {
+        spans.push(Span::raw(format!(" {}", operator.name())));
+    }
+    if let Some(observed) = &condition.observed {
+        spans.push(Span::styled(
+            format!("  {}", observed),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    lines.push(Line::from(spans));
+    for child in &condition.children {
+        condition_lines(child, depth + 1, lines);
+    }
+}
+
+fn render_log(app: &App, f: &mut Frame, area: Rect) {
+    let block = Block::default().title("Log").borders(Borders::ALL);
+    let Some(job) = app.selected_job() else {
+        f.render_widget(block, area);
+        return;
+    };
+    let mut lines: Vec<Line> = vec![];
+    if app.file_log.0 == job.id {
+        lines.extend(
+            app.file_log
+                .1
+                .iter()
+                .map(|line| Line::styled(line.clone(), Style::default().fg(Color::DarkGray))),
+        );
+    }
+    if let Some(live) = app.live_log.get(&job.id) {
+        lines.extend(live.iter().map(|line| Line::raw(line.clone())));
+    }
+    // Show the tail, like `tail -f` would
+    let height = area.height.saturating_sub(2) as usize;
+    let skip = lines.len().saturating_sub(height);
+    let paragraph = Paragraph::new(lines.split_off(skip)).block(block);
+    f.render_widget(paragraph, area);
+}
+
+fn render_status_bar(app: &App, f: &mut Frame, area: Rect) {
+    let (text, style) = match &app.message {
+        Some(message) => (message.as_str(), Style::default().fg(Color::Cyan)),
+        None => (HINTS, Style::default().fg(Color::DarkGray)),
+    };
+    f.render_widget(Paragraph::new(format!(" {}", text)).style(style), area);
+}
+
+fn render_confirm_delete(app: &App, f: &mut Frame) {
+    let Some(job) = app.selected_job() else {
+        return;
+    };
+    let area = f.area();
+    let width = 50.min(area.width);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + area.height.saturating_sub(5) / 2,
+        width,
+        5.min(area.height),
+    );
+    let lines = vec![
+        Line::from(vec![
+            Span::raw("Delete job "),
+            Span::styled(
+                job.id.clone(),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("?"),
+        ]),
+        Line::raw(""),
+        Line::from(vec![
+            Span::styled(
+                "[Y]es",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" / "),
+            Span::styled(
+                "[N]o",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+        ]),
+    ];
+    let block = Block::default()
+        .title("Confirm Deletion")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::LightRed));
+    f.render_widget(Clear, popup);
+    f.render_widget(Paragraph::new(lines).block(block), popup);
+}
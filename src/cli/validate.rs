@@ -0,0 +1,92 @@
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+
+use crate::{
+    fs::get_jobs_path,
+    job::validate::{Problem, validate_files},
+};
+
+/// Validates the given job files and directories, or the jobs directory without any, and exits
+/// with 1 when a problem was found so it can gate commits
+pub fn validate(paths: &[String]) {
+    let files = if paths.is_empty() {
+        job_files(Path::new(&get_jobs_path()))
+    } else {
+        paths
+            .iter()
+            .flat_map(|path| {
+                let path = Path::new(path);
+                if path.is_dir() {
+                    job_files(path)
+                } else {
+                    vec![path.to_path_buf()]
+                }
+            })
+            .collect()
+    };
+    if files.is_empty() {
+        println!("No job files found");
+        return;
+    }
+
+    let reports = validate_files(&files);
+    let mut problems = 0;
+    for report in &reports {
+        if report.problems.is_empty() {
+            println!("{} {}", "ok".green(), report.path.display());
+        }
+        for problem in &report.problems {
+            print_problem(&report.path, problem);
+            problems += 1;
+        }
+    }
+
+    let failed = reports
+        .iter()
+        .filter(|report| !report.problems.is_empty())
+        .count();
+    println!(
+        "{} file(s) checked, {} problem(s) in {} file(s)",
+        reports.len(),
+        problems,
+        failed
+    );
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Prints `path:line:column: error: message`, the form editors and CI logs link to
+pub fn print_problem(path: &Path, problem: &Problem) {
+    let location = match (problem.line, problem.column) {
+        (Some(line), Some(column)) => format!(":{}:{}", line, column),
+        _ => String::new(),
+    };
+    println!(
+        "{}{}: {} {}",
+        path.display(),
+        location,
+        "error:".red(),
+        problem.message
+    );
+}
+
+/// `.jsonc` and `.json` files directly inside `dir`, by name
+fn job_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        eprintln!("Failed to read directory {}", dir.display());
+        return vec![];
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|extension| extension == "jsonc" || extension == "json")
+        })
+        .collect();
+    files.sort();
+    files
+}
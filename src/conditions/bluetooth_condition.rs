@@ -1,9 +1,10 @@
 use dialoguer::{Confirm, Input, theme::ColorfulTheme};
 use duct::cmd;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::{
-    conditions::{Condition, ConditionScheme},
+    conditions::{Condition, ConditionScheme, Probe},
     error::AutoPilotError,
 };
 
@@ -44,7 +45,7 @@ impl BluetoothCondition {
 
 impl Condition for BluetoothCondition {
     fn check(&self) -> bool {
-        sync_condition(&self.device, self.match_by_mac)
+        self.probe().satisfied
     }
 
     fn clone_box(&self) -> Box<dyn Condition> {
@@ -55,6 +56,14 @@ impl Condition for BluetoothCondition {
         "Bluetooth"
     }
 
+    fn probe(&self) -> Probe {
+        if sync_condition(&self.device, self.match_by_mac) {
+            Probe::new(true, format!("{} is connected", self.device))
+        } else {
+            Probe::new(false, format!("{} is not connected", self.device))
+        }
+    }
+
     fn create(&self) -> Result<ConditionScheme, AutoPilotError> {
         let device = Input::with_theme(&ColorfulTheme::default())
             .with_prompt("Enter device name :")
@@ -164,11 +173,12 @@ fn check_bluetooth_linux(output: &str, target_device: &str, match_by_mac: bool)
 }
 
 /// Scheme for deserializing BluetoothCondition from JSON/JSONC
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct BluetoothConditionScheme {
     /// The Bluetooth device address or name to match
     #[serde(default)]
+    #[schema(required = true)]
     pub device: String,
     /// Match by MAC address (true) or device name (false). Defaults to false.
     #[serde(default)]
@@ -1,8 +1,11 @@
+use std::process::ExitStatus;
+
 use dialoguer::{Confirm, Editor, Input, Select, theme::ColorfulTheme};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::{
-    conditions::{Condition, ConditionScheme},
+    conditions::{Condition, ConditionScheme, Probe},
     cross_platform::get::get_supported_editors,
     error::AutoPilotError,
 };
@@ -49,11 +52,7 @@ impl CommandCondition {
 
 impl Condition for CommandCondition {
     fn check(&self) -> bool {
-        sync_condition(
-            &self.command,
-            self.check_exit_code,
-            self.target_output.as_deref(),
-        )
+        self.probe().satisfied
     }
 
     fn clone_box(&self) -> Box<dyn Condition> {
@@ -64,6 +63,21 @@ impl Condition for CommandCondition {
         "Command"
     }
 
+    fn probe(&self) -> Probe {
+        match run_command(&self.command) {
+            Ok((status, output)) => Probe::new(
+                is_satisfied(
+                    status.success(),
+                    &output,
+                    self.check_exit_code,
+                    self.target_output.as_deref(),
+                ),
+                format!("{}, output {:?}", status, output),
+            ),
+            Err(err) => Probe::new(false, format!("failed to run: {}", err)),
+        }
+    }
+
     fn create(&self) -> Result<ConditionScheme, AutoPilotError> {
         // let command = Editor::with_theme(&ColorfulTheme::default())
         //     .with_prompt("Enter command to execute:")
@@ -130,38 +144,58 @@ impl Condition for CommandCondition {
     }
 }
 
-/// Check if a custom command condition is satisfied (synchronously)
-pub fn sync_condition(command: &str, check_exit_code: bool, target_output: Option<&str>) -> bool {
-    match duct_sh::sh_dangerous(command).read() {
-        Ok(output) => {
-            if check_exit_code {
-                true
-            } else if let Some(target) = target_output {
-                output.trim() == target
-            } else {
-                true
-            }
-        }
-        Err(_) => false,
+/// Runs the command to completion, returning its exit status and trimmed standard output
+fn run_command(command: &str) -> std::io::Result<(ExitStatus, String)> {
+    let output = duct_sh::sh_dangerous(command)
+        .stdout_capture()
+        .unchecked()
+        .run()?;
+    Ok((
+        output.status,
+        String::from_utf8_lossy(&output.stdout).trim().to_string(),
+    ))
+}
+
+/// A command that exits non-zero never satisfies the condition, whatever it printed
+fn is_satisfied(
+    success: bool,
+    output: &str,
+    check_exit_code: bool,
+    target_output: Option<&str>,
+) -> bool {
+    if !success {
+        false
+    } else if check_exit_code {
+        true
+    } else if let Some(target) = target_output {
+        output == target
+    } else {
+        true
     }
 }
 
-// fallback to sync_condition for now
+// fallback to the blocking check for now
 pub async fn async_condition(
     command: &str,
     check_exit_code: bool,
     target_output: Option<&str>,
 ) -> bool {
-    // For now, just call sync_condition (until i decide do we even need async)
-    sync_condition(command, check_exit_code, target_output)
+    // For now, just run the check directly (until i decide do we even need async)
+    CommandCondition {
+        command: command.to_string(),
+        check_exit_code,
+        target_output: target_output.map(str::to_string),
+    }
+    .check()
 }
 
 /// Scheme for CommandCondition (JSON Comaptible)
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct CommandConditionScheme {
     /// The shell command to execute
     #[serde(default)]
+    #[schema(required = true)]
     pub command: String,
     /// Check for exit code 0 (true) or match output (false). Defaults to true.
     #[serde(default)]
@@ -170,3 +204,27 @@ pub struct CommandConditionScheme {
     #[serde(default)]
     pub target_output: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_probe_reports_output_and_exit_status() {
+        let probe =
+            CommandCondition::with_output("echo hello".to_string(), "hello".to_string()).probe();
+        assert!(probe.satisfied);
+        let observed = probe.observed.unwrap();
+        assert!(observed.contains("exit status: 0"), "{}", observed);
+        assert!(observed.contains("\"hello\""), "{}", observed);
+    }
+
+    #[test]
+    fn test_command_probe_fails_on_non_zero_exit() {
+        let probe = CommandCondition::new("echo partial; exit 3".to_string()).probe();
+        assert!(!probe.satisfied);
+        let observed = probe.observed.unwrap();
+        assert!(observed.contains("exit status: 3"), "{}", observed);
+        assert!(observed.contains("\"partial\""), "{}", observed);
+    }
+}
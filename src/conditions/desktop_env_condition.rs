@@ -2,9 +2,10 @@ use std::env;
 
 use log::error;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::{
-    conditions::{Condition, ConditionScheme},
+    conditions::{Condition, ConditionScheme, Probe},
     error::AutoPilotError,
 };
 use dialoguer::{Input, theme::ColorfulTheme};
@@ -24,7 +25,7 @@ impl DesktopEnvCondition {
 }
 impl Condition for DesktopEnvCondition {
     fn check(&self) -> bool {
-        sync_condition(&self.target)
+        self.probe().satisfied
     }
 
     fn clone_box(&self) -> Box<dyn Condition> {
@@ -35,6 +36,19 @@ impl Condition for DesktopEnvCondition {
         "Desktop Environment"
     }
 
+    fn probe(&self) -> Probe {
+        match get_current_de() {
+            Some(current_de) => Probe::new(
+                current_de.to_lowercase() == self.target.to_lowercase(),
+                current_de,
+            ),
+            None => {
+                error!("Failed to detect Desktop Environment");
+                Probe::unobserved(false)
+            }
+        }
+    }
+
     fn create(&self) -> Result<ConditionScheme, AutoPilotError> {
         let target = Input::with_theme(&ColorfulTheme::default())
             .with_prompt("Enter target Desktop Environment name:")
@@ -47,40 +61,33 @@ impl Condition for DesktopEnvCondition {
     }
 }
 
-pub fn sync_condition(target: &str) -> bool {
-    match get_current_de() {
-        Some(current_de) => current_de.to_lowercase() == target.to_lowercase(),
-        None => {
-            error!("Failed to detect Desktop Environment");
-            false
-        }
-    }
-}
-
 pub async fn async_condition(target: &str) -> bool {
-    sync_condition(target)
+    DesktopEnvCondition::new(target.to_string()).check()
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct DesktopEnvConditionScheme {
     #[serde(default)]
+    #[schema(required = true)]
     target: String,
 }
 
 /// Detects the current Desktop Environment using a robust fallback chain.
-fn get_current_de() -> Option<String> {
+pub fn get_current_de() -> Option<String> {
     // 1. XDG_CURRENT_DESKTOP (Most standard on modern Linux)
     if let Ok(val) = env::var("XDG_CURRENT_DESKTOP")
-        && !val.is_empty() {
-            return Some(val);
-        }
+        && !val.is_empty()
+    {
+        return Some(val);
+    }
 
     // 2. DESKTOP_SESSION (Common fallback)
     if let Ok(val) = env::var("DESKTOP_SESSION")
-        && !val.is_empty() {
-            return Some(val);
-        }
+        && !val.is_empty()
+    {
+        return Some(val);
+    }
 
     // 3. GNOME Specific
     if env::var("GNOME_DESKTOP_SESSION_ID").is_ok() {
@@ -1,10 +1,11 @@
 use crate::{
-    conditions::{Condition, ConditionScheme},
+    conditions::{Condition, ConditionScheme, Probe},
     error::AutoPilotError,
 };
 use dialoguer::{Input, theme::ColorfulTheme};
 use serde::{Deserialize, Serialize};
-use sysinfo::{Disks, System};
+use sysinfo::Disks;
+use utoipa::ToSchema;
 
 /// Represents a disk space condition
 #[derive(Clone)]
@@ -37,38 +38,7 @@ impl DiskSpaceCondition {
 
 impl Condition for DiskSpaceCondition {
     fn check(&self) -> bool {
-        let _sys = System::new_all();
-        let disks = Disks::new_with_refreshed_list();
-        for disk in &disks {
-            let disk_path = disk.mount_point().to_string_lossy();
-
-            // Check if this disk matches our target path
-            if disk_path
-                .to_lowercase()
-                .starts_with(&self.path.to_lowercase())
-            {
-                let available_gb = disk.available_space() as f64 / (1024.0 * 1024.0 * 1024.0);
-
-                // Check minimum free space
-                if available_gb < self.min_free_gb {
-                    return false;
-                }
-
-                // Check maximum used space if specified
-                if let Some(max_used) = self.max_used_gb {
-                    let total_gb = disk.total_space() as f64 / (1024.0 * 1024.0 * 1024.0);
-                    let used_gb = total_gb - available_gb;
-                    if used_gb > max_used {
-                        return false;
-                    }
-                }
-
-                return true;
-            }
-        }
-
-        // If we get here, the specified path wasn't found
-        false
+        self.probe().satisfied
     }
 
     fn clone_box(&self) -> Box<dyn Condition> {
@@ -79,6 +49,37 @@ impl Condition for DiskSpaceCondition {
         "Disk Space"
     }
 
+    fn probe(&self) -> Probe {
+        let disks = Disks::new_with_refreshed_list();
+        // The first disk whose mount point starts with the path is the one checked
+        let Some(disk) = disks.iter().find(|disk| {
+            disk.mount_point()
+                .to_string_lossy()
+                .to_lowercase()
+                .starts_with(&self.path.to_lowercase())
+        }) else {
+            return Probe::new(false, format!("no disk is mounted at {}", self.path));
+        };
+
+        let gb = 1024.0 * 1024.0 * 1024.0;
+        let available_gb = disk.available_space() as f64 / gb;
+        let total_gb = disk.total_space() as f64 / gb;
+        // Enough free space, and no more used than allowed if a maximum is set
+        let satisfied = available_gb >= self.min_free_gb
+            && self
+                .max_used_gb
+                .is_none_or(|max_used| total_gb - available_gb <= max_used);
+        Probe::new(
+            satisfied,
+            format!(
+                "{} has {:.1} GB free of {:.1} GB",
+                disk.mount_point().display(),
+                available_gb,
+                total_gb
+            ),
+        )
+    }
+
     fn create(&self) -> Result<ConditionScheme, AutoPilotError> {
         let path = Input::with_theme(&ColorfulTheme::default())
             .with_prompt("Enter path to check disk space (e.g., /, C:\\, /home):")
@@ -116,12 +117,14 @@ impl Condition for DiskSpaceCondition {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct DiskSpaceConditionScheme {
     #[serde(default)]
+    #[schema(required = true)]
     pub path: String,
     #[serde(default)]
+    #[schema(required = true)]
     pub min_free_gb: f64,
     #[serde(default)]
     pub max_used_gb: Option<f64>,
@@ -1,10 +1,11 @@
 use crate::{
-    conditions::{Condition, ConditionScheme},
+    conditions::{Condition, ConditionScheme, Probe},
     error::AutoPilotError,
 };
 use dialoguer::{Confirm, Input, theme::ColorfulTheme};
 use serde::{Deserialize, Serialize};
 use sysinfo::Disks;
+use utoipa::ToSchema;
 
 /// Represents an external device (USB/mount) condition
 #[derive(Clone)]
@@ -33,38 +34,49 @@ impl ExternalDeviceCondition {
 
 impl Condition for ExternalDeviceCondition {
     fn check(&self) -> bool {
+        self.probe().satisfied
+    }
+
+    fn clone_box(&self) -> Box<dyn Condition> {
+        Box::new(self.clone())
+    }
+
+    fn name(&self) -> &str {
+        "External Device"
+    }
+
+    fn probe(&self) -> Probe {
         let disks = Disks::new_with_refreshed_list();
 
-        for disk in &disks {
+        let found = disks.iter().find(|disk| {
             let disk_name = disk.name().to_string_lossy().to_lowercase();
             let mount_point = disk.mount_point().to_string_lossy().to_lowercase();
 
             if self.check_by_name {
                 // Check if the disk name contains our identifier
-                if disk_name.contains(&self.device_identifier)
+                disk_name.contains(&self.device_identifier)
                     || self.device_identifier.contains(&disk_name)
-                {
-                    return true;
-                }
             } else {
                 // Check if the mount point contains our identifier
-                if mount_point.contains(&self.device_identifier)
+                mount_point.contains(&self.device_identifier)
                     || self.device_identifier.contains(&mount_point)
-                {
-                    return true;
-                }
             }
-        }
-
-        false
-    }
-
-    fn clone_box(&self) -> Box<dyn Condition> {
-        Box::new(self.clone())
-    }
+        });
 
-    fn name(&self) -> &str {
-        "External Device"
+        match found {
+            Some(disk) => Probe::new(
+                true,
+                format!(
+                    "{} mounted at {}",
+                    disk.name().to_string_lossy(),
+                    disk.mount_point().display()
+                ),
+            ),
+            None => Probe::new(
+                false,
+                format!("no matching device among {} disk(s)", disks.list().len()),
+            ),
+        }
     }
 
     fn create(&self) -> Result<ConditionScheme, AutoPilotError> {
@@ -88,10 +100,11 @@ impl Condition for ExternalDeviceCondition {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct ExternalDeviceConditionScheme {
     #[serde(default)]
+    #[schema(required = true)]
     pub device_identifier: String,
     #[serde(default)]
     pub check_by_name: Option<bool>,
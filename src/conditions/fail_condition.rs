@@ -1,9 +1,10 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::{conditions::Condition, error::AutoPilotError};
 
 /// A condition that always returns false, used as a fallback for error cases
-#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+#[derive(Clone, Debug, Deserialize, Serialize, Default, ToSchema)]
 pub struct FailCondition;
 
 impl Condition for FailCondition {
@@ -1,12 +1,15 @@
 use crate::{
-    conditions::{Condition, ConditionScheme},
+    conditions::{Condition, ConditionScheme, Probe, string_enum_schema},
     error::AutoPilotError,
 };
 use chrono::{DateTime, Duration, Local};
 use dialoguer::{Input, Select, theme::ColorfulTheme};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use utoipa::{ToSchema, openapi::schema::Object};
+
+/// Values of `check_type` that `check` understands, in any case
+pub const CHECK_TYPES: [&str; 3] = ["exists", "modified_recently", "size_changed"];
 
 /// Represents a file/path monitor condition
 #[derive(Clone)]
@@ -48,52 +51,7 @@ impl FileCondition {
 
 impl Condition for FileCondition {
     fn check(&self) -> bool {
-        let path = Path::new(&self.path);
-
-        match self.check_type.as_str() {
-            "exists" => path.exists(),
-
-            "modified_recently" => {
-                if !path.exists() {
-                    return false;
-                }
-
-                let metadata = match fs::metadata(path) {
-                    Ok(metadata) => metadata,
-                    Err(_) => return false,
-                };
-
-                let modified_time = match metadata.modified() {
-                    Ok(time) => time,
-                    Err(_) => return false,
-                };
-
-                let modified_local: DateTime<Local> = modified_time.into();
-                let now = Local::now();
-                let threshold_seconds = self.time_threshold.unwrap_or(300);
-                let threshold_duration = Duration::seconds(threshold_seconds);
-
-                now.signed_duration_since(modified_local) < threshold_duration
-            }
-
-            "size_changed" => {
-                if !path.exists() {
-                    return false;
-                }
-
-                let metadata = match fs::metadata(path) {
-                    Ok(metadata) => metadata,
-                    Err(_) => return false,
-                };
-
-                let current_size = metadata.len();
-                let threshold_size = self.size_threshold.unwrap_or(0);
-
-                current_size >= threshold_size
-            }
-
-            _ => false,
-        }
+        self.probe().satisfied
     }
 
     fn clone_box(&self) -> Box<dyn Condition> {
@@ -104,6 +62,30 @@ impl Condition for FileCondition {
         "File"
     }
 
+    fn probe(&self) -> Probe {
+        let Ok(metadata) = fs::metadata(&self.path) else {
+            return Probe::new(false, format!("{} does not exist", self.path));
+        };
+        let modified: Option<DateTime<Local>> = metadata.modified().ok().map(Into::into);
+
+        let satisfied = match self.check_type.as_str() {
+            "exists" => true,
+            "modified_recently" => modified.is_some_and(|modified_local| {
+                let threshold_seconds = self.time_threshold.unwrap_or(300);
+                Local::now().signed_duration_since(modified_local)
+                    < Duration::seconds(threshold_seconds)
+            }),
+            "size_changed" => metadata.len() >= self.size_threshold.unwrap_or(0),
+            _ => false,
+        };
+
+        let mut observed = format!("{} exists, {} bytes", self.path, metadata.len());
+        if let Some(modified) = modified {
+            observed += &format!(", modified {}", modified.format("%Y-%m-%d %H:%M:%S"));
+        }
+        Probe::new(satisfied, observed)
+    }
+
     fn create(&self) -> Result<ConditionScheme, AutoPilotError> {
         let path = Input::with_theme(&ColorfulTheme::default())
             .with_prompt("Enter file or directory path to monitor:")
@@ -167,15 +149,21 @@ impl Condition for FileCondition {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct FileConditionScheme {
     #[serde(default)]
+    #[schema(required = true)]
     pub path: String,
     #[serde(default)]
+    #[schema(required = true, schema_with = check_type_schema)]
     pub check_type: String,
     #[serde(default)]
     pub time_threshold: Option<i64>,
     #[serde(default)]
     pub size_threshold: Option<u64>,
 }
+
+fn check_type_schema() -> Object {
+    string_enum_schema(&CHECK_TYPES)
+}
@@ -1,9 +1,10 @@
 use crate::{
-    conditions::{Condition, ConditionScheme},
+    conditions::{Condition, ConditionScheme, Probe},
     error::AutoPilotError,
 };
 use dialoguer::{Input, theme::ColorfulTheme};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// Represents an internet reachability condition (ping based)
 #[derive(Clone)]
@@ -29,50 +30,7 @@ impl InternetCondition {
 
 impl Condition for InternetCondition {
     fn check(&self) -> bool {
-        let timeout_str = self.timeout.to_string();
-
-        #[cfg(target_os = "windows")]
-        {
-            // Windows ping: -n is count, -w is timeout in ms
-            let ms_timeout = (self.timeout * 1000).to_string();
-            duct_sh::sh_dangerous(
-                vec!["ping", "-n", "1", "-w", &ms_timeout, &self.host]
-                    .join(" ")
-                    .to_string(),
-            )
-            .run()
-            .is_ok()
-        }
-
-        #[cfg(target_os = "macos")]
-        {
-            // macOS ping: -c is count, -t is timeout in seconds
-            duct_sh::sh_dangerous(
-                vec!["ping", "-c", "1", "-t", &timeout_str, &self.host]
-                    .join(" ")
-                    .to_string(),
-            )
-            .run()
-            .is_ok()
-        }
-
-        #[cfg(target_os = "linux")]
-        {
-            // Linux ping: -c is count, -W is timeout in seconds
-            duct_sh::sh_dangerous(
-                ["ping", "-c", "1", "-W", &timeout_str, &self.host]
-                    .join(" ")
-                    .to_string(),
-            )
-            .run()
-            .is_ok()
-        }
-
-        #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
-        {
-            warn!("Internet condition not supported on this platform");
-            false
-        }
+        self.probe().satisfied
     }
 
     fn clone_box(&self) -> Box<dyn Condition> {
@@ -83,6 +41,35 @@ impl Condition for InternetCondition {
         "Internet"
     }
 
+    fn probe(&self) -> Probe {
+        let Some(command) = self.ping_command() else {
+            return Probe::unobserved(false);
+        };
+        match duct_sh::sh_dangerous(command)
+            .stdout_capture()
+            .stderr_capture()
+            .unchecked()
+            .run()
+        {
+            Ok(output) if output.status.success() => {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                // Every platform's ping reports the round trip as `time=12.3 ms` or `time<1ms`
+                let round_trip = stdout
+                    .split_whitespace()
+                    .find(|word| word.starts_with("time=") || word.starts_with("time<"));
+                match round_trip {
+                    Some(time) => Probe::new(true, format!("{} replied, {}", self.host, time)),
+                    None => Probe::new(true, format!("{} replied", self.host)),
+                }
+            }
+            Ok(_) => Probe::new(
+                false,
+                format!("{} did not reply within {}s", self.host, self.timeout),
+            ),
+            Err(err) => Probe::new(false, format!("failed to run ping: {}", err)),
+        }
+    }
+
     fn create(&self) -> Result<ConditionScheme, AutoPilotError> {
         let host: String = Input::with_theme(&ColorfulTheme::default())
             .with_prompt("Enter host to ping (default: 8.8.8.8):")
@@ -111,7 +98,39 @@ impl Condition for InternetCondition {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+impl InternetCondition {
+    /// Command line that pings the host once, or `None` on unsupported platforms
+    fn ping_command(&self) -> Option<String> {
+        #[cfg(target_os = "windows")]
+        {
+            // Windows ping: -n is count, -w is timeout in ms
+            let ms_timeout = (self.timeout * 1000).to_string();
+            Some(["ping", "-n", "1", "-w", &ms_timeout, &self.host].join(" "))
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            // macOS ping: -c is count, -t is timeout in seconds
+            let timeout_str = self.timeout.to_string();
+            Some(["ping", "-c", "1", "-t", &timeout_str, &self.host].join(" "))
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            // Linux ping: -c is count, -W is timeout in seconds
+            let timeout_str = self.timeout.to_string();
+            Some(["ping", "-c", "1", "-W", &timeout_str, &self.host].join(" "))
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+        {
+            warn!("Internet condition not supported on this platform");
+            None
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct InternetConditionScheme {
     #[serde(default)]
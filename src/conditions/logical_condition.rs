@@ -6,6 +6,7 @@ use colored::Colorize;
 use dialoguer::{Confirm, Select, theme::ColorfulTheme};
 use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
+use utoipa::ToSchema;
 
 #[derive(Clone)]
 pub struct LogicalCondition {
@@ -13,7 +14,7 @@ pub struct LogicalCondition {
     conditions: Vec<Box<dyn Condition>>,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Deserialize, Serialize, ToSchema)]
 pub enum LogicalOperator {
     #[default]
     And,
@@ -22,7 +23,7 @@ pub enum LogicalOperator {
 }
 
 impl LogicalOperator {
-    fn name(&self) -> &'static str {
+    pub fn name(&self) -> &'static str {
         match self {
             LogicalOperator::And => "AND",
             LogicalOperator::Or => "OR",
@@ -30,7 +31,7 @@ impl LogicalOperator {
         }
     }
 
-    fn evaluate(&self, results: &[bool]) -> bool {
+    pub fn evaluate(&self, results: &[bool]) -> bool {
         match self {
             LogicalOperator::And => results.iter().all(|&x| x),
             LogicalOperator::Or => results.iter().any(|&x| x),
@@ -164,12 +165,14 @@ impl Condition for LogicalCondition {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default, ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct LogicalConditionScheme {
     #[serde(rename = "operator")]
-    operator: LogicalOperator,
+    pub operator: LogicalOperator,
 
     #[serde(default)]
-    conditions: Vec<ConditionScheme>,
+    // Refers back to ConditionScheme, which the schema generator must not expand again
+    #[schema(no_recursion, required = true)]
+    pub conditions: Vec<ConditionScheme>,
 }
@@ -1,5 +1,9 @@
 use serde::{Deserialize, Serialize};
 use strum::{EnumIter, IntoEnumIterator};
+use utoipa::{
+    ToSchema,
+    openapi::schema::{Object, ObjectBuilder, Type},
+};
 
 use crate::error::AutoPilotError;
 
@@ -34,6 +38,45 @@ pub trait Condition: Send + Sync {
     fn create(&self) -> Result<ConditionScheme, AutoPilotError>;
 
     fn name(&self) -> &str;
+
+    /// Checks the condition once, along with the live value the verdict was reached on, so the
+    /// two always agree
+    fn probe(&self) -> Probe {
+        Probe::unobserved(self.check())
+    }
+}
+
+/// Verdict of a condition and a description of the value it was checked against, if it can be
+/// observed
+#[derive(Clone, Debug)]
+pub struct Probe {
+    pub satisfied: bool,
+    pub observed: Option<String>,
+}
+
+impl Probe {
+    pub fn new(satisfied: bool, observed: impl Into<String>) -> Self {
+        Self {
+            satisfied,
+            observed: Some(observed.into()),
+        }
+    }
+
+    pub fn unobserved(satisfied: bool) -> Self {
+        Self {
+            satisfied,
+            observed: None,
+        }
+    }
+}
+
+/// Schema of a string field that only takes one of `values`, for fields the daemon matches
+/// against a fixed list
+pub fn string_enum_schema(values: &[&str]) -> Object {
+    ObjectBuilder::new()
+        .schema_type(Type::String)
+        .enum_values(Some(values.iter().copied()))
+        .build()
 }
 
 /// Implement Clone for Box<dyn Condition>
@@ -44,7 +87,7 @@ impl Clone for Box<dyn Condition> {
 }
 
 /// Unified enum for all condition types, supporting deserialization from JSON/JSONC
-#[derive(Clone, Debug, Serialize, Deserialize, EnumIter)]
+#[derive(Clone, Debug, Serialize, Deserialize, EnumIter, ToSchema)]
 #[serde(tag = "type", content = "condition", rename_all = "lowercase")]
 pub enum ConditionScheme {
     /// Command condition: checks if command output matches a target value
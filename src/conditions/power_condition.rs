@@ -1,9 +1,13 @@
 use crate::{
-    conditions::{Condition, ConditionScheme},
+    conditions::{Condition, ConditionScheme, Probe, string_enum_schema},
     error::AutoPilotError,
 };
 use dialoguer::{Confirm, Input, Select, theme::ColorfulTheme};
 use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, openapi::schema::Object};
+
+/// Values of `operator` that `check` tells apart, anything else compares greater
+pub const OPERATORS: [&str; 4] = ["greater", ">", "less", "<"];
 
 /// Represents a power/battery condition
 #[derive(Clone)]
@@ -40,108 +44,101 @@ impl PowerCondition {
             operator: scheme.operator.map(|s| s.to_lowercase()),
         }
     }
-}
 
-impl Condition for PowerCondition {
-    fn check(&self) -> bool {
+    /// Whether the machine runs on AC power, `None` when that can't be read
+    fn read_on_ac_power() -> Option<bool> {
         #[cfg(target_os = "linux")]
         {
-            if self.check_charging {
-                // Try multiple common paths for AC adapter status
-                let paths = [
-                    "/sys/class/power_supply/AC/online",
-                    "/sys/class/power_supply/ACAD/online",
-                ];
-                for path in paths {
-                    if let Ok(status) = std::fs::read_to_string(path) {
-                        return status.trim() == "1";
-                    }
-                }
-                false
-            } else if let Some(threshold) = self.threshold {
-                if let Ok(capacity) =
-                    std::fs::read_to_string("/sys/class/power_supply/BAT0/capacity")
-                    && let Ok(val) = capacity.trim().parse::<f32>()
-                {
-                    return match self.operator.as_deref() {
-                        Some("less") | Some("<") => val < threshold,
-                        _ => val > threshold,
-                    };
-                }
-                false
-            } else {
-                false
-            }
+            // Try multiple common paths for AC adapter status
+            [
+                "/sys/class/power_supply/AC/online",
+                "/sys/class/power_supply/ACAD/online",
+            ]
+            .iter()
+            .find_map(|path| std::fs::read_to_string(path).ok())
+            .map(|status| status.trim() == "1")
         }
 
         #[cfg(target_os = "macos")]
         {
-            if let Ok(output) = duct::cmd("pmset", vec!["-g", "batt"]).read() {
-                if self.check_charging {
-                    return output.contains("AC Power");
-                } else if let Some(threshold) = self.threshold {
-                    // Output example: " -InternalBattery-0 (id=123)	100%; charged; 0:00 remaining"
-                    if let Some(pct_pos) = output.find('%') {
-                        let start = output[..pct_pos]
-                            .rfind(|c: char| !c.is_numeric())
-                            .unwrap_or(0);
-                        if let Ok(val) = output[start..pct_pos].trim().parse::<f32>() {
-                            return match self.operator.as_deref() {
-                                Some("less") | Some("<") => val < threshold,
-                                _ => val > threshold,
-                            };
-                        }
-                    }
-                }
-            }
-            false
+            duct::cmd("pmset", vec!["-g", "batt"])
+                .read()
+                .ok()
+                .map(|output| output.contains("AC Power"))
         }
 
         #[cfg(target_os = "windows")]
         {
-            if self.check_charging {
-                // 2 = AC, 1 = Battery
-                if let Ok(output) = duct::cmd(
-                    "powershell",
-                    vec![
-                        "-Command",
-                        "(Get-CimInstance -ClassName Win32_Battery).BatteryStatus",
-                    ],
-                )
-                .read()
-                {
-                    return output.trim() == "2";
-                }
-                false
-            } else if let Some(threshold) = self.threshold {
-                if let Ok(output) = duct::cmd(
-                    "powershell",
-                    vec![
-                        "-Command",
-                        "(Get-CimInstance -ClassName Win32_Battery).EstimatedChargeRemaining",
-                    ],
-                )
-                .read()
-                {
-                    if let Ok(val) = output.trim().parse::<f32>() {
-                        return match self.operator.as_deref() {
-                            Some("less") | Some("<") => val < threshold,
-                            _ => val > threshold,
-                        };
-                    }
-                }
-                false
-            } else {
-                false
-            }
+            // 2 = AC, 1 = Battery
+            duct::cmd(
+                "powershell",
+                vec![
+                    "-Command",
+                    "(Get-CimInstance -ClassName Win32_Battery).BatteryStatus",
+                ],
+            )
+            .read()
+            .ok()
+            .map(|output| output.trim() == "2")
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+        {
+            warn!("Power condition not supported on this platform");
+            None
+        }
+    }
+
+    /// Battery charge in percent, `None` when it can't be read
+    fn read_battery_level() -> Option<f32> {
+        #[cfg(target_os = "linux")]
+        {
+            std::fs::read_to_string("/sys/class/power_supply/BAT0/capacity")
+                .ok()?
+                .trim()
+                .parse::<f32>()
+                .ok()
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            let output = duct::cmd("pmset", vec!["-g", "batt"]).read().ok()?;
+            // Output example: " -InternalBattery-0 (id=123)	100%; charged; 0:00 remaining"
+            let pct_pos = output.find('%')?;
+            let start = output[..pct_pos]
+                .rfind(|c: char| !c.is_numeric())
+                .unwrap_or(0);
+            output[start..pct_pos].trim().parse::<f32>().ok()
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            duct::cmd(
+                "powershell",
+                vec![
+                    "-Command",
+                    "(Get-CimInstance -ClassName Win32_Battery).EstimatedChargeRemaining",
+                ],
+            )
+            .read()
+            .ok()?
+            .trim()
+            .parse::<f32>()
+            .ok()
         }
 
         #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
         {
             warn!("Power condition not supported on this platform");
-            false
+            None
         }
     }
+}
+
+impl Condition for PowerCondition {
+    fn check(&self) -> bool {
+        self.probe().satisfied
+    }
 
     fn clone_box(&self) -> Box<dyn Condition> {
         Box::new(self.clone())
@@ -151,6 +148,29 @@ impl Condition for PowerCondition {
         "Power"
     }
 
+    fn probe(&self) -> Probe {
+        if self.check_charging {
+            match Self::read_on_ac_power() {
+                Some(true) => Probe::new(true, "on AC power"),
+                Some(false) => Probe::new(false, "on battery"),
+                None => Probe::unobserved(false),
+            }
+        } else if let Some(threshold) = self.threshold {
+            match Self::read_battery_level() {
+                Some(level) => {
+                    let satisfied = match self.operator.as_deref() {
+                        Some("less") | Some("<") => level < threshold,
+                        _ => level > threshold,
+                    };
+                    Probe::new(satisfied, format!("battery {}%", level))
+                }
+                None => Probe::unobserved(false),
+            }
+        } else {
+            Probe::unobserved(false)
+        }
+    }
+
     fn create(&self) -> Result<ConditionScheme, AutoPilotError> {
         let check_charging = Confirm::with_theme(&ColorfulTheme::default())
             .with_prompt("Check if charging? (Otherwise check battery level)")
@@ -192,7 +212,7 @@ impl Condition for PowerCondition {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct PowerConditionScheme {
     #[serde(default)]
@@ -200,5 +220,10 @@ pub struct PowerConditionScheme {
     #[serde(default)]
     pub threshold: Option<f32>,
     #[serde(default)]
+    #[schema(schema_with = operator_schema)]
     pub operator: Option<String>,
 }
+
+fn operator_schema() -> Object {
+    string_enum_schema(&OPERATORS)
+}
@@ -1,10 +1,11 @@
 use crate::{
-    conditions::{Condition, ConditionScheme},
+    conditions::{Condition, ConditionScheme, Probe},
     error::AutoPilotError,
 };
 use dialoguer::{Confirm, Input, theme::ColorfulTheme};
 use serde::{Deserialize, Serialize};
 use sysinfo::System;
+use utoipa::ToSchema;
 
 /// Represents a process monitor condition
 #[derive(Clone)]
@@ -29,27 +30,25 @@ impl ProcessCondition {
             should_be_running: scheme.should_be_running.unwrap_or(true),
         }
     }
-}
 
-impl Condition for ProcessCondition {
-    fn check(&self) -> bool {
+    /// Whether a process matching the configured name is currently running
+    fn is_running(&self) -> bool {
         let mut sys = System::new_all();
         sys.refresh_all();
 
-        let mut found = false;
         for process in sys.processes().values() {
             let proc_name = process.name().to_string_lossy().to_lowercase();
             if proc_name.contains(&self.process_name) || self.process_name.contains(&proc_name) {
-                found = true;
-                break;
+                return true;
             }
         }
+        false
+    }
+}
 
-        if self.should_be_running {
-            found
-        } else {
-            !found
-        }
+impl Condition for ProcessCondition {
+    fn check(&self) -> bool {
+        self.probe().satisfied
     }
 
     fn clone_box(&self) -> Box<dyn Condition> {
@@ -60,6 +59,16 @@ impl Condition for ProcessCondition {
         "Process"
     }
 
+    fn probe(&self) -> Probe {
+        let found = self.is_running();
+        let observed = if found {
+            format!("{} is running", self.process_name)
+        } else {
+            format!("{} is not running", self.process_name)
+        };
+        Probe::new(found == self.should_be_running, observed)
+    }
+
     fn create(&self) -> Result<ConditionScheme, AutoPilotError> {
         let process_name = Input::with_theme(&ColorfulTheme::default())
             .with_prompt("Enter process name to monitor:")
@@ -79,10 +88,11 @@ impl Condition for ProcessCondition {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct ProcessConditionScheme {
     #[serde(default)]
+    #[schema(required = true)]
     pub process_name: String,
     #[serde(default)]
     pub should_be_running: Option<bool>,
@@ -1,10 +1,16 @@
 use crate::{
-    conditions::{Condition, ConditionScheme},
+    conditions::{Condition, ConditionScheme, Probe, string_enum_schema},
     error::AutoPilotError,
 };
 use dialoguer::{Input, Select, theme::ColorfulTheme};
 use serde::{Deserialize, Serialize};
 use sysinfo::{CpuRefreshKind, MemoryRefreshKind, System};
+use utoipa::{ToSchema, openapi::schema::Object};
+
+/// Values of `resource_type` that `check` understands, in any case
+pub const RESOURCE_TYPES: [&str; 3] = ["cpu", "memory", "ram"];
+/// Values of `operator` that `check` understands, in any case
+pub const OPERATORS: [&str; 6] = ["greater", "gt", ">", "less", "lt", "<"];
 
 /// Represents a system resource condition (CPU or RAM)
 #[derive(Clone)]
@@ -36,10 +42,9 @@ impl ResourceCondition {
                 .to_lowercase(),
         }
     }
-}
 
-impl Condition for ResourceCondition {
-    fn check(&self) -> bool {
+    /// Current usage percentage of the monitored resource
+    fn current_value(&self) -> Option<f32> {
         let mut sys = System::new_with_specifics(
             sysinfo::RefreshKind::nothing()
                 .with_cpu(CpuRefreshKind::everything())
@@ -52,25 +57,25 @@ impl Condition for ResourceCondition {
             sys.refresh_cpu_all();
         }
 
-        let current_value = match self.resource_type.as_str() {
-            "cpu" => sys.global_cpu_usage(),
+        match self.resource_type.as_str() {
+            "cpu" => Some(sys.global_cpu_usage()),
             "memory" | "ram" => {
                 let total = sys.total_memory();
                 if total == 0 {
-                    0.0
+                    Some(0.0)
                 } else {
-                    (sys.used_memory() as f32 / total as f32) * 100.0
+                    Some((sys.used_memory() as f32 / total as f32) * 100.0)
                 }
             }
-            _ => return false,
-        };
-
-        match self.operator.as_str() {
-            "greater" | "gt" | ">" => current_value > self.threshold,
-            "less" | "lt" | "<" => current_value < self.threshold,
-            _ => false,
+            _ => None,
         }
     }
+}
+
+impl Condition for ResourceCondition {
+    fn check(&self) -> bool {
+        self.probe().satisfied
+    }
 
     fn clone_box(&self) -> Box<dyn Condition> {
         Box::new(self.clone())
@@ -80,6 +85,22 @@ impl Condition for ResourceCondition {
         "Resource"
     }
 
+    fn probe(&self) -> Probe {
+        let Some(current_value) = self.current_value() else {
+            return Probe::unobserved(false);
+        };
+
+        let satisfied = match self.operator.as_str() {
+            "greater" | "gt" | ">" => current_value > self.threshold,
+            "less" | "lt" | "<" => current_value < self.threshold,
+            _ => false,
+        };
+        Probe::new(
+            satisfied,
+            format!("{} usage {:.1}%", self.resource_type, current_value),
+        )
+    }
+
     fn create(&self) -> Result<ConditionScheme, AutoPilotError> {
         let resource_types = ["CPU", "Memory (RAM)"];
         let selected_type = Select::with_theme(&ColorfulTheme::default())
@@ -116,13 +137,24 @@ impl Condition for ResourceCondition {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct ResourceConditionScheme {
     #[serde(default)]
+    #[schema(required = true, schema_with = resource_type_schema)]
     pub resource_type: String,
     #[serde(default)]
+    #[schema(required = true)]
     pub threshold: f32,
     #[serde(default)]
+    #[schema(schema_with = operator_schema)]
     pub operator: Option<String>,
 }
+
+fn resource_type_schema() -> Object {
+    string_enum_schema(&RESOURCE_TYPES)
+}
+
+fn operator_schema() -> Object {
+    string_enum_schema(&OPERATORS)
+}
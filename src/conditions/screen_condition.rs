@@ -1,9 +1,10 @@
 use dialoguer::{Input, theme::ColorfulTheme};
 use display_info::DisplayInfo;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::{
-    conditions::{Condition, ConditionScheme},
+    conditions::{Condition, ConditionScheme, Probe},
     error::AutoPilotError,
 };
 
@@ -38,11 +39,7 @@ impl ScreenCondition {
 
 impl Condition for ScreenCondition {
     fn check(&self) -> bool {
-        sync_condition(
-            self.screen_count,
-            &self.active_screen_name,
-            &self.screen_names,
-        )
+        self.probe().satisfied
     }
 
     fn clone_box(&self) -> Box<dyn Condition> {
@@ -53,6 +50,32 @@ impl Condition for ScreenCondition {
         "Screen"
     }
 
+    fn probe(&self) -> Probe {
+        let Ok(displays) = DisplayInfo::all() else {
+            return Probe::new(false, "screens could not be listed");
+        };
+        let satisfied = displays_match(
+            &displays,
+            self.screen_count,
+            &self.active_screen_name,
+            &self.screen_names,
+        );
+        let names: Vec<String> = displays
+            .iter()
+            .map(|display| {
+                if display.is_primary {
+                    format!("{} (primary)", display.name)
+                } else {
+                    display.name.clone()
+                }
+            })
+            .collect();
+        Probe::new(
+            satisfied,
+            format!("{} screen(s): {}", displays.len(), names.join(", ")),
+        )
+    }
+
     fn create(&self) -> Result<ConditionScheme, AutoPilotError> {
         let screen_count_str: String = Input::with_theme(&ColorfulTheme::default())
             .with_prompt("Enter target screen count (optional, but at least 1 check is required):")
@@ -110,59 +133,50 @@ impl Condition for ScreenCondition {
     }
 }
 
-pub fn sync_condition(
+fn displays_match(
+    displays: &[DisplayInfo],
     expected_count: Option<u16>,
     expected_active_name: &Option<String>,
     expected_names: &Option<Vec<String>>,
 ) -> bool {
-    // Use display-info crate to get reliable, cross-platform display data
-    // without spawning shell processes.
-    match DisplayInfo::all() {
-        Ok(displays) => {
-            // Check count
-            if let Some(count) = expected_count
-                && (displays.len() as u16) != count {
-                    return false;
-                }
+    // Check count
+    if let Some(count) = expected_count
+        && (displays.len() as u16) != count
+    {
+        return false;
+    }
 
-            // Check active screen name (primary display)
-            if let Some(target_active) = expected_active_name {
-                let primary = displays.iter().find(|d| d.is_primary);
-                match primary {
-                    Some(p) => {
-                        // Check exact name match or partial match
-                        if !p.name.contains(target_active) {
-                            return false;
-                        }
-                    }
-                    None => return false, // No primary display found? Should not happen usually.
+    // Check active screen name (primary display)
+    if let Some(target_active) = expected_active_name {
+        let primary = displays.iter().find(|d| d.is_primary);
+        match primary {
+            Some(p) => {
+                // Check exact name match or partial match
+                if !p.name.contains(target_active) {
+                    return false;
                 }
             }
+            None => return false, // No primary display found? Should not happen usually.
+        }
+    }
 
-            // Check specific screen names presence
-            if let Some(targets) = expected_names {
-                if targets.is_empty() {
-                    return true;
-                }
+    // Check specific screen names presence
+    if let Some(targets) = expected_names {
+        if targets.is_empty() {
+            return true;
+        }
 
-                let current_names: Vec<String> = displays.iter().map(|d| d.name.clone()).collect();
+        let current_names: Vec<String> = displays.iter().map(|d| d.name.clone()).collect();
 
-                for target in targets {
-                    let found = current_names.iter().any(|name| name.contains(target));
-                    if !found {
-                        return false;
-                    }
-                }
+        for target in targets {
+            let found = current_names.iter().any(|name| name.contains(target));
+            if !found {
+                return false;
             }
-
-            true
-        }
-        Err(_e) => {
-            // Log error if possible, or return false
-            // eprintln!("Failed to get display info: {}", e);
-            false
         }
     }
+
+    true
 }
 
 pub async fn async_condition(
@@ -173,10 +187,10 @@ pub async fn async_condition(
     // DisplayInfo::all() is synchronous but fast (native API calls)
     // Wrapping in spawn_blocking might be safer if it blocks, but usually it's instant.
     // For now, run directly.
-    sync_condition(expected_count, &expected_active_name, &expected_names)
+    ScreenCondition::new(expected_count, expected_active_name, expected_names).check()
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct ScreenConditionScheme {
     pub screen_count: Option<u16>,
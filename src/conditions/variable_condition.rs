@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::{
-    conditions::{Condition, ConditionScheme},
+    conditions::{Condition, ConditionScheme, Probe},
     error::AutoPilotError,
 };
 use dialoguer::{Input, theme::ColorfulTheme};
@@ -23,7 +24,7 @@ impl VariableCondition {
 }
 impl Condition for VariableCondition {
     fn check(&self) -> bool {
-        sync_condition(&self.variable, &self.target)
+        self.probe().satisfied
     }
 
     fn clone_box(&self) -> Box<dyn Condition> {
@@ -34,6 +35,14 @@ impl Condition for VariableCondition {
         "Variable"
     }
 
+    fn probe(&self) -> Probe {
+        let value = std::env::var(&self.variable).unwrap_or_default();
+        Probe::new(
+            value == self.target,
+            format!("${} = \"{}\"", self.variable, value),
+        )
+    }
+
     fn create(&self) -> Result<ConditionScheme, AutoPilotError> {
         let variable = Input::with_theme(&ColorfulTheme::default())
             .with_prompt("Enter environment variable name (without $):")
@@ -52,21 +61,18 @@ impl Condition for VariableCondition {
     }
 }
 
-pub fn sync_condition(var: &str, target: &str) -> bool {
-    let env_var = std::env::var(var).unwrap_or_default();
-    env_var == target
-}
-
 pub async fn async_condition(var: &str, target: &str) -> bool {
     let env_var = std::env::var(var).unwrap_or_default();
     env_var == target
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct VariableConditionScheme {
     #[serde(default)]
+    #[schema(required = true)]
     variable: String,
     #[serde(default)]
+    #[schema(required = true)]
     target: String,
 }
@@ -1,9 +1,10 @@
 use dialoguer::{Input, theme::ColorfulTheme};
 use duct::cmd;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::{
-    conditions::{Condition, ConditionScheme},
+    conditions::{Condition, ConditionScheme, Probe},
     error::AutoPilotError,
 };
 
@@ -28,7 +29,7 @@ impl WifiCondition {
 
 impl Condition for WifiCondition {
     fn check(&self) -> bool {
-        sync_condition(&self.ssid)
+        self.probe().satisfied
     }
 
     fn clone_box(&self) -> Box<dyn Condition> {
@@ -39,6 +40,13 @@ impl Condition for WifiCondition {
         "WiFi"
     }
 
+    fn probe(&self) -> Probe {
+        match connected_ssid() {
+            Some(ssid) => Probe::new(ssid == self.ssid, format!("connected to {}", ssid)),
+            None => Probe::new(has_profile(&self.ssid), "not connected"),
+        }
+    }
+
     fn create(&self) -> Result<ConditionScheme, AutoPilotError> {
         let ssid = Input::with_theme(&ColorfulTheme::default())
             .with_prompt("Enter WiFi network name (SSID):")
@@ -49,83 +57,77 @@ impl Condition for WifiCondition {
     }
 }
 
-/// Check if the current WiFi network matches the target SSID (synchronously)
-pub fn sync_condition(target_ssid: &str) -> bool {
+/// The SSID of the network currently connected to, if it can be found
+pub fn connected_ssid() -> Option<String> {
     #[cfg(target_os = "linux")]
     {
         // Try nmcli (NetworkManager) first - most common on Linux
         if let Ok(output) = cmd("nmcli", vec!["-t", "-f", "active,ssid", "dev", "wifi"]).read()
-            && let Ok(ssid) = get_connected_wifi_linux(&output) {
-                return ssid == target_ssid;
-            }
-
-        // Fallback to iwgetid if nmcli fails
-        if let Ok(output) = cmd("iwgetid", vec!["-r"]).read() {
-            return output.trim() == target_ssid;
+            && let Ok(ssid) = get_connected_wifi_linux(&output)
+        {
+            return Some(ssid);
         }
 
-        false
+        // Fallback to iwgetid if nmcli fails
+        cmd("iwgetid", vec!["-r"])
+            .read()
+            .ok()
+            .map(|output| output.trim().to_string())
+            .filter(|ssid| !ssid.is_empty())
     }
 
     #[cfg(target_os = "macos")]
     {
         // macOS: use networksetup or ipconfig
         // networksetup is more reliable for SSID
-        let _ssid =
-            if let Ok(output) = cmd("networksetup", vec!["-getairportnetwork", "en0"]).read() {
-                get_connected_wifi_macos(&output)
-            } else {
-                None
-            };
-
-        if let Some(ssid) = _ssid {
-            if ssid == target_ssid {
-                return true;
-            }
+        if let Ok(output) = cmd("networksetup", vec!["-getairportnetwork", "en0"]).read()
+            && let Some(ssid) = get_connected_wifi_macos(&output)
+        {
+            return Some(ssid);
         }
 
         // Fallback for other interfaces if en0 fails
-        if let Ok(output) = cmd("/System/Library/PrivateFrameworks/Apple80211.framework/Versions/Current/Resources/airport", vec!["-I"])
+        cmd("/System/Library/PrivateFrameworks/Apple80211.framework/Versions/Current/Resources/airport", vec!["-I"])
             .read()
-        {
-            if let Some(ssid) = get_connected_wifi_macos_airport(&output) {
-                return ssid == target_ssid;
-            }
-        }
-        false
+            .ok()
+            .and_then(|output| get_connected_wifi_macos_airport(&output))
     }
 
     #[cfg(target_os = "windows")]
     {
         // Windows: use netsh
-        if let Ok(output) = cmd("netsh", vec!["wlan", "show", "interfaces"]).read() {
-            if let Some(ssid) = get_connected_wifi_windows(&output) {
-                return ssid == target_ssid;
-            }
-        }
-
-        // Fallback for Windows: try parsing netsh show current profile
-        if let Ok(output) = cmd("netsh", vec!["wlan", "show", "profiles"]).read() {
-            // This is less reliable but might help in some environments
-            if output.contains(target_ssid) {
-                return true;
-            }
-        }
-        false
+        cmd("netsh", vec!["wlan", "show", "interfaces"])
+            .read()
+            .ok()
+            .and_then(|output| get_connected_wifi_windows(&output))
     }
 
     #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
     {
         warn!("WiFi condition not supported on this platform");
-        false
+        None
+    }
+}
+
+/// Whether a saved network profile names the SSID, used on Windows when the connected network
+/// can't be read
+fn has_profile(target_ssid: &str) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        // This is less reliable but might help in some environments
+        if let Ok(output) = cmd("netsh", vec!["wlan", "show", "profiles"]).read() {
+            return output.contains(target_ssid);
+        }
     }
+    let _ = target_ssid;
+    false
 }
 
 /// Check if the current WiFi network matches the target SSID (asynchronously)
 pub async fn async_condition(target_ssid: &str) -> bool {
-    // For now, just call sync_condition since WiFi checks are typically fast
+    // For now, just run the blocking check since WiFi checks are typically fast
     // In a real async implementation, you'd use tokio::process::Command
-    sync_condition(target_ssid)
+    WifiCondition::new(target_ssid.to_string()).check()
 }
 
 /// Parse SSID from nmcli output (Linux with NetworkManager)
@@ -178,11 +180,12 @@ fn get_connected_wifi_windows(output: &str) -> Option<String> {
 }
 
 /// Scheme for deserializing WifiCondition from JSON/JSONC
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct WifiConditionScheme {
     /// The SSID (network name) to match
     #[serde(default)]
+    #[schema(required = true)]
     pub ssid: String,
 }
 
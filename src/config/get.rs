@@ -0,0 +1,38 @@
+use std::fs;
+
+use crate::{
+    config::{CONFIG, CONFIG_OVERRIDES, Config},
+    error::AutoPilotError,
+    fs::get_config_path,
+    utilities::jsonc_parser::jsonc_parse,
+};
+
+/// The configuration currently in effect, or the defaults before it's loaded
+pub fn get_config() -> Config {
+    match CONFIG.get() {
+        Some(config) => config.read().map(|c| c.clone()).unwrap_or_default(),
+        None => Config::default(),
+    }
+}
+
+/// Reads `autopilot.jsonc` and applies environment variables and command line overrides on top
+pub fn load_config() -> Result<Config, AutoPilotError> {
+    let config_path = get_config_path();
+    let mut config = match fs::read_to_string(&config_path) {
+        Ok(content) => serde_json::from_str::<Config>(jsonc_parse(&content).as_str())
+            .map_err(|e| AutoPilotError::Config(format!("{}: {}", config_path, e)))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Config::default(),
+        Err(e) => {
+            return Err(AutoPilotError::Config(format!(
+                "Failed to read {}: {}",
+                config_path, e
+            )));
+        }
+    };
+    config.apply_env(|name| std::env::var(name).ok())?;
+    if let Some(overrides) = CONFIG_OVERRIDES.get() {
+        config.apply_overrides(overrides);
+    }
+    config.validate()?;
+    Ok(config)
+}
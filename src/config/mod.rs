@@ -0,0 +1,419 @@
+use std::{
+    fmt,
+    net::SocketAddr,
+    path::PathBuf,
+    str::FromStr,
+    sync::{OnceLock, RwLock},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AutoPilotError;
+
+pub mod get;
+pub use get::*;
+pub mod set;
+pub use set::*;
+
+/// The configuration currently in effect, replaced as a whole on reload
+pub static CONFIG: OnceLock<RwLock<Config>> = OnceLock::new();
+/// Values given on the command line, kept around so reloads apply them again
+pub static CONFIG_OVERRIDES: OnceLock<ConfigOverrides> = OnceLock::new();
+
+/// Global settings read from `autopilot.jsonc`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    #[serde(rename = "$schema", skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    pub api: ApiConfig,
+    pub logging: LoggingConfig,
+    pub defaults: DefaultsConfig,
+    pub concurrency: ConcurrencyConfig,
+    pub paths: PathsConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiConfig {
+    /// Where the API listens, an empty list disables it
+    pub listen: Vec<ListenAddress>,
+    /// Bearer tokens accepted by the API, no tokens means no authentication
+    pub tokens: Vec<ApiToken>,
+    /// Serves the TCP listeners over HTTPS when set
+    pub tls: Option<TlsConfig>,
+    /// Also offers the event stream over a WebSocket at `/events/ws`
+    pub websocket: bool,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            listen: vec![ListenAddress::Tcp(SocketAddr::from(([127, 0, 0, 1], 3000)))],
+            tokens: vec![],
+            tls: None,
+            websocket: false,
+        }
+    }
+}
+
+/// PEM files for the API's TLS listeners
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    /// Certificate chain, leaf first
+    pub cert: String,
+    pub key: String,
+    /// When set, clients must present a certificate issued by one of these CAs
+    #[serde(default)]
+    pub client_ca: Option<String>,
+    /// Certificate and key local commands present to the daemon, needed once `client_ca` is set
+    #[serde(default)]
+    pub client_cert: Option<String>,
+    #[serde(default)]
+    pub client_key: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ApiToken {
+    /// Shown in the audit log instead of the token itself
+    pub name: String,
+    pub token: String,
+    #[serde(default)]
+    pub role: ApiRole,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiRole {
+    /// Only `GET` routes
+    #[default]
+    Read,
+    /// Every route, including creating, changing and running jobs
+    Admin,
+}
+
+/// `IP:port` or `unix:<path>` (a bare absolute path works too)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ListenAddress {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl FromStr for ListenAddress {
+    type Err = AutoPilotError;
+
+    fn from_str(address: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = address.strip_prefix("unix:") {
+            return Ok(Self::Unix(PathBuf::from(path)));
+        }
+        if address.starts_with('/') {
+            return Ok(Self::Unix(PathBuf::from(address)));
+        }
+        address.parse::<SocketAddr>().map(Self::Tcp).map_err(|_| {
+            AutoPilotError::Config(format!(
+                "'{}' is neither an IP:port address nor unix:<path>",
+                address
+            ))
+        })
+    }
+}
+
+impl TryFrom<String> for ListenAddress {
+    type Error = AutoPilotError;
+
+    fn try_from(address: String) -> Result<Self, Self::Error> {
+        address.parse()
+    }
+}
+
+impl From<ListenAddress> for String {
+    fn from(address: ListenAddress) -> Self {
+        address.to_string()
+    }
+}
+
+impl fmt::Display for ListenAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(address) => write!(f, "{}", address),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    /// Log level used when not running with `--verbose`
+    pub level: String,
+    /// Where log records are written to
+    pub sinks: Vec<LogSink>,
+    pub syslog_facility: SyslogFacility,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            sinks: vec![LogSink::File, LogSink::Stdout],
+            syslog_facility: SyslogFacility::User,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LogSink {
+    /// Rotating files in the logs directory
+    File,
+    Stdout,
+    /// The systemd journal (Linux only)
+    Journald,
+    /// The local syslog daemon (Unix only)
+    Syslog,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SyslogFacility {
+    User,
+    Daemon,
+    Local0,
+    Local1,
+    Local2,
+    Local3,
+    Local4,
+    Local5,
+    Local6,
+    Local7,
+}
+
+/// Fallbacks for settings a job file doesn't specify
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct DefaultsConfig {
+    /// Milliseconds between condition checks for jobs without a `check_interval`
+    pub check_interval: Option<u64>,
+    /// Shell tasks are run with as `<shell> -c <command>`, the platform shell if unset
+    pub shell: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConcurrencyConfig {
+    /// How many jobs may run their tasks at the same time, 0 means no limit
+    pub max_running_jobs: usize,
+}
+
+/// Locations that default to subdirectories of the autopilot directory
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathsConfig {
+    pub jobs: Option<String>,
+    pub logs: Option<String>,
+}
+
+/// Settings that can be given on the command line
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    pub api_listen: Option<Vec<ListenAddress>>,
+    pub log_level: Option<String>,
+}
+
+impl Config {
+    /// Applies `AUTOPILOT_*` environment variables, looked up through `var`
+    pub fn apply_env(
+        &mut self,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Result<(), AutoPilotError> {
+        fn parse<T: std::str::FromStr>(name: &str, value: String) -> Result<T, AutoPilotError> {
+            value.parse().map_err(|_| {
+                AutoPilotError::Config(format!("{} has an invalid value '{}'", name, value))
+            })
+        }
+
+        if let Some(listen) = var("AUTOPILOT_API_LISTEN") {
+            self.api.listen = listen
+                .split(',')
+                .filter(|address| !address.trim().is_empty())
+                .map(|address| address.trim().parse())
+                .collect::<Result<_, _>>()?;
+        }
+        if let Some(level) = var("AUTOPILOT_LOG_LEVEL") {
+            self.logging.level = level;
+        }
+        if let Some(sinks) = var("AUTOPILOT_LOG_SINKS") {
+            self.logging.sinks = sinks
+                .split(',')
+                .map(|sink| {
+                    serde_json::from_value(serde_json::Value::String(sink.trim().to_string()))
+                        .map_err(|_| {
+                            AutoPilotError::Config(format!(
+                                "AUTOPILOT_LOG_SINKS has an unknown sink '{}'",
+                                sink.trim()
+                            ))
+                        })
+                })
+                .collect::<Result<_, _>>()?;
+        }
+        if let Some(interval) = var("AUTOPILOT_CHECK_INTERVAL") {
+            self.defaults.check_interval = Some(parse("AUTOPILOT_CHECK_INTERVAL", interval)?);
+        }
+        if let Some(shell) = var("AUTOPILOT_SHELL") {
+            self.defaults.shell = Some(shell);
+        }
+        if let Some(limit) = var("AUTOPILOT_MAX_RUNNING_JOBS") {
+            self.concurrency.max_running_jobs = parse("AUTOPILOT_MAX_RUNNING_JOBS", limit)?;
+        }
+        if let Some(jobs) = var("AUTOPILOT_JOBS_PATH") {
+            self.paths.jobs = Some(jobs);
+        }
+        if let Some(logs) = var("AUTOPILOT_LOGS_PATH") {
+            self.paths.logs = Some(logs);
+        }
+        Ok(())
+    }
+
+    pub fn apply_overrides(&mut self, overrides: &ConfigOverrides) {
+        if let Some(listen) = &overrides.api_listen {
+            self.api.listen = listen.clone();
+        }
+        if let Some(level) = &overrides.log_level {
+            self.logging.level = level.clone();
+        }
+    }
+
+    /// Checks the values serde can't, reporting every problem at once
+    pub fn validate(&self) -> Result<(), AutoPilotError> {
+        let mut problems = vec![];
+        for address in &self.api.listen {
+            match address {
+                ListenAddress::Tcp(address) if address.port() == 0 => {
+                    problems.push(format!("api.listen '{}' needs a port", address));
+                }
+                ListenAddress::Unix(path) if path.as_os_str().is_empty() => {
+                    problems.push("api.listen has an empty unix socket path".to_string());
+                }
+                _ => {}
+            }
+        }
+        if let Some(tls) = &self.api.tls {
+            if tls.cert.trim().is_empty() || tls.key.trim().is_empty() {
+                problems.push("api.tls needs both cert and key".to_string());
+            }
+            if tls
+                .client_ca
+                .as_ref()
+                .is_some_and(|client_ca| client_ca.trim().is_empty())
+            {
+                problems.push("api.tls.client_ca must not be empty".to_string());
+            }
+            match (&tls.client_cert, &tls.client_key) {
+                (Some(cert), Some(key)) if cert.trim().is_empty() || key.trim().is_empty() => {
+                    problems
+                        .push("api.tls.client_cert and client_key must not be empty".to_string());
+                }
+                (Some(_), None) | (None, Some(_)) => {
+                    problems.push("api.tls needs both client_cert and client_key".to_string());
+                }
+                _ => {}
+            }
+        }
+        for (index, token) in self.api.tokens.iter().enumerate() {
+            if token.name.trim().is_empty() {
+                problems.push(format!("api.tokens[{}] needs a name", index));
+            }
+            if token.token.trim().is_empty() {
+                problems.push(format!("api.tokens[{}] has an empty token", index));
+            }
+            if self.api.tokens[..index]
+                .iter()
+                .any(|other| other.token == token.token || other.name == token.name)
+            {
+                problems.push(format!(
+                    "api.tokens[{}] reuses the name or token of another entry",
+                    index
+                ));
+            }
+        }
+        if self.logging.level.parse::<log::LevelFilter>().is_err() {
+            problems.push(format!(
+                "logging.level '{}' is not one of off, error, warn, info, debug, trace",
+                self.logging.level
+            ));
+        }
+        if self.defaults.check_interval == Some(0) {
+            problems.push("defaults.check_interval must be greater than 0".to_string());
+        }
+        if self
+            .defaults
+            .shell
+            .as_ref()
+            .is_some_and(|shell| shell.trim().is_empty())
+        {
+            problems.push("defaults.shell must not be empty".to_string());
+        }
+        for (name, path) in [
+            ("paths.jobs", &self.paths.jobs),
+            ("paths.logs", &self.paths.logs),
+        ] {
+            if path.as_ref().is_some_and(|path| path.trim().is_empty()) {
+                problems.push(format!("{} must not be empty", name));
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(AutoPilotError::Config(problems.join("; ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_overrides_and_validation() {
+        let mut config = Config::default();
+        config
+            .apply_env(|name| match name {
+                "AUTOPILOT_API_LISTEN" => Some("[::1]:8080, unix:/run/autopilot.sock".to_string()),
+                "AUTOPILOT_LOG_SINKS" => Some("journald, stdout".to_string()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(
+            config.api.listen,
+            vec![
+                ListenAddress::Tcp("[::1]:8080".parse().unwrap()),
+                ListenAddress::Unix(PathBuf::from("/run/autopilot.sock")),
+            ]
+        );
+        assert_eq!(
+            config.logging.sinks,
+            vec![LogSink::Journald, LogSink::Stdout]
+        );
+        assert!(config.validate().is_ok());
+
+        config.apply_overrides(&ConfigOverrides {
+            log_level: Some("loud".to_string()),
+            ..Default::default()
+        });
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("logging.level 'loud'"));
+
+        let error = config
+            .apply_env(|name| (name == "AUTOPILOT_MAX_RUNNING_JOBS").then(|| "many".to_string()))
+            .unwrap_err();
+        assert!(error.to_string().contains("AUTOPILOT_MAX_RUNNING_JOBS"));
+
+        let error = config
+            .apply_env(|name| (name == "AUTOPILOT_API_LISTEN").then(|| "localhost".to_string()))
+            .unwrap_err();
+        assert!(error.to_string().contains("'localhost'"));
+    }
+}
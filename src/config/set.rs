@@ -0,0 +1,41 @@
+use std::sync::RwLock;
+
+use crate::{
+    config::{CONFIG, CONFIG_OVERRIDES, Config, ConfigOverrides, load_config},
+    error::AutoPilotError,
+};
+
+/// Loads the configuration for the first time, remembering the command line overrides
+pub fn init_config(overrides: ConfigOverrides) -> Result<(), AutoPilotError> {
+    let _ = CONFIG_OVERRIDES.set(overrides);
+    let config = load_config()?;
+    set_config(config);
+    Ok(())
+}
+
+/// Re-reads the configuration, keeping the current one if the new one is invalid
+pub fn reload_config() -> Result<Config, AutoPilotError> {
+    let config = load_config()?;
+    set_config(config.clone());
+    Ok(config)
+}
+
+pub fn set_config(config: Config) {
+    match CONFIG.get() {
+        Some(current) => {
+            if let Ok(mut current) = current.write() {
+                *current = config;
+            }
+        }
+        None => {
+            let _ = CONFIG.set(RwLock::new(config));
+        }
+    }
+}
+
+/// Taken by tests that change the global configuration, so they don't overwrite each other's
+#[cfg(test)]
+pub fn config_turn() -> std::sync::MutexGuard<'static, ()> {
+    static TURN: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    TURN.lock().unwrap_or_else(|e| e.into_inner())
+}
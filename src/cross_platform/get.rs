@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
 use crate::cross_platform::TERMINAL_EDITORS;
@@ -28,26 +30,27 @@ pub fn get_supported_editors() -> Vec<&'static str> {
     TERMINAL_EDITORS
         .par_iter() // Parallel iteration
         .copied()
-        .filter(|&editor| {
-            #[cfg(target_os = "windows")]
-            {
-                match duct_sh::sh_dangerous(format!("where {}", editor))
-                    .stdout_null()
-                    .stderr_null()
-                    .run()
-                {
-                    Ok(_) => return true,
-                    Err(_) => return false,
-                }
-            }
-            match duct_sh::sh_dangerous(format!("which {}", editor))
-                .stdout_null()
-                .stderr_null()
-                .run()
-            {
-                Ok(_) => true,
-                Err(_) => false,
-            }
-        })
+        .filter(|&editor| is_installed(editor))
         .collect()
 }
+
+/// Whether `program` is an existing path or found in one of the `PATH` directories
+pub fn is_installed(program: &str) -> bool {
+    if program.contains(['/', '\\']) {
+        return Path::new(program).is_file();
+    }
+    let Some(paths) = std::env::var_os("PATH") else {
+        return false;
+    };
+    // Windows finds `netsh` as `netsh.exe`
+    let names: Vec<String> = if cfg!(target_os = "windows") {
+        vec![
+            format!("{}.exe", program),
+            format!("{}.cmd", program),
+            program.to_string(),
+        ]
+    } else {
+        vec![program.to_string()]
+    };
+    std::env::split_paths(&paths).any(|dir| names.iter().any(|name| dir.join(name).is_file()))
+}
@@ -8,8 +8,20 @@ pub enum AutoPilotError {
     #[error("JSON serialization/deserialization error: {0}")]
     Json(String),
 
-    #[error("Job error: {0}")]
-    Job(String),
+    #[error("Job error: {message}: {source}")]
+    Job {
+        message: String,
+        source: std::io::Error,
+    },
+
+    #[error("Job not found: {0}")]
+    JobNotFound(String),
+
+    #[error("Job already exists: {0}")]
+    JobExists(String),
+
+    #[error("Job was modified: {0}")]
+    JobModified(String),
 
     #[error("Job execution error: {0}")]
     JobExecution(String),
@@ -29,6 +41,9 @@ pub enum AutoPilotError {
     #[error("Invalid job definition: {0}")]
     InvalidJob(String),
 
+    #[error("Invalid job definition at {path}: {message}")]
+    InvalidJobField { path: String, message: String },
+
     #[error("Dialoguer error: {0}")]
     Dialoguer(#[from] dialoguer::Error),
 
@@ -49,6 +64,12 @@ pub enum AutoPilotError {
 
     #[error("Autopilot error: {0}")]
     Autopilot(String),
+
+    #[error("Service error: {0}")]
+    Service(String),
+
+    #[error("API error: {0}")]
+    Api(String),
 }
 
 // Type alias for convenience
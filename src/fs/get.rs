@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 
-use crate::fs::CONFIG_PATH;
+use crate::{config::get_config, fs::CONFIG_PATH};
 
 pub fn get_autopilot_path() -> String {
     CONFIG_PATH
@@ -11,7 +11,10 @@ pub fn get_autopilot_path() -> String {
 }
 
 pub fn get_logs_path() -> String {
-    get_autopilot_path() + "/logs"
+    get_config()
+        .paths
+        .logs
+        .unwrap_or_else(|| get_autopilot_path() + "/logs")
 }
 
 pub fn get_config_path() -> String {
@@ -22,8 +25,16 @@ pub fn get_status_path() -> String {
     get_autopilot_path() + "/status.jsonc"
 }
 
+/// Finished runs of every job, one JSON object per line
+pub fn get_history_path() -> String {
+    get_autopilot_path() + "/history.jsonl"
+}
+
 pub fn get_jobs_path() -> String {
-    get_autopilot_path() + "/jobs"
+    get_config()
+        .paths
+        .jobs
+        .unwrap_or_else(|| get_autopilot_path() + "/jobs")
 }
 
 pub fn get_jobs_dir() -> Result<PathBuf, crate::error::AutoPilotError> {
@@ -5,3 +5,51 @@ pub mod set;
 pub use set::*;
 
 pub static CONFIG_PATH: OnceLock<String> = OnceLock::new();
+
+/// Takes an advisory lock on `<name>.lock` in the autopilot directory, released when the file is
+/// dropped. Other autopilot processes, e.g. `enable` next to a running daemon, take the same lock,
+/// so their read, change and write cycles don't interleave. A separate file is locked as the
+/// guarded files are replaced by renames and editors.
+pub fn lock_file(name: &str) -> std::io::Result<std::fs::File> {
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(format!("{}/{}.lock", get_autopilot_path(), name))?;
+    file.lock()?;
+    Ok(file)
+}
+
+/// Points the jobs directory at a fresh temporary one while it is held. The directory is global,
+/// so tests that touch job files take turns.
+#[cfg(test)]
+pub(crate) struct TempJobsDir {
+    pub dir: tempfile::TempDir,
+    _turn: std::sync::MutexGuard<'static, ()>,
+}
+
+#[cfg(test)]
+impl TempJobsDir {
+    pub fn new() -> Self {
+        let turn = crate::config::config_turn();
+        CONFIG_PATH.get_or_init(test_autopilot_path);
+        let dir = tempfile::tempdir().expect("Failed to create a temporary directory");
+        let mut config = crate::config::get_config();
+        config.paths.jobs = Some(dir.path().display().to_string());
+        crate::config::set_config(config);
+        Self { dir, _turn: turn }
+    }
+}
+
+/// Status and history files live next to the config, whose path can only be set once. Tests share
+/// a directory beside the test binary, so `cargo clean` removes it.
+#[cfg(test)]
+fn test_autopilot_path() -> String {
+    let exe = std::env::current_exe().expect("Failed to find the test binary");
+    let path = exe
+        .parent()
+        .expect("The test binary has no directory")
+        .join("autopilot-test");
+    std::fs::create_dir_all(&path).expect("Failed to create the test autopilot directory");
+    path.display().to_string()
+}
@@ -0,0 +1,335 @@
+//! Job bundles: several job definitions in one JSON document, to move jobs to another machine.
+//! Jobs are written from their parsed definition, so comments in the job files are not carried.
+
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    conditions::ConditionScheme,
+    error::AutoPilotError,
+    job::{
+        JobScheme,
+        get::load_job_schemes,
+        set::{add_job_scheme, new_job_id, update_job},
+    },
+};
+
+/// Format version written by this build, bundles from newer builds are refused
+pub const BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Bundle {
+    pub version: u32,
+    pub exported_at: DateTime<Local>,
+    pub jobs: Vec<JobScheme>,
+}
+
+/// What importing does with a job whose id is already taken
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema, ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum OnConflict {
+    /// Keep the existing job
+    #[default]
+    Skip,
+    /// Replace the existing job's definition, keeping its file
+    Overwrite,
+    /// Import the job under a new id
+    Rename,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportAction {
+    Created,
+    Overwritten,
+    Renamed,
+    Skipped,
+    Failed,
+}
+
+/// What happened to one job of a bundle
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Imported {
+    /// Id the job has now, the new one when it was renamed
+    pub id: String,
+    pub action: ImportAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renamed_from: Option<String>,
+    /// Machine-specific conditions were removed and the job disabled
+    pub stripped: bool,
+    /// Why the job was skipped or failed, or why the daemon didn't load it after writing it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Bundles the jobs with the given ids, or every job that loads when `ids` is empty. Returns the
+/// ids of the jobs `strip` changed along with the bundle.
+pub fn export_jobs(ids: &[String], strip: bool) -> Result<(Bundle, Vec<String>), AutoPilotError> {
+    let mut schemes: Vec<JobScheme> = load_job_schemes()
+        .0
+        .into_iter()
+        .map(|(_, scheme)| scheme)
+        .collect();
+    if let Some(missing) = ids.iter().find(|id| !schemes.iter().any(|s| s.id == **id)) {
+        return Err(AutoPilotError::JobNotFound(missing.clone()));
+    }
+    if !ids.is_empty() {
+        schemes.retain(|scheme| ids.contains(&scheme.id));
+    }
+    let mut stripped = vec![];
+    if strip {
+        for scheme in &mut schemes {
+            if strip_machine_specific(scheme) {
+                stripped.push(scheme.id.clone());
+            }
+        }
+    }
+    let bundle = Bundle {
+        version: BUNDLE_VERSION,
+        exported_at: Local::now(),
+        jobs: schemes,
+    };
+    Ok((bundle, stripped))
+}
+
+/// Writes the jobs of a bundle to the jobs directory. `on_conflict` decides about each job whose
+/// id is already used; a job that fails to import, or whose conflict can't be decided, doesn't
+/// stop the others.
+pub fn import_jobs(
+    bundle: Bundle,
+    strip: bool,
+    mut on_conflict: impl FnMut(&JobScheme) -> Result<OnConflict, AutoPilotError>,
+) -> Result<Vec<Imported>, AutoPilotError> {
+    if bundle.version > BUNDLE_VERSION {
+        return Err(AutoPilotError::InvalidJob(format!(
+            "Bundle version {} is newer than the {} this build reads",
+            bundle.version, BUNDLE_VERSION
+        )));
+    }
+    let mut imported = vec![];
+    for mut scheme in bundle.jobs {
+        let stripped = strip && strip_machine_specific(&mut scheme);
+        let id = scheme.id.clone();
+        // The id is checked under the jobs lock as the job is written, so a job created meanwhile,
+        // by the daemon or during an earlier prompt, still counts as a conflict. No lock is held
+        // while `on_conflict` asks, and a failed prompt only fails this job.
+        let result = match add_job_scheme(&scheme) {
+            Err(AutoPilotError::JobExists(_)) => match on_conflict(&scheme) {
+                Err(e) => Err(e),
+                Ok(OnConflict::Skip) => Ok(ImportAction::Skipped),
+                Ok(OnConflict::Overwrite) => match overwrite(&scheme) {
+                    // The existing job was removed since the conflict was found
+                    Err(AutoPilotError::JobNotFound(_)) => {
+                        add_job_scheme(&scheme).map(|_| ImportAction::Created)
+                    }
+                    result => result.map(|_| ImportAction::Overwritten),
+                },
+                Ok(OnConflict::Rename) => {
+                    scheme.id = new_job_id();
+                    add_job_scheme(&scheme).map(|_| ImportAction::Renamed)
+                }
+            },
+            result => result.map(|_| ImportAction::Created),
+        };
+        imported.push(match result {
+            Ok(action) => Imported {
+                renamed_from: (action == ImportAction::Renamed).then(|| id.clone()),
+                message: (action == ImportAction::Skipped)
+                    .then(|| format!("A job with id '{}' already exists", id)),
+                id: scheme.id,
+                action,
+                stripped,
+            },
+            Err(e) => Imported {
+                id: scheme.id,
+                action: ImportAction::Failed,
+                renamed_from: None,
+                stripped,
+                message: Some(e.to_string()),
+            },
+        });
+    }
+    Ok(imported)
+}
+
+/// Replaces every field of an existing job file, the way `PUT /jobs/{id}` does
+fn overwrite(scheme: &JobScheme) -> Result<(), AutoPilotError> {
+    let mut changes = match serde_json::to_value(scheme) {
+        Ok(serde_json::Value::Object(changes)) => changes,
+        _ => {
+            return Err(AutoPilotError::Json(
+                "Failed to serialize the job".to_string(),
+            ));
+        }
+    };
+    changes.entry("tags").or_insert(serde_json::Value::Null);
+    update_job(&scheme.id, &changes, None).map(|_| ())
+}
+
+/// Conditions about this machine's networks and hardware, meaningless on another one
+fn is_machine_specific(condition: &ConditionScheme) -> bool {
+    matches!(
+        condition,
+        ConditionScheme::Wifi(_)
+            | ConditionScheme::Bluetooth(_)
+            | ConditionScheme::ExternalDevice(_)
+            | ConditionScheme::Screen(_)
+            | ConditionScheme::DesktopEnv(_)
+    )
+}
+
+/// Removes machine-specific conditions, also from logical ones, and disables the job if any were
+/// removed, as it would otherwise start running in more situations than it used to
+pub fn strip_machine_specific(scheme: &mut JobScheme) -> bool {
+    let stripped = strip_conditions(&mut scheme.conditions) > 0;
+    if stripped {
+        scheme.enabled = false;
+    }
+    stripped
+}
+
+fn strip_conditions(conditions: &mut Vec<ConditionScheme>) -> usize {
+    let mut stripped = 0;
+    conditions.retain_mut(|condition| match condition {
+        // A logical condition emptied by stripping goes as well
+        ConditionScheme::Logical(logical) if !logical.conditions.is_empty() => {
+            stripped += strip_conditions(&mut logical.conditions);
+            !logical.conditions.is_empty()
+        }
+        _ if is_machine_specific(condition) => {
+            stripped += 1;
+            false
+        }
+        _ => true,
+    });
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_machine_specific_removes_nested_conditions_and_disables() {
+        let mut scheme: JobScheme = serde_json::from_value(serde_json::json!({
+            "id": "sync",
+            "conditions": [
+                { "type": "variable", "condition": { "variable": "HOME", "target": "x" } },
+                { "type": "logical", "condition": { "operator": "And", "conditions": [
+                    { "type": "wifi", "condition": { "ssid": "home" } },
+                    { "type": "bluetooth", "condition": { "device": "phone" } }
+                ] } }
+            ],
+            "tasks": [{ "command": "true" }]
+        }))
+        .unwrap();
+        assert!(strip_machine_specific(&mut scheme));
+        assert!(!scheme.enabled);
+        assert_eq!(scheme.conditions.len(), 1);
+        assert!(matches!(scheme.conditions[0], ConditionScheme::Variable(_)));
+        assert!(!strip_machine_specific(&mut scheme));
+    }
+
+    #[test]
+    fn test_import_rejects_ids_that_leave_the_jobs_directory() {
+        let jobs = crate::fs::TempJobsDir::new();
+        let elsewhere = tempfile::tempdir().unwrap();
+        let absolute = elsewhere.path().join("absolute");
+        let escaped = format!("autopilot-escaped-{}", std::process::id());
+        let bundle: Bundle = serde_json::from_value(serde_json::json!({
+            "version": BUNDLE_VERSION,
+            "exported_at": Local::now(),
+            "jobs": [
+                { "id": format!("../../{}", escaped), "conditions": [], "tasks": [{ "command": "true" }] },
+                { "id": absolute, "conditions": [], "tasks": [{ "command": "true" }] },
+                { "id": "named", "name": "../up", "conditions": [], "tasks": [{ "command": "true" }] }
+            ]
+        }))
+        .unwrap();
+        let imported = import_jobs(bundle, false, |_| Ok(OnConflict::Skip)).unwrap();
+        assert_eq!(imported.len(), 3);
+        assert!(
+            imported
+                .iter()
+                .all(|job| job.action == ImportAction::Failed)
+        );
+        assert_eq!(std::fs::read_dir(jobs.dir.path()).unwrap().count(), 0);
+        let outside = jobs
+            .dir
+            .path()
+            .join("../..")
+            .join(format!("{}.jsonc", escaped));
+        assert!(!outside.exists());
+        assert_eq!(std::fs::read_dir(elsewhere.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_a_failed_conflict_decision_keeps_the_jobs_written_before_it() {
+        let _jobs = crate::fs::TempJobsDir::new();
+        let job = |id: &str| serde_json::json!({ "id": id, "conditions": [], "tasks": [{ "command": "true" }] });
+        let bundle: Bundle = serde_json::from_value(serde_json::json!({
+            "version": BUNDLE_VERSION,
+            "exported_at": Local::now(),
+            "jobs": [job("first"), job("first"), job("second")]
+        }))
+        .unwrap();
+        let imported = import_jobs(bundle, false, |_| {
+            Err(AutoPilotError::InvalidJob("no terminal".to_string()))
+        })
+        .unwrap();
+        let actions: Vec<_> = imported.iter().map(|job| job.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                ImportAction::Created,
+                ImportAction::Failed,
+                ImportAction::Created
+            ]
+        );
+        assert!(
+            imported[1]
+                .message
+                .as_ref()
+                .unwrap()
+                .contains("no terminal")
+        );
+    }
+
+    #[test]
+    fn test_a_job_created_during_the_import_is_a_conflict() {
+        let _jobs = crate::fs::TempJobsDir::new();
+        let job = |id: &str| {
+            serde_json::from_value::<JobScheme>(serde_json::json!({
+                "id": id, "conditions": [], "tasks": [{ "command": "true" }]
+            }))
+            .unwrap()
+        };
+        add_job_scheme(&job("first")).unwrap();
+        let bundle = Bundle {
+            version: BUNDLE_VERSION,
+            exported_at: Local::now(),
+            jobs: vec![job("first"), job("second")],
+        };
+        let mut asked = vec![];
+        let imported = import_jobs(bundle, false, |scheme| {
+            // Someone else adds the next job while the first conflict is being decided
+            if asked.is_empty() {
+                add_job_scheme(&job("second")).unwrap();
+            }
+            asked.push(scheme.id.clone());
+            Ok(OnConflict::Skip)
+        })
+        .unwrap();
+        assert_eq!(asked, vec!["first", "second"]);
+        assert!(
+            imported
+                .iter()
+                .all(|job| job.action == ImportAction::Skipped)
+        );
+    }
+}
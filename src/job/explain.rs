@@ -0,0 +1,162 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use serde::Serialize;
+
+use crate::{
+    conditions::{ConditionScheme, logical_condition::LogicalOperator},
+    job::JobScheme,
+    task::runner::shell_command_line,
+    time::{When, next_run, to_cron_expression},
+};
+
+/// Everything a job would do right now, without running any of its tasks
+#[derive(Debug, Serialize)]
+pub struct JobExplanation {
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub enabled: bool,
+    pub file: Option<PathBuf>,
+    pub schedule: Option<ScheduleExplanation>,
+    pub check_interval: Option<String>,
+    /// Whether all top level conditions are currently satisfied
+    pub satisfied: bool,
+    pub conditions: Vec<ConditionExplanation>,
+    pub tasks: Vec<TaskExplanation>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScheduleExplanation {
+    pub when: When,
+    pub description: String,
+    /// `UTC` for recurring schedules, `local` for a one-off date
+    pub timezone: &'static str,
+    /// Cron expression handed to the scheduler, for recurring schedules
+    pub cron: Option<String>,
+    pub next_run: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConditionExplanation {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub name: String,
+    pub parameters: serde_json::Value,
+    pub operator: Option<LogicalOperator>,
+    pub satisfied: bool,
+    pub observed: Option<String>,
+    pub children: Vec<ConditionExplanation>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskExplanation {
+    pub command: String,
+    pub program: Vec<String>,
+    /// Working directory of the process explaining the job, which is not necessarily the daemon
+    /// that runs it
+    pub caller_cwd: Option<PathBuf>,
+    /// Notable parts of the explaining process's environment. Tasks inherit the daemon's, which
+    /// can differ, for example under a service manager.
+    pub caller_env: BTreeMap<String, String>,
+}
+
+pub fn explain_job(scheme: &JobScheme, file: Option<PathBuf>) -> JobExplanation {
+    let conditions: Vec<ConditionExplanation> =
+        scheme.conditions.iter().map(explain_condition).collect();
+    JobExplanation {
+        id: scheme.id.clone(),
+        name: scheme.name.clone(),
+        description: scheme.description.clone(),
+        enabled: scheme.enabled,
+        file,
+        schedule: scheme.when.as_ref().map(explain_schedule),
+        check_interval: scheme.check_interval.clone(),
+        satisfied: conditions.iter().all(|condition| condition.satisfied),
+        conditions,
+        tasks: scheme
+            .tasks
+            .iter()
+            .map(|task| explain_task(&task.command))
+            .collect(),
+    }
+}
+
+fn explain_schedule(when: &When) -> ScheduleExplanation {
+    let cron = match when {
+        When::Once(_) => None,
+        _ => to_cron_expression(when.clone()).ok(),
+    };
+    let (next_run, error) = match next_run(when) {
+        Ok(next) => (next.map(|next| next.to_rfc3339()), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    ScheduleExplanation {
+        when: when.clone(),
+        description: when.describe(),
+        timezone: when.timezone(),
+        cron,
+        next_run,
+        error,
+    }
+}
+
+/// Evaluates a condition and, for logical conditions, each of its children exactly once
+fn explain_condition(scheme: &ConditionScheme) -> ConditionExplanation {
+    let condition = scheme.to_condition();
+    let mut value = serde_json::to_value(scheme).unwrap_or_default();
+    let kind = value["type"].as_str().unwrap_or_default().to_string();
+    let mut parameters = value["condition"].take();
+    if let Some(parameters) = parameters.as_object_mut() {
+        parameters.retain(|_, value| !value.is_null());
+    }
+
+    match scheme {
+        ConditionScheme::Logical(logical) => {
+            if let Some(parameters) = parameters.as_object_mut() {
+                parameters.clear();
+            }
+            let children: Vec<ConditionExplanation> =
+                logical.conditions.iter().map(explain_condition).collect();
+            let results: Vec<bool> = children.iter().map(|child| child.satisfied).collect();
+            ConditionExplanation {
+                kind,
+                name: condition.name().to_string(),
+                parameters,
+                operator: Some(logical.operator),
+                satisfied: logical.operator.evaluate(&results),
+                observed: None,
+                children,
+            }
+        }
+        _ => {
+            let probe = condition.probe();
+            ConditionExplanation {
+                kind,
+                name: condition.name().to_string(),
+                parameters,
+                operator: None,
+                satisfied: probe.satisfied,
+                observed: probe.observed,
+                children: vec![],
+            }
+        }
+    }
+}
+
+fn explain_task(command: &str) -> TaskExplanation {
+    let env = ["PATH", "HOME", "SHELL"]
+        .iter()
+        .filter_map(|key| {
+            std::env::var(key)
+                .ok()
+                .map(|value| (key.to_string(), value))
+        })
+        .collect();
+    TaskExplanation {
+        command: command.to_string(),
+        program: shell_command_line(command),
+        caller_cwd: std::env::current_dir().ok(),
+        caller_env: env,
+    }
+}
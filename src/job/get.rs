@@ -1,62 +1,99 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 use colored::*;
-use log::{error, info};
+use log::{error, info, warn};
+use serde::Serialize;
+use utoipa::ToSchema;
 
 use crate::{
     error::AutoPilotError,
     fs::get_jobs_path,
     job::{Job, JobScheme},
+    status::{JobStatusEnum, LastRun, get::get_status_log},
     utilities::jsonc_parser::jsonc_parse,
 };
 
+/// Loads every enabled job from the jobs directory
 pub fn get_jobs(quiet: bool) -> Vec<Job> {
-    let mut jobs_string: Vec<String> = vec![];
-    let mut job_objects: Vec<Job> = vec![];
-    let jobs_path = get_jobs_paths();
-    for job in &jobs_path {
-        match fs::read_to_string(job) {
-            Ok(content) => jobs_string.push(content),
-            Err(e) => {
-                if !quiet {
-                    if log::log_enabled!(log::Level::Error) {
-                        error!("Failed to read job file {}: {}", job.display(), e);
-                    } else {
-                        eprintln!("Failed to read job file {}: {}", job.display(), e);
-                    }
-                }
+    get_all_jobs(quiet)
+        .into_iter()
+        .filter(|job| {
+            if !job.enabled && !quiet {
+                info!("Skipping disabled job: {}", job.name);
             }
+            job.enabled
+        })
+        .collect()
+}
+
+/// Loads every job from the jobs directory, including disabled ones
+pub fn get_all_jobs(quiet: bool) -> Vec<Job> {
+    let (schemes, errors) = load_job_schemes();
+    if !quiet {
+        for error in &errors {
+            warn!(
+                "Skipping job, `autopilot-rs validate` shows why: \n Job path: {} \n Error: {}",
+                error.path.display().to_string().green(),
+                error.message.red()
+            );
         }
     }
-
-    for (i, job_str) in jobs_string.iter().enumerate() {
-        match serde_json::from_str::<JobScheme>(jsonc_parse(job_str).as_str()) {
-            Ok(job_scheme) => {
-                let job_object = Job::from_scheme(job_scheme);
-                if !quiet {
-                    info!("Loaded job: {}", job_object.name);
-                }
-                job_objects.push(job_object);
-            }
-            Err(e) => {
-                // Use the index `i` directly instead of searching again
-                let job_path = &jobs_path
-                    .get(i)
-                    .and_then(|p| p.to_str())
-                    .unwrap_or("unknown");
-                if !quiet {
-                    info!(
-                        "Failed to parse job: \n Job path: {} \n Error: {}",
-                        job_path.green(),
-                        e.to_string().red()
-                    );
+    schemes
+        .into_iter()
+        .map(|(_, scheme)| {
+            let job = Job::from_scheme(scheme);
+            if !quiet {
+                match job.description.trim() {
+                    "" => info!("Loaded job: {}", job.name),
+                    description => info!("Loaded job: {} ({})", job.name, description),
                 }
             }
+            job
+        })
+        .collect()
+}
+
+/// A job file that was left out when loading the jobs directory
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct LoadError {
+    #[schema(value_type = String)]
+    pub path: PathBuf,
+    pub message: String,
+}
+
+/// Parses every job file in file name order. Files that can't be read or parsed are left out, and
+/// so is any file reusing the id of an earlier one, as ids have to name a single job.
+pub fn load_job_schemes() -> (Vec<(PathBuf, JobScheme)>, Vec<LoadError>) {
+    let paths = get_jobs_paths();
+    let mut schemes: Vec<(PathBuf, JobScheme)> = vec![];
+    let mut errors = vec![];
+    for path in paths {
+        match get_job_scheme(&path) {
+            Ok(scheme) => match schemes.iter().find(|(_, other)| other.id == scheme.id) {
+                Some((first, _)) => errors.push(LoadError {
+                    message: format!(
+                        "Job id '{}' is already used by {}",
+                        scheme.id,
+                        first.display()
+                    ),
+                    path,
+                }),
+                None => schemes.push((path, scheme)),
+            },
+            Err(e) => errors.push(LoadError {
+                path,
+                message: e.to_string(),
+            }),
         }
     }
-    job_objects
+    (schemes, errors)
 }
 
+/// Every entry of the jobs directory, sorted so the first file claiming an id is the same one
+/// everywhere
 pub fn get_jobs_paths() -> Vec<PathBuf> {
     let path = get_jobs_path();
     let mut jobs_path: Vec<PathBuf> = vec![];
@@ -84,13 +121,19 @@ pub fn get_jobs_paths() -> Vec<PathBuf> {
             }
         }
     }
+    jobs_path.sort();
     jobs_path
 }
 
 pub fn get_job(path: PathBuf) -> Result<Job, AutoPilotError> {
-    match fs::read_to_string(&path) {
+    get_job_scheme(&path).map(Job::from_scheme)
+}
+
+/// Reads and parses a job file without turning it into a runnable job
+pub fn get_job_scheme(path: &Path) -> Result<JobScheme, AutoPilotError> {
+    match fs::read_to_string(path) {
         Ok(content) => match serde_json::from_str::<JobScheme>(jsonc_parse(&content).as_str()) {
-            Ok(job_scheme) => Ok(Job::from_scheme(job_scheme)),
+            Ok(job_scheme) => Ok(job_scheme),
             Err(e) => Err(AutoPilotError::InvalidJob(format!(
                 "Failed to parse job file {}: {}",
                 path.display(),
@@ -104,3 +147,91 @@ pub fn get_job(path: PathBuf) -> Result<Job, AutoPilotError> {
         )))?,
     }
 }
+
+/// Parses every job file, skipping files that fail to parse or reuse an id
+pub fn get_job_schemes() -> Vec<JobScheme> {
+    load_job_schemes()
+        .0
+        .into_iter()
+        .map(|(_, scheme)| scheme)
+        .collect()
+}
+
+/// Finds the file of the job with the given id, the first one by name if several claim it
+pub fn get_job_path(id: &str) -> Result<PathBuf, AutoPilotError> {
+    get_jobs_paths()
+        .into_iter()
+        .find(|path| get_job_scheme(path).is_ok_and(|scheme| scheme.id == id))
+        .ok_or_else(|| AutoPilotError::JobNotFound(id.to_string()))
+}
+
+/// Loads the job with the given id straight from its file, whether it is enabled or not
+pub fn get_job_by_id(id: &str) -> Result<Job, AutoPilotError> {
+    get_job(get_job_path(id)?)
+}
+
+/// A job file along with what the daemon knows about it
+pub struct JobEntry {
+    pub scheme: JobScheme,
+    pub status: JobStatusEnum,
+    pub last_run: Option<LastRun>,
+}
+
+impl JobEntry {
+    pub fn name(&self) -> String {
+        self.scheme
+            .name
+            .clone()
+            .unwrap_or(format!("job_{}", self.scheme.id))
+    }
+
+    pub fn schedule(&self) -> &'static str {
+        self.scheme.when.as_ref().map_or("none", |when| when.kind())
+    }
+}
+
+/// Pairs job files with their entries in the status log
+pub fn job_entries(schemes: Vec<JobScheme>) -> Vec<JobEntry> {
+    let statuses = get_status_log().statuses;
+    schemes
+        .into_iter()
+        .map(|scheme| {
+            let known = statuses.iter().find(|status| status.id == scheme.id);
+            let status = match known {
+                Some(known) => known.status.clone(),
+                None if !scheme.enabled => JobStatusEnum::Disabled,
+                None => JobStatusEnum::Unknown,
+            };
+            JobEntry {
+                last_run: known.and_then(|known| known.last_run.clone()),
+                status,
+                scheme,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::TempJobsDir;
+
+    #[test]
+    fn test_first_file_by_name_keeps_a_duplicate_id() {
+        let jobs = TempJobsDir::new();
+        let job = r#"{ "id": "dup", "conditions": [], "tasks": [{ "command": "true" }] }"#;
+        // Written out of order, so directory order can't pass for name order
+        let second = jobs.dir.path().join("b.jsonc");
+        let first = jobs.dir.path().join("a.jsonc");
+        fs::write(&second, job).unwrap();
+        fs::write(&first, job).unwrap();
+
+        let (schemes, errors) = load_job_schemes();
+        assert_eq!(schemes.len(), 1);
+        assert_eq!(schemes[0].0, first);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].path, second);
+        assert!(errors[0].message.contains(&first.display().to_string()));
+        assert_eq!(get_job_path("dup").unwrap(), first);
+    }
+}
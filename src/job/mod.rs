@@ -1,28 +1,50 @@
-use std::{sync::Arc, thread::sleep, time::Duration};
+use std::{
+    sync::{Arc, Mutex},
+    thread::sleep,
+    time::{Duration, Instant},
+};
 
+use chrono::{DateTime, Local};
 use colored::Colorize;
 use futures::future::join_all;
 use log::{error, info};
 use serde::{Deserialize, Serialize};
-use tokio::{sync::RwLock, task::JoinHandle};
+use tokio::{
+    sync::{Semaphore, mpsc::UnboundedSender},
+    task::JoinHandle,
+};
 use tokio_cron_scheduler::JobScheduler;
+use utoipa::ToSchema;
 
 use crate::{
+    autopilot::{
+        events::{EventKind, emit, emit_in},
+        metrics,
+    },
     conditions::{Condition, ConditionScheme},
+    config::get_config,
     error::AutoPilotError,
-    status::{JobStatusEnum, set::set_state_item},
+    logging::context::{JobLogContext, current_job_context, with_job_context},
+    status::{
+        JobStatusEnum, LastRun,
+        set::{append_history, set_finished_run, set_state_item},
+    },
     task::{self, Task, TaskScheme},
     time::{When, add::add_job},
 };
 
+pub mod bundle;
+pub mod explain;
 pub mod get;
 pub mod set;
+pub mod validate;
 
 #[derive(Clone)]
 pub struct Job {
     pub id: String,
     pub name: String,
     pub status: JobStatusEnum,
+    pub enabled: bool,
     pub description: String,
     pub when: Option<When>,
     pub check_interval: Option<String>,
@@ -44,6 +66,7 @@ impl Job {
             id,
             name,
             status: JobStatusEnum::Unknown,
+            enabled: true,
             description,
             when,
             check_interval,
@@ -70,6 +93,7 @@ impl Job {
             id: scheme.id.clone(),
             name: scheme.name.unwrap_or(format!("job_{}", scheme.id)),
             status: JobStatusEnum::Unknown,
+            enabled: scheme.enabled,
             description: scheme.description.unwrap_or(" ".to_string()),
             when,
             check_interval: scheme.check_interval,
@@ -97,46 +121,68 @@ impl Job {
         }
 
         if self.when.is_none() {
+            let default_interval = get_config().defaults.check_interval;
+            let started = Local::now();
+            emit(EventKind::RunStarted {
+                mode: RunMode::Normal,
+            });
             loop {
                 let mut result = true;
                 for condition in &self.conditions {
-                    let condition_result = condition.check();
+                    let condition_result = check_condition(condition.as_ref());
+                    emit(EventKind::ConditionEvaluated {
+                        name: condition.name().to_string(),
+                        satisfied: condition_result,
+                    });
                     result = result && condition_result;
                 }
                 if result {
-                    run_tasks(self.tasks.clone()).await;
-                    self.status = JobStatusEnum::Completed;
+                    let status = if run_tasks(self.tasks.clone()).await {
+                        JobStatusEnum::Completed
+                    } else {
+                        JobStatusEnum::Failed
+                    };
+                    self.status = status.clone();
                     // dbg!(self.status.clone());
-                    if let Err(e) = set_state_item(self.id.clone(), JobStatusEnum::Completed) {
-                        error!("Failed to set state item: {}", e);
-                    }
+                    record_finished_run(&self.id, &self.name, started, status.clone());
                     if !quiet {
-                        info!("{} : {}", "Job Completed".green(), self.name);
+                        match status {
+                            JobStatusEnum::Completed => {
+                                info!("{} : {}", "Job Completed".green(), self.name)
+                            }
+                            _ => info!("{} : {}", "Job Failed".red(), self.name),
+                        }
                     }
+                    emit(EventKind::RunFinished { status });
                     break;
-                } else if !result && self.check_interval.is_some() {
-                    let interval_ms = match self
-                        .check_interval
-                        .as_ref()
-                        .expect("WTF you should NOT be seeing this")
-                        .parse::<u64>()
-                    {
-                        Ok(ms) => ms,
-                        Err(_) => {
-                            error!("check_interval value is not valid, using 1000ms as default");
-                            1000
-                        }
+                }
+                metrics::record_unsatisfied_check(&self.id);
+                if !result && (self.check_interval.is_some() || default_interval.is_some()) {
+                    let interval_ms = match self.check_interval.as_ref() {
+                        None => default_interval.unwrap_or(1000),
+                        Some(interval) => match interval.parse::<u64>() {
+                            Ok(ms) => ms,
+                            Err(_) => {
+                                let fallback = default_interval.unwrap_or(1000);
+                                error!(
+                                    "check_interval value is not valid, using {}ms as default",
+                                    fallback
+                                );
+                                fallback
+                            }
+                        },
                     };
                     sleep(Duration::from_millis(interval_ms));
                     continue;
                 }
                 self.status = JobStatusEnum::Unsatisfied;
-                if let Err(e) = set_state_item(self.id.clone(), JobStatusEnum::Unsatisfied) {
-                    error!("Failed to set state item: {}", e);
-                }
+                record_finished_run(&self.id, &self.name, started, JobStatusEnum::Unsatisfied);
                 if !quiet {
                     info!("{} : {}", "Job Unsatisfied".yellow(), self.name);
                 }
+                emit(EventKind::RunFinished {
+                    status: JobStatusEnum::Unsatisfied,
+                });
                 break;
             }
         } else if self.when.is_some() {
@@ -149,56 +195,307 @@ impl Job {
     }
 }
 
+/// How a job run treats its conditions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RunMode {
+    /// Check conditions and run the tasks only if all of them are satisfied
+    #[default]
+    Normal,
+    /// Skip the conditions and run the tasks unconditionally
+    Force,
+    /// Only evaluate the conditions, never run the tasks
+    DryRun,
+}
+
+/// Progress of a single job run, streamed to whoever triggered it and published on the event bus
+#[derive(Clone, Debug, Serialize, ToSchema)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum RunEvent {
+    /// A top level condition was evaluated
+    Condition { name: String, satisfied: bool },
+    /// A line printed by a task (stdout and stderr are merged)
+    TaskOutput { command: String, line: String },
+    /// A task exited
+    TaskFinished {
+        command: String,
+        success: bool,
+        error: Option<String>,
+    },
+    /// The run is over; dry runs finish as `Pending` when the tasks would have run
+    Finished { status: JobStatusEnum },
+}
+
 pub async fn run_job(job: Job) {
-    if let Err(e) = set_state_item(job.id.clone(), JobStatusEnum::Running) {
+    run_job_with(job, RunMode::Normal, None).await;
+}
+
+/// Runs a job once, reporting condition results and task output to `events` if given
+pub async fn run_job_with(
+    job: Job,
+    mode: RunMode,
+    events: Option<UnboundedSender<RunEvent>>,
+) -> JobStatusEnum {
+    with_job_context(JobLogContext::new(&job), run_job_scoped(job, mode, events)).await
+}
+
+async fn run_job_scoped(
+    job: Job,
+    mode: RunMode,
+    events: Option<UnboundedSender<RunEvent>>,
+) -> JobStatusEnum {
+    let send = |event: RunEvent| {
+        if let Some(events) = &events {
+            let _ = events.send(event.clone());
+        }
+        emit(event.into());
+    };
+    let started = Local::now();
+    emit(EventKind::RunStarted { mode });
+    if mode != RunMode::DryRun
+        && let Err(e) = set_state_item(job.id.clone(), JobStatusEnum::Running)
+    {
         error!("Failed to set state item: {}", e);
     }
     let mut result = true;
-    for condition in &job.conditions {
-        let condition_result = condition.check();
-        result = result && condition_result;
-    }
-    if result {
-        run_tasks(job.tasks.clone()).await;
-        if let Err(e) = set_state_item(job.id, JobStatusEnum::Completed) {
-            error!("Failed to set state item: {}", e);
+    if mode != RunMode::Force {
+        for condition in &job.conditions {
+            let condition_result = check_condition(condition.as_ref());
+            send(RunEvent::Condition {
+                name: condition.name().to_string(),
+                satisfied: condition_result,
+            });
+            result = result && condition_result;
         }
-    } else if !result {
-        if let Err(e) = set_state_item(job.id.clone(), JobStatusEnum::Unsatisfied) {
-            error!("Failed to set state item: {}", e);
+        if !result && mode != RunMode::DryRun {
+            metrics::record_unsatisfied_check(&job.id);
         }
     }
+    let status = if !result {
+        JobStatusEnum::Unsatisfied
+    } else if mode == RunMode::DryRun {
+        JobStatusEnum::Pending
+    } else if run_tasks_with(job.tasks.clone(), events.clone()).await {
+        JobStatusEnum::Completed
+    } else {
+        JobStatusEnum::Failed
+    };
+    if mode != RunMode::DryRun {
+        record_finished_run(&job.id, &job.name, started, status.clone());
+    }
+    send(RunEvent::Finished {
+        status: status.clone(),
+    });
+    status
+}
+
+fn check_condition(condition: &dyn Condition) -> bool {
+    let started = Instant::now();
+    let satisfied = condition.check();
+    metrics::record_condition(condition.name(), started.elapsed());
+    satisfied
 }
 
-pub async fn run_tasks(tasks: Vec<Task>) {
+/// Keeps the outcome of a run for the status log and the metrics
+fn record_finished_run(
+    job_id: &str,
+    job_name: &str,
+    started: DateTime<Local>,
+    status: JobStatusEnum,
+) {
+    metrics::record_run(job_id, &status);
+    let last_run = LastRun {
+        run_id: current_job_context().map(|context| context.run_id),
+        started,
+        finished: Local::now(),
+        status,
+    };
+    if let Err(e) = append_history(job_id, last_run.clone()) {
+        error!("Failed to add the run to the history: {}", e);
+    }
+    if let Err(e) = set_finished_run(job_id, job_name, last_run) {
+        error!("Failed to record the last run: {}", e);
+    }
+}
+
+/// Permits for `concurrency.max_running_jobs`, recreated when the configured limit changes
+static RUN_PERMITS: Mutex<Option<(usize, Arc<Semaphore>)>> = Mutex::new(None);
+
+fn run_permits() -> Option<Arc<Semaphore>> {
+    let limit = get_config().concurrency.max_running_jobs;
+    if limit == 0 {
+        return None;
+    }
+    let mut permits = RUN_PERMITS.lock().ok()?;
+    match &*permits {
+        Some((current, semaphore)) if *current == limit => Some(semaphore.clone()),
+        _ => {
+            let semaphore = Arc::new(Semaphore::new(limit));
+            *permits = Some((limit, semaphore.clone()));
+            Some(semaphore)
+        }
+    }
+}
+
+pub async fn run_tasks(tasks: Vec<Task>) -> bool {
+    run_tasks_with(tasks, None).await
+}
+
+/// Logs a line a task printed under the `task_output` target, so `logs --run` shows what a run
+/// printed. Output is read on a blocking thread, outside the run's log context, so the context
+/// fields are given here.
+fn log_task_output(context: Option<&JobLogContext>, command: &str, line: &str) {
+    match context {
+        Some(context) => info!(
+            target: "task_output",
+            JOB_ID = context.job_id.as_str(), JOB_NAME = context.job_name.as_str(),
+            RUN_ID = context.run_id.as_str();
+            "{}: {}", command, line
+        ),
+        None => info!(target: "task_output", "{}: {}", command, line),
+    }
+}
+
+/// Runs all tasks concurrently and returns whether every one of them succeeded
+pub async fn run_tasks_with(tasks: Vec<Task>, events: Option<UnboundedSender<RunEvent>>) -> bool {
+    // Held until every task is done, so a job counts against the limit as a whole
+    let _permit = match run_permits() {
+        Some(permits) => {
+            let _queued = metrics::queued_run();
+            permits.acquire_owned().await.ok()
+        }
+        None => None,
+    };
+    let context = current_job_context();
     let mut handles: Vec<JoinHandle<Result<(), AutoPilotError>>> = vec![];
+    let started = Instant::now();
     for task in &tasks {
-        handles.push(task.run());
-    }
-    join_all(handles)
-        .await
-        .iter()
-        .for_each(|handle| match handle {
-            Ok(handle) => match handle {
-                Ok(_) => {}
-                Err(err) => {
-                    error!("{}", err)
-                }
-            },
+        let sender = events.clone();
+        let context = context.clone();
+        let command = task.command.clone();
+        handles.push(task.run_with_output(move |line| {
+            log_task_output(context.as_ref(), &command, line);
+            let event = RunEvent::TaskOutput {
+                command: command.clone(),
+                line: line.to_string(),
+            };
+            if let Some(sender) = &sender {
+                let _ = sender.send(event.clone());
+            }
+            emit_in(context.as_ref(), event.into());
+        }));
+    }
+    let mut success = true;
+    // Tasks start together, so each one took from `started` until its handle finished
+    let timed = handles.into_iter().map(|handle| async move {
+        let result = handle.await;
+        (result, started.elapsed())
+    });
+    for (task, (handle, duration)) in tasks.iter().zip(join_all(timed).await) {
+        if let Some(context) = &context {
+            metrics::record_task_duration(&context.job_id, duration);
+        }
+        let error = match handle {
+            Ok(Ok(_)) => None,
+            Ok(Err(err)) => {
+                error!("{}", err);
+                Some(err.to_string())
+            }
             Err(err) => {
                 error!("Failed to join handles : {}", err);
+                Some(err.to_string())
             }
-        });
+        };
+        success = success && error.is_none();
+        let event = RunEvent::TaskFinished {
+            command: task.command.clone(),
+            success: error.is_none(),
+            error,
+        };
+        if let Some(sender) = &events {
+            let _ = sender.send(event.clone());
+        }
+        emit_in(context.as_ref(), event.into());
+    }
+    success
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct JobScheme {
     pub id: String,
     pub name: Option<String>,
     pub description: Option<String>,
+    /// Disabled jobs are kept on disk but never loaded into the daemon
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Free form labels, used to filter the job list
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     pub when: Option<When>,
     pub check_interval: Option<String>,
     pub conditions: Vec<ConditionScheme>,
     pub tasks: Vec<TaskScheme>,
 }
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc::unbounded_channel;
+
+    use super::*;
+    use crate::{conditions::fail_condition::FailCondition, fs::TempJobsDir};
+
+    /// Runs a job whose task leaves a marker file, returning whether the file was left
+    fn run_in_mode(
+        mode: RunMode,
+        conditions: Vec<Box<dyn Condition>>,
+    ) -> (JobStatusEnum, Vec<RunEvent>, bool) {
+        let jobs = TempJobsDir::new();
+        let marker = jobs.dir.path().join("ran");
+        let job = Job::new(
+            "modes".to_string(),
+            "modes".to_string(),
+            String::new(),
+            None,
+            None,
+            conditions,
+            vec![task::Task::new(format!("touch '{}'", marker.display()))],
+        );
+        let (sender, mut receiver) = unbounded_channel();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let status = runtime.block_on(run_job_with(job, mode, Some(sender)));
+        let mut events = vec![];
+        while let Ok(event) = receiver.try_recv() {
+            events.push(event);
+        }
+        (status, events, marker.exists())
+    }
+
+    #[test]
+    fn test_dry_run_skips_tasks_and_force_skips_conditions() {
+        let (status, events, ran) = run_in_mode(RunMode::DryRun, vec![]);
+        assert!(matches!(status, JobStatusEnum::Pending));
+        assert!(
+            !events
+                .iter()
+                .any(|event| matches!(event, RunEvent::TaskFinished { .. }))
+        );
+        assert!(!ran);
+
+        let (status, events, ran) = run_in_mode(RunMode::Force, vec![Box::new(FailCondition)]);
+        assert!(matches!(status, JobStatusEnum::Completed));
+        assert!(
+            !events
+                .iter()
+                .any(|event| matches!(event, RunEvent::Condition { .. }))
+        );
+        assert!(ran);
+    }
+}
@@ -1,6 +1,8 @@
 use std::{collections::HashMap, path::PathBuf};
 
 use serde_json::de;
+use sha2::{Digest, Sha256};
+use ulid::Ulid;
 
 use crate::{
     conditions::ConditionScheme,
@@ -8,12 +10,24 @@ use crate::{
     fs::{self, get_jobs_dir},
     job::{
         JobScheme,
-        get::{get_job, get_jobs_paths},
+        get::{get_job, get_job_path, get_jobs_paths},
     },
+    status::{JobStatusEnum, set::set_state_item},
     task::TaskScheme,
     time::When,
+    utilities::jsonc_parser::{jsonc_parse, jsonc_remove_field, jsonc_set_field},
 };
 
+/// Held while a job file is read, checked and rewritten, so concurrent updates from the daemon
+/// and the command line can't interleave. `edit` leaves the writing to the user's editor and
+/// isn't covered.
+fn lock_job_files() -> Result<std::fs::File, AutoPilotError> {
+    fs::lock_file("jobs").map_err(|e| AutoPilotError::Job {
+        message: "Failed to lock the job files".to_string(),
+        source: e,
+    })
+}
+
 pub fn add_job(
     name: Option<String>,
     description: Option<String>,
@@ -22,31 +36,83 @@ pub fn add_job(
     conditions: Vec<ConditionScheme>,
     tasks: Vec<TaskScheme>,
 ) -> Result<PathBuf, AutoPilotError> {
-    // Generate unique ID
-    let id = format!("job_{}", chrono::Utc::now().timestamp());
-
-    let job = JobScheme {
-        id: id.clone(),
-        name: name.clone(),
+    add_job_scheme(&JobScheme {
+        id: new_job_id(),
+        name,
         description,
+        enabled: true,
+        tags: vec![],
         when,
         check_interval,
         conditions,
         tasks,
-    };
+    })
+}
+
+/// A fresh job id. ULIDs sort by creation time like the old timestamp ids did, but jobs created
+/// in the same second, e.g. through the API, no longer get the same one.
+pub fn new_job_id() -> String {
+    format!("job_{}", Ulid::new().to_string().to_lowercase())
+}
+
+/// Checks that an id is a plain token, as it becomes part of the job's file name: letters, digits,
+/// `-`, `_` and `.`, without `..` or a leading dot
+pub fn validate_job_id(id: &str) -> Result<(), AutoPilotError> {
+    if id.is_empty() {
+        return Err(AutoPilotError::InvalidJob(
+            "id: must not be empty".to_string(),
+        ));
+    }
+    if id.starts_with('.')
+        || id.contains("..")
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(AutoPilotError::InvalidJob(format!(
+            "id: '{}' may only contain letters, digits, '-', '_' and '.', and not start with '.' or contain '..'",
+            id
+        )));
+    }
+    Ok(())
+}
+
+/// Names follow the id in the file name, so they must not carry path separators either
+fn validate_job_name(name: &str) -> Result<(), AutoPilotError> {
+    if name.contains(['/', '\\']) || name.contains("..") || name.chars().any(char::is_control) {
+        return Err(AutoPilotError::InvalidJob(format!(
+            "name: '{}' must not contain '/', '\\', '..' or control characters",
+            name.escape_debug()
+        )));
+    }
+    Ok(())
+}
+
+/// Writes a new job file for `job`, keeping its id
+pub fn add_job_scheme(job: &JobScheme) -> Result<PathBuf, AutoPilotError> {
+    let _lock = lock_job_files()?;
+    write_new_job(job)
+}
+
+/// [`add_job_scheme`] for callers that already hold [`lock_job_files`], which isn't reentrant.
+/// The id is checked for uniqueness under the same lock the file is written with.
+fn write_new_job(job: &JobScheme) -> Result<PathBuf, AutoPilotError> {
+    // Both end up in the file name, which must stay inside the jobs directory
+    validate_job_id(&job.id)?;
+    if let Some(name) = &job.name {
+        validate_job_name(name)?;
+    }
+    if get_job_path(&job.id).is_ok() {
+        return Err(AutoPilotError::JobExists(job.id.clone()));
+    }
 
     // Write to file
     let jobs_dir = get_jobs_dir()?;
     let job_file_path = jobs_dir.join(format!(
         "{}.jsonc",
-        if name.is_some() {
-            format!(
-                "{}_{}",
-                id,
-                name.expect("there is something wrong with world if you are seeing this")
-            )
-        } else {
-            id
+        match &job.name {
+            Some(name) => format!("{}_{}", job.id, name),
+            None => job.id.clone(),
         }
     ));
 
@@ -56,9 +122,12 @@ pub fn add_job(
 
     std::fs::write(
         &job_file_path,
-        serde_json::to_string_pretty(&job).expect("couldnt convert job to string"),
+        serde_json::to_string_pretty(job).expect("couldnt convert job to string"),
     )
-    .map_err(|e| AutoPilotError::InvalidJob(format!("Failed to write job file: {}", e)))?;
+    .map_err(|e| AutoPilotError::Job {
+        message: "Failed to write job file".to_string(),
+        source: e,
+    })?;
 
     Ok(job_file_path)
 }
@@ -90,7 +159,10 @@ pub fn remove_job(id: Option<String>, file_name: Option<String>) -> Result<(), A
                 )
             },
         )
-        .map_err(|e| AutoPilotError::InvalidJob(format!("Failed to remove job file: {}", e)))?;
+        .map_err(|e| AutoPilotError::Job {
+            message: "Failed to remove job file".to_string(),
+            source: e,
+        })?;
     }
     if id.is_some() && file_name.is_none() {
         let mut deleted = false;
@@ -100,8 +172,9 @@ pub fn remove_job(id: Option<String>, file_name: Option<String>) -> Result<(), A
             match get_job(path.clone()) {
                 Ok(value) => {
                     if value.id == id.clone().expect("you shouldnt see this") {
-                        std::fs::remove_file(path).map_err(|e| {
-                            AutoPilotError::InvalidJob(format!("Failed to remove job file: {}", e))
+                        std::fs::remove_file(path).map_err(|e| AutoPilotError::Job {
+                            message: "Failed to remove job file".to_string(),
+                            source: e,
                         })?;
                         deleted = true;
                     }
@@ -111,10 +184,138 @@ pub fn remove_job(id: Option<String>, file_name: Option<String>) -> Result<(), A
         }
 
         if deleted == false {
-            return Err(AutoPilotError::Job(
-                "Job associated with provided job id not found".to_string(),
-            ));
+            return Err(AutoPilotError::JobNotFound(id.unwrap_or_default()));
         }
     }
     Ok(())
 }
+
+/// Rewrites the `enabled` field of a job file in place, keeping the user's comments and formatting
+pub fn set_job_enabled(id: String, enabled: bool) -> Result<PathBuf, AutoPilotError> {
+    let job_file_path = {
+        let _lock = lock_job_files()?;
+        let job_file_path = get_job_path(&id)?;
+        let content = std::fs::read_to_string(&job_file_path).map_err(|e| AutoPilotError::Job {
+            message: "Failed to read job file".to_string(),
+            source: e,
+        })?;
+        let updated = jsonc_set_field(&content, "enabled", &enabled.to_string())?;
+        std::fs::write(&job_file_path, updated).map_err(|e| AutoPilotError::Job {
+            message: "Failed to write job file".to_string(),
+            source: e,
+        })?;
+        job_file_path
+    };
+
+    let status = if enabled {
+        JobStatusEnum::Unknown
+    } else {
+        JobStatusEnum::Disabled
+    };
+    set_state_item(id, status).map_err(AutoPilotError::State)?;
+    Ok(job_file_path)
+}
+
+/// Hex encoded SHA-256 of a job file's contents, changes whenever the file does
+pub fn job_etag(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Rewrites top-level fields of a job file in place, keeping its name, id and comments; a `null`
+/// value removes the field. With `expected_etag` the update only goes through if the file still
+/// has that [`job_etag`]. Returns the file and its new ETag.
+pub fn update_job(
+    id: &str,
+    changes: &serde_json::Map<String, serde_json::Value>,
+    expected_etag: Option<&str>,
+) -> Result<(PathBuf, String), AutoPilotError> {
+    let _lock = lock_job_files()?;
+    let job_file_path = get_job_path(id)?;
+    let content = std::fs::read_to_string(&job_file_path).map_err(|e| AutoPilotError::Job {
+        message: "Failed to read job file".to_string(),
+        source: e,
+    })?;
+    if let Some(expected) = expected_etag
+        && expected != job_etag(&content)
+    {
+        return Err(AutoPilotError::JobModified(format!(
+            "{} changed since it was read",
+            id
+        )));
+    }
+
+    let mut updated = content;
+    for (key, value) in changes {
+        if key == "id" {
+            if value.as_str() != Some(id) {
+                return Err(AutoPilotError::InvalidJob(
+                    "The id of a job can't be changed".to_string(),
+                ));
+            }
+            continue;
+        }
+        updated = if value.is_null() {
+            jsonc_remove_field(&updated, key)?
+        } else {
+            let value = serde_json::to_string_pretty(value)
+                .map_err(|e| AutoPilotError::Json(e.to_string()))?;
+            jsonc_set_field(&updated, key, &value)?
+        };
+    }
+
+    // Never leave a file behind that the daemon can't load
+    let parsed = jsonc_parse(&updated);
+    let mut deserializer = serde_json::Deserializer::from_str(&parsed);
+    serde_path_to_error::deserialize::<_, JobScheme>(&mut deserializer).map_err(|e| {
+        AutoPilotError::InvalidJobField {
+            path: e.path().to_string(),
+            message: e.into_inner().to_string(),
+        }
+    })?;
+
+    std::fs::write(&job_file_path, &updated).map_err(|e| AutoPilotError::Job {
+        message: "Failed to write job file".to_string(),
+        source: e,
+    })?;
+    Ok((job_file_path, job_etag(&updated)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::TempJobsDir;
+
+    #[test]
+    fn test_concurrent_adds_of_one_id_create_a_single_job() {
+        let jobs = TempJobsDir::new();
+        let job = JobScheme {
+            id: "racing".to_string(),
+            name: None,
+            description: None,
+            enabled: true,
+            tags: vec![],
+            when: None,
+            check_interval: None,
+            conditions: vec![],
+            tasks: vec![],
+        };
+        let added = std::thread::scope(|scope| {
+            let adds: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| add_job_scheme(&job).is_ok()))
+                .collect();
+            adds.into_iter()
+                .map(|add| add.join().unwrap_or(false))
+                .filter(|&added| added)
+                .count()
+        });
+        assert_eq!(added, 1);
+        assert!(matches!(
+            add_job_scheme(&job),
+            Err(AutoPilotError::JobExists(id)) if id == "racing"
+        ));
+        assert_eq!(std::fs::read_dir(jobs.dir.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,217 @@
+//! Checks job files without loading them, for `autopilot validate` and git hooks.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::{
+    conditions::{
+        ConditionScheme,
+        file_condition::CHECK_TYPES,
+        resource_condition::{OPERATORS, RESOURCE_TYPES},
+    },
+    job::JobScheme,
+    time::{When, next_run, parse_cron},
+    utilities::jsonc_parser::{jsonc_parse, jsonc_position},
+};
+
+/// Something wrong with a job file, with the line and column it was found at when known
+#[derive(Debug)]
+pub struct Problem {
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+pub struct FileReport {
+    pub path: PathBuf,
+    pub problems: Vec<Problem>,
+}
+
+/// Validates every file, and that no two of them share an id
+pub fn validate_files(paths: &[PathBuf]) -> Vec<FileReport> {
+    let mut reports = vec![];
+    let mut ids: HashMap<String, PathBuf> = HashMap::new();
+    for path in paths {
+        let mut problems = match std::fs::read_to_string(path) {
+            Err(e) => vec![Problem {
+                line: None,
+                column: None,
+                message: format!("Failed to read file: {}", e),
+            }],
+            Ok(content) => match validate_job(&content) {
+                Err(problem) => vec![problem],
+                Ok((scheme, mut problems)) => {
+                    match ids.get(&scheme.id) {
+                        Some(first) => problems.push(located(
+                            &content,
+                            "id",
+                            format!(
+                                "Job id '{}' is already used by {}",
+                                scheme.id,
+                                first.display()
+                            ),
+                        )),
+                        None => {
+                            ids.insert(scheme.id.clone(), path.clone());
+                        }
+                    }
+                    problems
+                }
+            },
+        };
+        problems.sort_by_key(|problem| (problem.line, problem.column));
+        reports.push(FileReport {
+            path: path.clone(),
+            problems,
+        });
+    }
+    reports
+}
+
+/// Parses a job file, then checks what deserializing can't: operators and check types that
+/// conditions would quietly treat as unsatisfied, and schedules that never fire
+pub fn validate_job(content: &str) -> Result<(JobScheme, Vec<Problem>), Problem> {
+    let parsed = jsonc_parse(content);
+    let mut deserializer = serde_json::Deserializer::from_str(&parsed);
+    let scheme: JobScheme = serde_path_to_error::deserialize(&mut deserializer).map_err(|e| {
+        let path = e.path().to_string();
+        let inner = e.into_inner();
+        // serde_json's message already ends with the position
+        let message = inner.to_string();
+        let message = match message.rfind(" at line ") {
+            Some(end) => message[..end].to_string(),
+            None => message,
+        };
+        let message = if path == "." || inner.is_syntax() || inner.is_eof() {
+            message
+        } else {
+            format!("{}: {}", path, message)
+        };
+        Problem {
+            line: Some(inner.line()).filter(|line| *line > 0),
+            column: Some(inner.column()).filter(|_| inner.line() > 0),
+            message,
+        }
+    })?;
+
+    let mut problems = vec![];
+    if let Some(when) = &scheme.when {
+        check_when(when, &mut problems);
+    }
+    check_conditions(&scheme.conditions, "conditions", &mut problems);
+    let problems = problems
+        .into_iter()
+        .map(|(path, message)| located(content, &path, message))
+        .collect();
+    Ok((scheme, problems))
+}
+
+fn located(content: &str, path: &str, message: String) -> Problem {
+    let position = jsonc_position(content, path);
+    Problem {
+        line: position.map(|(line, _)| line),
+        column: position.map(|(_, column)| column),
+        message: format!("{}: {}", path, message),
+    }
+}
+
+fn check_when(when: &When, problems: &mut Vec<(String, String)>) {
+    let path = "when.trigger".to_string();
+    if let When::Cron(expression) = when {
+        if let Err(e) = parse_cron(expression) {
+            problems.push((path, e.to_string()));
+        }
+        return;
+    }
+    match next_run(when) {
+        Ok(Some(_)) => {}
+        Ok(None) => problems.push((
+            path,
+            format!("{} is in the past, the job will never run", when.describe()),
+        )),
+        Err(e) => problems.push((path, e.to_string())),
+    }
+}
+
+fn check_conditions(
+    conditions: &[ConditionScheme],
+    path: &str,
+    problems: &mut Vec<(String, String)>,
+) {
+    let unknown = |field: &str, value: &str, known: &[&str]| {
+        format!(
+            "Unknown {} '{}', expected one of: {}",
+            field,
+            value,
+            known.join(", ")
+        )
+    };
+    for (index, condition) in conditions.iter().enumerate() {
+        let path = format!("{}[{}].condition", path, index);
+        match condition {
+            ConditionScheme::Resource(scheme) => {
+                if !RESOURCE_TYPES.contains(&scheme.resource_type.to_lowercase().as_str()) {
+                    problems.push((
+                        format!("{}.resource_type", path),
+                        unknown("resource_type", &scheme.resource_type, &RESOURCE_TYPES),
+                    ));
+                }
+                if let Some(operator) = &scheme.operator
+                    && !OPERATORS.contains(&operator.to_lowercase().as_str())
+                {
+                    problems.push((
+                        format!("{}.operator", path),
+                        unknown("operator", operator, &OPERATORS),
+                    ));
+                }
+            }
+            ConditionScheme::File(scheme)
+                if !CHECK_TYPES.contains(&scheme.check_type.to_lowercase().as_str()) =>
+            {
+                problems.push((
+                    format!("{}.check_type", path),
+                    unknown("check_type", &scheme.check_type, &CHECK_TYPES),
+                ));
+            }
+            ConditionScheme::Logical(scheme) => {
+                check_conditions(
+                    &scheme.conditions,
+                    &format!("{}.conditions", path),
+                    problems,
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_job_locates_problems() {
+        let content = r#"{
+  // nightly
+  "id": "j1",
+  "when": { "type": "once", "trigger": { "date": "2001/01/01", "time": "10:00" } },
+  "conditions": [
+    { "type": "logical", "condition": { "operator": "And", "conditions": [
+      { "type": "resource", "condition": { "resource_type": "cpu", "threshold": 5, "operator": "above" } }
+    ] } }
+  ],
+  "tasks": [{ "command": "true" }],
+}"#;
+        let (_, problems) = validate_job(content).unwrap();
+        assert_eq!(problems.len(), 2);
+        assert_eq!((problems[0].line, problems[0].column), (Some(4), Some(40)));
+        assert!(problems[0].message.contains("in the past"));
+        assert_eq!(problems[1].line, Some(7));
+        assert!(problems[1].message.starts_with(
+            "conditions[0].condition.conditions[0].condition.operator: Unknown operator 'above'"
+        ));
+
+        let problem = validate_job("{\n  \"id\": \"j1\",\n  \"tasks\": 3\n}").unwrap_err();
+        assert_eq!(problem.line, Some(3));
+        assert!(problem.message.starts_with("tasks: invalid type"));
+    }
+}
@@ -0,0 +1,16 @@
+pub mod api;
+pub mod autopilot;
+pub mod cli;
+pub mod conditions;
+pub mod config;
+pub mod cross_platform;
+pub mod error;
+pub mod fs;
+pub mod job;
+pub mod language;
+pub mod logging;
+pub mod service;
+pub mod status;
+pub mod task;
+pub mod time;
+pub mod utilities;
@@ -0,0 +1,96 @@
+//! Attaches the job being run to every log record emitted while running it.
+
+use std::future::Future;
+
+use log::{
+    Log, Metadata, Record,
+    kv::{self, Key, Source, Value, VisitSource},
+};
+use ulid::Ulid;
+
+use crate::job::Job;
+
+tokio::task_local! {
+    static JOB_CONTEXT: JobLogContext;
+}
+
+/// Structured fields identifying a single run of a job
+#[derive(Clone, Debug)]
+pub struct JobLogContext {
+    pub job_id: String,
+    pub job_name: String,
+    pub run_id: String,
+}
+
+impl JobLogContext {
+    /// Context for a new run of `job`, with a freshly generated run id
+    pub fn new(job: &Job) -> Self {
+        Self {
+            job_id: job.id.clone(),
+            job_name: job.name.clone(),
+            run_id: Ulid::new().to_string(),
+        }
+    }
+}
+
+/// Runs `future` with `context` attached to all records it logs
+pub async fn with_job_context<F: Future>(context: JobLogContext, future: F) -> F::Output {
+    JOB_CONTEXT.scope(context, future).await
+}
+
+/// Context of the job run the current task belongs to, if any
+pub fn current_job_context() -> Option<JobLogContext> {
+    JOB_CONTEXT.try_with(|context| context.clone()).ok()
+}
+
+/// Wraps the actual logger, adding `JOB_ID`, `JOB_NAME` and `RUN_ID` to records logged from a job
+pub struct ContextLogger {
+    pub inner: Box<dyn Log>,
+}
+
+impl Log for ContextLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        let logged = JOB_CONTEXT.try_with(|context| {
+            let fields = ContextFields {
+                context,
+                inner: record.key_values(),
+            };
+            self.inner
+                .log(&record.to_builder().key_values(&fields).build());
+        });
+        if logged.is_err() {
+            self.inner.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+struct ContextFields<'a> {
+    context: &'a JobLogContext,
+    inner: &'a dyn Source,
+}
+
+impl Source for ContextFields<'_> {
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn VisitSource<'kvs>) -> Result<(), kv::Error> {
+        visitor.visit_pair(
+            Key::from_str("JOB_ID"),
+            Value::from(self.context.job_id.as_str()),
+        )?;
+        visitor.visit_pair(
+            Key::from_str("JOB_NAME"),
+            Value::from(self.context.job_name.as_str()),
+        )?;
+        visitor.visit_pair(
+            Key::from_str("RUN_ID"),
+            Value::from(self.context.run_id.as_str()),
+        )?;
+        self.inner.visit(visitor)
+    }
+}
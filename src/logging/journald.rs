@@ -0,0 +1,129 @@
+//! Writes log records to the systemd journal using its native datagram protocol.
+
+use std::{io::Result as IoResult, os::unix::net::UnixDatagram};
+
+use flexi_logger::{DeferredNow, writers::LogWriter};
+use log::{
+    Level, Record,
+    kv::{self, Key, Value, VisitSource},
+};
+
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+pub struct JournaldWriter {
+    socket: UnixDatagram,
+}
+
+impl JournaldWriter {
+    pub fn new() -> IoResult<Self> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(JOURNALD_SOCKET)?;
+        Ok(Self { socket })
+    }
+}
+
+impl LogWriter for JournaldWriter {
+    fn write(&self, _now: &mut DeferredNow, record: &Record) -> IoResult<()> {
+        self.socket.send(&encode_entry(record))?;
+        Ok(())
+    }
+
+    fn flush(&self) -> IoResult<()> {
+        Ok(())
+    }
+}
+
+/// Serializes a record into a journal entry, with its key-values as extra fields
+pub fn encode_entry(record: &Record) -> Vec<u8> {
+    let mut entry = Vec::new();
+    add_field(&mut entry, "PRIORITY", priority(record.level()));
+    add_field(&mut entry, "MESSAGE", &record.args().to_string());
+    add_field(&mut entry, "SYSLOG_IDENTIFIER", env!("CARGO_PKG_NAME"));
+    add_field(&mut entry, "TARGET", record.target());
+    if let Some(module) = record.module_path() {
+        add_field(&mut entry, "CODE_MODULE", module);
+    }
+    if let Some(file) = record.file() {
+        add_field(&mut entry, "CODE_FILE", file);
+    }
+    if let Some(line) = record.line() {
+        add_field(&mut entry, "CODE_LINE", &line.to_string());
+    }
+    let _ = record.key_values().visit(&mut FieldVisitor(&mut entry));
+    entry
+}
+
+/// Appends `KEY=value`, switching to the length-prefixed form for multi-line values
+fn add_field(entry: &mut Vec<u8>, key: &str, value: &str) {
+    entry.extend_from_slice(key.as_bytes());
+    if value.contains('\n') {
+        entry.push(b'\n');
+        entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        entry.push(b'=');
+    }
+    entry.extend_from_slice(value.as_bytes());
+    entry.push(b'\n');
+}
+
+/// Journal field names may only contain uppercase letters, digits and underscores
+fn field_name(key: &str) -> String {
+    let name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    name.trim_start_matches(|c: char| c == '_' || c.is_ascii_digit())
+        .to_string()
+}
+
+fn priority(level: Level) -> &'static str {
+    match level {
+        Level::Error => "3",
+        Level::Warn => "4",
+        Level::Info => "6",
+        Level::Debug | Level::Trace => "7",
+    }
+}
+
+struct FieldVisitor<'a>(&'a mut Vec<u8>);
+
+impl<'kvs> VisitSource<'kvs> for FieldVisitor<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        let name = field_name(key.as_str());
+        if !name.is_empty() {
+            add_field(self.0, &name, &value.to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_entry_fields() {
+        let fields = [("job_id", "1700000000"), ("JOB_NAME", "backup")];
+        let record = Record::builder()
+            .level(Level::Warn)
+            .target("autopilot")
+            .args(format_args!("first\nsecond"))
+            .key_values(&fields)
+            .build();
+
+        let entry = encode_entry(&record);
+
+        assert!(entry.starts_with(b"PRIORITY=4\nMESSAGE\n"));
+        let message = b"MESSAGE\n\x0c\0\0\0\0\0\0\0first\nsecond\n";
+        assert!(entry.windows(message.len()).any(|window| window == message));
+        let text = String::from_utf8_lossy(&entry);
+        assert!(text.contains("\nJOB_ID=1700000000\n"));
+        assert!(text.ends_with("\nJOB_NAME=backup\n"));
+    }
+}
@@ -1,17 +1,86 @@
-use flexi_logger::{Duplicate, FileSpec, LevelFilter, Logger, WriteMode};
+use std::{io::Result as IoResult, sync::OnceLock};
 
-use crate::fs::get_logs_path;
+use flexi_logger::{
+    DeferredNow, Duplicate, FileSpec, LevelFilter, Logger, LoggerHandle, WriteMode,
+    detailed_format, writers::LogWriter,
+};
+use log::{
+    Record,
+    kv::{self, Key, Value, VisitSource},
+    warn,
+};
+
+use crate::{
+    config::{LogSink, SyslogFacility, get_config},
+    fs::get_logs_path,
+    logging::context::ContextLogger,
+};
+
+pub mod context;
+#[cfg(target_os = "linux")]
+pub mod journald;
+pub mod read;
+
+/// Keeps the writers alive for the lifetime of the process
+static LOGGER_HANDLE: OnceLock<LoggerHandle> = OnceLock::new();
 
 pub fn init_logging(verbose: bool) {
-    let log_level = if verbose { "debug" } else { "info" };
-    match Logger::try_with_str(log_level)
-        .expect("Failed to init logger")
-        .log_to_file(FileSpec::default().directory(get_logs_path()))
-        .duplicate_to_stdout(Duplicate::All)
+    let config = get_config().logging;
+    let log_level = if verbose {
+        "debug"
+    } else {
+        config.level.as_str()
+    };
+    let logger = match Logger::try_with_str(log_level) {
+        Ok(logger) => logger,
+        Err(e) => {
+            println!("Invalid log level '{}', using info: {}", log_level, e);
+            Logger::try_with_str("info").expect("Failed to init logger")
+        }
+    };
+
+    let mut writers: Vec<Box<dyn LogWriter>> = vec![];
+    if config.sinks.contains(&LogSink::Journald) {
+        match journald_writer() {
+            Ok(writer) => writers.push(writer),
+            Err(e) => println!("Systemd journal logger is not available, reason: {}", e),
+        }
+    }
+    if config.sinks.contains(&LogSink::Syslog) {
+        match syslog_writer(config.syslog_facility) {
+            Ok(writer) => writers.push(writer),
+            Err(e) => println!("Syslog logger is not available, reason: {}", e),
+        }
+    }
+
+    let file_spec = FileSpec::default().directory(get_logs_path());
+    let to_file = config.sinks.contains(&LogSink::File);
+    let to_stdout = config.sinks.contains(&LogSink::Stdout);
+    let has_writers = !writers.is_empty();
+    let logger = match (to_file, has_writers) {
+        (true, false) => logger.log_to_file(file_spec),
+        (true, true) => logger.log_to_file_and_writer(file_spec, Box::new(MultiWriter(writers))),
+        (false, true) => logger.log_to_writer(Box::new(MultiWriter(writers))),
+        (false, false) if to_stdout => logger.log_to_stdout(),
+        (false, false) => logger.do_not_log(),
+    };
+    let logger = if to_stdout && (to_file || has_writers) {
+        logger.duplicate_to_stdout(Duplicate::All)
+    } else {
+        logger
+    };
+
+    // Files get timestamps so they can be read back later on
+    match logger
+        .format_for_files(detailed_format)
         .write_mode(WriteMode::BufferAndFlush)
-        .start()
+        .build()
     {
-        Ok(_) => {}
+        Ok((logger, handle)) => {
+            if log::set_boxed_logger(Box::new(ContextLogger { inner: logger })).is_ok() {
+                let _ = LOGGER_HANDLE.set(handle);
+            }
+        }
         Err(e) => {
             println!("Logger start failed, reason: {}", e);
         }
@@ -19,40 +88,9 @@ pub fn init_logging(verbose: bool) {
     let level = if verbose {
         LevelFilter::Debug
     } else {
-        LevelFilter::Info
+        log_level.parse().unwrap_or(LevelFilter::Info)
     };
     log::set_max_level(level);
-    // system logging code (currently is just commented):
-    // #[cfg(target_os = "linux")]
-    // {
-    //     // init journald or syslog
-    //     match systemd_journal_logger::JournalLog::new() {
-    //         Ok(logger) => match logger.install() {
-    //             Ok(_) => {}
-    //             Err(e) => {
-    //                 println!("Systemd journal logger is not available, reason: {}", e);
-    //             }
-    //         },
-    //         Err(e) => {
-    //             println!("Systemd journal logger is not available, reason: {}", e);
-    //         }
-    //     }
-    // }
-
-    // #[cfg(all(unix, not(target_os = "linux")))]
-    // {
-    //     // fallback for other unix / macos
-    //     match syslog::unix(syslog::Formatter3164::default()) {
-    //         Ok(writer) => {
-    //             let logger = syslog::BasicLogger::new(writer);
-    //             let _ = log::set_boxed_logger(Box::new(logger));
-    //         }
-    //         Err(e) => {
-    //             println!("Syslog logger is not available, reason: {}", e);
-    //         }
-    //     }
-    // }
-
     // #[cfg(target_os = "windows")]
     // {
     //     // Try to register and init winlog2 for Windows Event Log
@@ -66,3 +104,117 @@ pub fn init_logging(verbose: bool) {
     //     }
     // }
 }
+
+/// Applies a changed log level, the sinks are only set up once at startup
+pub fn reload_logging(verbose: bool) {
+    if verbose {
+        return;
+    }
+    let level = get_config().logging.level;
+    match LOGGER_HANDLE.get() {
+        Some(handle) => {
+            if let Err(e) = handle.parse_new_spec(&level) {
+                warn!("Failed to apply log level '{}': {}", level, e);
+            }
+        }
+        None => log::set_max_level(level.parse().unwrap_or(LevelFilter::Info)),
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn journald_writer() -> IoResult<Box<dyn LogWriter>> {
+    Ok(Box::new(journald::JournaldWriter::new()?))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn journald_writer() -> IoResult<Box<dyn LogWriter>> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "journald is only available on Linux",
+    ))
+}
+
+#[cfg(unix)]
+pub fn syslog_writer(facility: SyslogFacility) -> IoResult<Box<dyn LogWriter>> {
+    use flexi_logger::writers::{
+        SyslogConnection, SyslogFacility as Facility, SyslogLineHeader, SyslogWriter,
+    };
+
+    let facility = match facility {
+        SyslogFacility::User => Facility::UserLevel,
+        SyslogFacility::Daemon => Facility::SystemDaemons,
+        SyslogFacility::Local0 => Facility::LocalUse0,
+        SyslogFacility::Local1 => Facility::LocalUse1,
+        SyslogFacility::Local2 => Facility::LocalUse2,
+        SyslogFacility::Local3 => Facility::LocalUse3,
+        SyslogFacility::Local4 => Facility::LocalUse4,
+        SyslogFacility::Local5 => Facility::LocalUse5,
+        SyslogFacility::Local6 => Facility::LocalUse6,
+        SyslogFacility::Local7 => Facility::LocalUse7,
+    };
+    let writer: Box<dyn LogWriter> = SyslogWriter::builder(
+        SyslogConnection::syslog_call(),
+        SyslogLineHeader::Rfc3164,
+        facility,
+    )
+    .format(syslog_format)
+    .build()?;
+    Ok(writer)
+}
+
+#[cfg(not(unix))]
+pub fn syslog_writer(_facility: SyslogFacility) -> IoResult<Box<dyn LogWriter>> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "syslog is only available on Unix",
+    ))
+}
+
+/// Plain message followed by the record's structured fields as `KEY=value` pairs
+#[cfg_attr(not(unix), allow(dead_code))]
+fn syslog_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> IoResult<()> {
+    write!(w, "{}", record.args())?;
+    let _ = record.key_values().visit(&mut FieldWriter(w));
+    Ok(())
+}
+
+struct FieldWriter<'a>(&'a mut dyn std::io::Write);
+
+impl<'kvs> VisitSource<'kvs> for FieldWriter<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        write!(self.0, " {}={:?}", key, value)?;
+        Ok(())
+    }
+}
+
+/// Fans records out to every configured system log
+struct MultiWriter(Vec<Box<dyn LogWriter>>);
+
+impl LogWriter for MultiWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> IoResult<()> {
+        let mut result = Ok(());
+        for writer in &self.0 {
+            if let Err(e) = writer.write(now, record) {
+                result = Err(e);
+            }
+        }
+        result
+    }
+
+    fn flush(&self) -> IoResult<()> {
+        for writer in &self.0 {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn shutdown(&self) {
+        for writer in &self.0 {
+            writer.shutdown();
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Reads back the log files written by the `file` sink.
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+
+use crate::fs::get_logs_path;
+
+/// Log levels from least to most severe, as `detailed_format` writes them
+pub const LEVELS: [&str; 5] = ["TRACE", "DEBUG", "INFO", "WARN", "ERROR"];
+
+/// One record of a log file, as written by `detailed_format`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct LogRecord {
+    /// `2026-10-18 22:52:45.907222 +00:00`
+    pub timestamp: String,
+    pub level: String,
+    pub job_id: Option<String>,
+    pub run_id: Option<String>,
+    pub message: String,
+}
+
+impl LogRecord {
+    /// `22:52:45 INFO message`, for places with little room
+    pub fn short(&self) -> String {
+        let time = self.timestamp.get(11..19).unwrap_or(&self.timestamp);
+        format!("{} {} {}", time, self.level, self.message)
+    }
+
+    pub fn time(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_str(&self.timestamp, "%Y-%m-%d %H:%M:%S%.f %:z").ok()
+    }
+
+    /// Position of the level in [`LEVELS`], higher is more severe
+    pub fn severity(&self) -> Option<usize> {
+        LEVELS.iter().position(|level| *level == self.level)
+    }
+}
+
+/// Log files in the logs directory, oldest first
+pub fn log_files() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(get_logs_path()) else {
+        return vec![];
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "log"))
+        .collect();
+    // File names start with the time they were created at
+    files.sort();
+    files
+}
+
+/// Every record of every log file, oldest first. Lines continuing a multi-line message are
+/// added to the record they belong to.
+pub fn all_records() -> Vec<LogRecord> {
+    let mut records: Vec<LogRecord> = vec![];
+    for file in log_files() {
+        let Ok(content) = std::fs::read_to_string(file) else {
+            continue;
+        };
+        for line in content.lines() {
+            match parse_line(line) {
+                Some(record) => records.push(record),
+                None => {
+                    if let Some(record) = records.last_mut() {
+                        record.message.push('\n');
+                        record.message += &strip_ansi(line);
+                    }
+                }
+            }
+        }
+    }
+    records
+}
+
+/// The last `limit` records logged for a job, oldest first
+pub fn job_records(job_id: &str, limit: usize) -> Vec<LogRecord> {
+    let mut records = vec![];
+    for file in log_files().iter().rev() {
+        let Ok(content) = std::fs::read_to_string(file) else {
+            continue;
+        };
+        let mut found: Vec<LogRecord> = content
+            .lines()
+            .filter_map(parse_line)
+            .filter(|record| record.job_id.as_deref() == Some(job_id))
+            .collect();
+        found.append(&mut records);
+        records = found;
+        if records.len() >= limit {
+            break;
+        }
+    }
+    let skip = records.len().saturating_sub(limit);
+    records.split_off(skip)
+}
+
+/// Parses `[timestamp] LEVEL [module] file:line: {KEY="value", ...} message`; lines that continue
+/// a multi-line message don't start with a timestamp and give `None`
+pub fn parse_line(line: &str) -> Option<LogRecord> {
+    let (timestamp, rest) = line.strip_prefix('[')?.split_once("] ")?;
+    let (level, rest) = rest.split_once(' ')?;
+    let rest = match rest.strip_prefix('[') {
+        Some(rest) => rest.split_once("] ")?.1,
+        None => rest,
+    };
+    let (_location, rest) = rest.split_once(": ")?;
+
+    let mut record = LogRecord {
+        timestamp: timestamp.to_string(),
+        level: level.to_string(),
+        job_id: None,
+        run_id: None,
+        message: rest.to_string(),
+    };
+    if let Some(fields) = rest.strip_prefix('{')
+        && let Some((fields, message)) = parse_fields(fields)
+    {
+        for (key, value) in fields {
+            match key {
+                "JOB_ID" => record.job_id = Some(value),
+                "RUN_ID" => record.run_id = Some(value),
+                _ => {}
+            }
+        }
+        record.message = message.to_string();
+    }
+    // Messages colored for a terminal keep their escape codes in the file
+    record.message = strip_ansi(&record.message);
+    Some(record)
+}
+
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // `ESC [ parameters letter`
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+/// Splits `KEY="value", KEY=value} message` into its fields and the message
+fn parse_fields(mut rest: &str) -> Option<(Vec<(&str, String)>, &str)> {
+    let mut fields = vec![];
+    loop {
+        let (key, after) = rest.split_once('=')?;
+        let (value, after) = match after.strip_prefix('"') {
+            Some(quoted) => unquote(quoted)?,
+            None => {
+                let end = after.find([',', '}'])?;
+                (after[..end].to_string(), &after[end..])
+            }
+        };
+        fields.push((key, value));
+        if let Some(after) = after.strip_prefix(", ") {
+            rest = after;
+        } else {
+            let message = after.strip_prefix('}')?;
+            return Some((fields, message.strip_prefix(' ').unwrap_or(message)));
+        }
+    }
+}
+
+/// Reads a Debug formatted string up to its closing quote
+fn unquote(quoted: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &quoted[index + 1..])),
+            '\\' => match chars.next()?.1 {
+                'n' => value.push('\n'),
+                't' => value.push('\t'),
+                escaped => value.push(escaped),
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line_reads_job_fields() {
+        let line = r#"[2026-10-18 22:52:45.907222 +00:00] INFO [autopilot_rs::job] src/job/mod.rs:139: {JOB_ID="j1", JOB_NAME="a \"b\"} c", RUN_ID="01M58"} Job Completed : again"#;
+        let record = parse_line(line).unwrap();
+        assert_eq!(record.job_id.as_deref(), Some("j1"));
+        assert_eq!(record.run_id.as_deref(), Some("01M58"));
+        assert_eq!(record.message, "Job Completed : again");
+        assert_eq!(record.short(), "22:52:45 INFO Job Completed : again");
+
+        let line = "[2026-10-18 22:56:10.553268 +00:00] INFO [autopilot_rs::api::routes] src/api/routes.rs:142: Api server started";
+        let record = parse_line(line).unwrap();
+        assert_eq!(record.job_id, None);
+        assert_eq!(record.message, "Api server started");
+        assert!(parse_line("  continued").is_none());
+
+        let line = "[2026-10-18 22:56:10.553268 +00:00] WARN [autopilot_rs::job] src/job/mod.rs:111: \x1b[33mRunning job\x1b[0m : again";
+        let record = parse_line(line).unwrap();
+        assert_eq!(record.message, "Running job : again");
+        assert_eq!(record.severity(), Some(3));
+        assert!(record.time().is_some());
+    }
+}
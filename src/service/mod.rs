@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+
+use crate::{error::AutoPilotError, fs::get_autopilot_path};
+
+pub mod notify;
+
+pub const SERVICE_NAME: &str = "autopilot-rs.service";
+
+/// Directory systemd reads user units from
+pub fn get_unit_dir() -> Result<PathBuf, AutoPilotError> {
+    let config_home = match std::env::var("XDG_CONFIG_HOME") {
+        Ok(path) if !path.is_empty() => PathBuf::from(path),
+        _ => std::env::home_dir()
+            .ok_or_else(|| AutoPilotError::Service("Couldn't get home directory".to_string()))?
+            .join(".config"),
+    };
+    Ok(config_home.join("systemd").join("user"))
+}
+
+pub fn get_unit_path() -> Result<PathBuf, AutoPilotError> {
+    Ok(get_unit_dir()?.join(SERVICE_NAME))
+}
+
+/// Builds a user unit that serves the current binary with the current config path
+pub fn generate_unit(api: bool) -> Result<String, AutoPilotError> {
+    let executable = std::env::current_exe().map_err(|e| {
+        AutoPilotError::Service(format!("Couldn't locate the autopilot binary: {}", e))
+    })?;
+    let mut exec_start = vec![
+        quote(&executable.to_string_lossy()),
+        "--config-path".to_string(),
+        quote(&get_autopilot_path()),
+        "serve".to_string(),
+    ];
+    if api {
+        exec_start.push("--api".to_string());
+    }
+
+    Ok(format!(
+        "[Unit]
+Description=AutoPilot-rs automation daemon
+After=network-online.target
+
+[Service]
+Type=notify
+NotifyAccess=main
+ExecStart={}
+ExecReload=/bin/kill -HUP $MAINPID
+Restart=on-failure
+RestartSec=5
+WatchdogSec=30
+
+[Install]
+WantedBy=default.target
+",
+        exec_start.join(" ")
+    ))
+}
+
+/// Quotes a command line argument for systemd if it contains whitespace
+fn quote(arg: &str) -> String {
+    if arg.contains(char::is_whitespace) || arg.contains('"') {
+        format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        arg.to_string()
+    }
+}
+
+/// Runs `systemctl --user` with the given arguments, returning its combined output
+pub fn systemctl(args: &[&str]) -> Result<String, AutoPilotError> {
+    let mut command = vec!["--user"];
+    command.extend_from_slice(args);
+    let output = duct::cmd("systemctl", command)
+        .stderr_to_stdout()
+        .unchecked()
+        .stdout_capture()
+        .run()
+        .map_err(|e| AutoPilotError::Service(format!("Failed to run systemctl: {}", e)))?;
+    let text = String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string();
+    if output.status.success() {
+        Ok(text)
+    } else {
+        Err(AutoPilotError::Service(format!(
+            "systemctl --user {} failed: {}",
+            args.join(" "),
+            text
+        )))
+    }
+}
+
+pub fn install(api: bool) -> Result<PathBuf, AutoPilotError> {
+    let unit_path = get_unit_path()?;
+    std::fs::create_dir_all(get_unit_dir()?).map_err(|e| {
+        AutoPilotError::Service(format!("Failed to create systemd unit directory: {}", e))
+    })?;
+    std::fs::write(&unit_path, generate_unit(api)?)
+        .map_err(|e| AutoPilotError::Service(format!("Failed to write unit file: {}", e)))?;
+    systemctl(&["daemon-reload"])?;
+    systemctl(&["enable", "--now", SERVICE_NAME])?;
+    Ok(unit_path)
+}
+
+pub fn uninstall() -> Result<PathBuf, AutoPilotError> {
+    let unit_path = get_unit_path()?;
+    if !unit_path.exists() {
+        return Err(AutoPilotError::Service(format!(
+            "{} is not installed",
+            SERVICE_NAME
+        )));
+    }
+    // The unit may already be stopped or disabled by hand, that's fine
+    let _ = systemctl(&["disable", "--now", SERVICE_NAME]);
+    std::fs::remove_file(&unit_path)
+        .map_err(|e| AutoPilotError::Service(format!("Failed to remove unit file: {}", e)))?;
+    systemctl(&["daemon-reload"])?;
+    Ok(unit_path)
+}
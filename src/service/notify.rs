@@ -0,0 +1,91 @@
+//! Minimal implementation of the systemd `sd_notify` protocol.
+//!
+//! The service manager passes a datagram socket in `NOTIFY_SOCKET`; every state update is a
+//! newline separated list of `KEY=VALUE` assignments sent to it as a single datagram.
+
+use std::time::Duration;
+
+use log::debug;
+use tokio_cron_scheduler::JobScheduler;
+
+use crate::error::AutoPilotError;
+
+/// Sends a state update to the service manager, doing nothing when not started by systemd
+pub fn notify(state: &str) {
+    let Ok(socket) = std::env::var("NOTIFY_SOCKET") else {
+        return;
+    };
+    if let Err(e) = notify_to(&socket, state) {
+        debug!("Failed to notify service manager: {}", e);
+    }
+}
+
+/// Sends a state update to the notification socket at `socket`
+#[cfg(unix)]
+pub fn notify_to(socket: &str, state: &str) -> std::io::Result<()> {
+    use std::os::unix::net::UnixDatagram;
+
+    let datagram = UnixDatagram::unbound()?;
+    // Names starting with '@' live in the abstract namespace
+    #[cfg(target_os = "linux")]
+    if let Some(name) = socket.strip_prefix('@') {
+        use std::os::linux::net::SocketAddrExt;
+        use std::os::unix::net::SocketAddr;
+
+        let address = SocketAddr::from_abstract_name(name)?;
+        datagram.send_to_addr(state.as_bytes(), &address)?;
+        return Ok(());
+    }
+    datagram.send_to(state.as_bytes(), socket)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn notify_to(_socket: &str, _state: &str) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// How often the watchdog has to be pinged, if the service manager enabled it for this process
+pub fn watchdog_interval() -> Option<Duration> {
+    let usec = std::env::var("WATCHDOG_USEC").ok()?.parse::<u64>().ok()?;
+    if let Ok(pid) = std::env::var("WATCHDOG_PID")
+        && pid.parse::<u32>().ok() != Some(std::process::id())
+    {
+        return None;
+    }
+    // Ping twice per period so a single late tick doesn't get us killed
+    Some(Duration::from_micros(usec / 2))
+}
+
+/// Pings the watchdog from a job on `scheduler`, so the pings stop as soon as the scheduler stops
+/// running jobs, not only when the whole process is gone
+pub async fn schedule_watchdog(scheduler: &JobScheduler) -> Result<(), AutoPilotError> {
+    let Some(interval) = watchdog_interval() else {
+        return Ok(());
+    };
+    let ping = tokio_cron_scheduler::Job::new_repeated(interval, |_, _| notify("WATCHDOG=1"))?;
+    scheduler.add(ping).await?;
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::net::UnixDatagram;
+
+    use super::*;
+
+    #[test]
+    fn test_notify_to_sends_state_as_one_datagram() {
+        let path =
+            std::env::temp_dir().join(format!("autopilot-notify-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixDatagram::bind(&path).unwrap();
+
+        notify_to(path.to_str().unwrap(), "READY=1\nSTATUS=2 jobs loaded").unwrap();
+
+        let mut buffer = [0u8; 128];
+        let size = listener.recv(&mut buffer).unwrap();
+        assert_eq!(&buffer[..size], b"READY=1\nSTATUS=2 jobs loaded");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -5,20 +5,19 @@ use std::fs;
 // use serde_json::value;
 
 use crate::{
-    fs::get_status_path,
-    status::{JobStatusEnum, StatusLog, set::set_status_initial},
+    fs::{get_history_path, get_status_path},
+    status::{HistoryEntry, JobStatusEnum, StatusLog, set::set_status_initial},
     utilities,
 };
 
 pub fn get_status_log() -> StatusLog {
-    let state_path = get_status_path();
-    let state_string = match fs::read_to_string(state_path) {
-        Ok(content) => content,
+    match read_status_log() {
+        Ok(status_log) => status_log,
         Err(e) => {
             if log::log_enabled!(log::Level::Error) {
-                error!("Failed to read state file: {}", e);
+                error!("{}", e);
             } else {
-                eprintln!("Failed to read state file: {}", e);
+                eprintln!("{}", e);
             }
             // Initialize status and retry
             if let Err(init_e) = set_status_initial() {
@@ -33,38 +32,17 @@ pub fn get_status_log() -> StatusLog {
                     statuses: vec![],
                 };
             }
-            return get_status_log();
+            get_status_log()
         }
-    };
-    // println!("state {}", state_string);
-    let status_log: StatusLog =
-        match serde_json::from_str(utilities::jsonc_parser::jsonc_parse(&state_string).as_str()) {
-            Ok(value) => value,
-            Err(e) => {
-                // Use the index `i` directly instead of searching again
+    }
+}
 
-                if log::log_enabled!(log::Level::Error) {
-                    error!("Failed to parse state file: \n Error: {}", e);
-                } else {
-                    eprintln!("Failed to parse state file: \n Error: {}", e);
-                }
-                if let Err(init_e) = set_status_initial() {
-                    if log::log_enabled!(log::Level::Error) {
-                        error!("Failed to initialize state: {}", init_e);
-                    } else {
-                        eprintln!("Failed to initialize state: {}", init_e);
-                    }
-                    // Return a default empty status log
-                    return StatusLog {
-                        time: Local::now().to_rfc3339(),
-                        statuses: vec![],
-                    };
-                }
-                // vec![]
-                get_status_log()
-            }
-        };
-    status_log
+/// Reads the status file as it is, without starting it over when it is missing or broken
+pub fn read_status_log() -> Result<StatusLog, String> {
+    let content = fs::read_to_string(get_status_path())
+        .map_err(|e| format!("Failed to read state file: {}", e))?;
+    serde_json::from_str(utilities::jsonc_parser::jsonc_parse(&content).as_str())
+        .map_err(|e| format!("Failed to parse state file: \n Error: {}", e))
 }
 
 pub fn get_job_status(id: String) -> JobStatusEnum {
@@ -76,3 +54,15 @@ pub fn get_job_status(id: String) -> JobStatusEnum {
         .find(|job| job.id == id)
         .map_or(JobStatusEnum::Unknown, |job| job.status.clone())
 }
+
+/// The last `limit` finished runs, of one job or of all of them, newest first
+pub fn get_history(id: Option<&str>, limit: usize) -> Vec<HistoryEntry> {
+    let content = fs::read_to_string(get_history_path()).unwrap_or_default();
+    content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<HistoryEntry>(line).ok())
+        .filter(|entry| id.is_none_or(|id| entry.job_id == id))
+        .take(limit)
+        .collect()
+}
@@ -1,4 +1,6 @@
+use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 pub mod get;
 pub mod set;
@@ -14,14 +16,38 @@ pub struct JobStatusStruct {
     pub id: String,
     pub name: String,
     pub status: JobStatusEnum,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_run: Option<LastRun>,
 }
 
 impl JobStatusStruct {
     pub fn new(id: String, name: String, status: JobStatusEnum) -> Self {
-        JobStatusStruct { id, name, status }
+        JobStatusStruct {
+            id,
+            name,
+            status,
+            last_run: None,
+        }
     }
 }
-#[derive(Debug, Deserialize, Serialize, Clone)]
+
+/// The most recent finished run of a job
+#[derive(Deserialize, Serialize, Debug, Clone, ToSchema)]
+pub struct LastRun {
+    pub run_id: Option<String>,
+    pub started: DateTime<Local>,
+    pub finished: DateTime<Local>,
+    pub status: JobStatusEnum,
+}
+/// A finished run in the run history
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct HistoryEntry {
+    pub job_id: String,
+    #[serde(flatten)]
+    pub run: LastRun,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 pub enum JobStatusEnum {
     /// Job is queued but not yet started
     Pending,
@@ -41,4 +67,6 @@ pub enum JobStatusEnum {
     Unknown,
     /// Job is scheduled but not yet started
     Scheduled,
+    /// Job is disabled in its job file and will not be loaded
+    Disabled,
 }
@@ -1,53 +1,190 @@
-use std::fs;
+use std::{fs, path::PathBuf, sync::Mutex};
+
+use log::debug;
 
 use crate::{
-    fs::get_status_path,
-    job::get::get_jobs,
-    status::{JobStatusEnum, JobStatusStruct, StatusLog, get::get_status_log},
+    fs::{get_history_path, get_status_path, lock_file},
+    job::get::get_all_jobs,
+    status::{
+        HistoryEntry, JobStatusEnum, JobStatusStruct, LastRun, StatusLog, get::read_status_log,
+    },
+    utilities::jsonc_parser::jsonc_parse,
 };
 
+/// Every change to the status file reads, changes and writes it back, so concurrent runs, API
+/// calls and commands like `enable` take turns instead of dropping each other's changes
+fn lock_status() -> Result<fs::File, String> {
+    lock_file("status").map_err(|e| format!("Failed to lock the status file: {}", e))
+}
+
+/// Applies `change` to the status file under [`lock_status`], starting over if it can't be read
+fn update_status_log(change: impl FnOnce(&mut StatusLog)) -> Result<(), String> {
+    let _guard = lock_status()?;
+    let mut status_log = read_status_log().unwrap_or_else(|e| {
+        debug!("{}, starting the status file over", e);
+        initial_status_log()
+    });
+    change(&mut status_log);
+    write_status_log(&status_log)
+}
+
+/// Writes a sibling file and renames it over the status file, so readers never see half of it
+fn write_status_log(status_log: &StatusLog) -> Result<(), String> {
+    let path = PathBuf::from(get_status_path());
+    let json = serde_json::to_string_pretty(status_log).map_err(|e| e.to_string())?;
+    let partial = path.with_extension("jsonc.tmp");
+    fs::write(&partial, json).map_err(|e| e.to_string())?;
+    fs::rename(&partial, &path).map_err(|e| e.to_string())
+}
+
 pub fn set_state_item(id: String, status: JobStatusEnum) -> Result<(), String> {
-    let state_path = get_status_path();
-    let mut status_log = get_status_log().clone();
-    let mut statuses = status_log
-        .statuses
-        .clone()
-        .into_iter()
-        .collect::<Vec<JobStatusStruct>>();
-    let index = statuses.iter().position(|item| item.id == id);
-
-    match index {
-        Some(index) => {
-            statuses[index].status = status;
+    update_status_log(|status_log| {
+        match status_log.statuses.iter_mut().find(|item| item.id == id) {
+            Some(item) => item.status = status,
+            None => debug!("No status entry for job {}", id),
         }
-        None => {
-            println!("none");
+    })
+}
+
+/// Sets the status of a single job, adding its entry if the job is new and dropping it with
+/// `None` when the job is gone
+pub fn set_status_entry(id: &str, entry: Option<(String, JobStatusEnum)>) -> Result<(), String> {
+    update_status_log(|status_log| {
+        let index = status_log.statuses.iter().position(|item| item.id == id);
+        match (index, entry) {
+            (Some(index), Some((name, status))) => {
+                status_log.statuses[index].name = name;
+                status_log.statuses[index].status = status;
+            }
+            (None, Some((name, status))) => {
+                status_log
+                    .statuses
+                    .push(JobStatusStruct::new(id.to_string(), name, status))
+            }
+            (Some(index), None) => {
+                status_log.statuses.remove(index);
+            }
+            (None, None) => {}
         }
-    }
-    status_log.statuses = statuses;
-    let json = serde_json::to_string_pretty(&status_log).map_err(|e| e.to_string())?;
-    // println!("json2 {}", json);
-    fs::write(&state_path, json).map_err(|e| e.to_string())?;
+    })
+}
+
+/// Records how a run ended, its status and last run together, adding the job's entry if it has
+/// none yet
+pub fn set_finished_run(id: &str, name: &str, last_run: LastRun) -> Result<(), String> {
+    update_status_log(|status_log| {
+        match status_log.statuses.iter_mut().find(|item| item.id == id) {
+            Some(item) => {
+                item.status = last_run.status.clone();
+                item.last_run = Some(last_run);
+            }
+            None => {
+                let mut item =
+                    JobStatusStruct::new(id.to_string(), name.to_string(), last_run.status.clone());
+                item.last_run = Some(last_run);
+                status_log.statuses.push(item);
+            }
+        }
+    })
+}
+
+/// Runs kept in the history file, older ones are dropped
+const HISTORY_LIMIT: usize = 1000;
+/// Jobs finishing together would otherwise drop each other's runs
+static HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+/// Adds a finished run to the run history
+pub fn append_history(id: &str, run: LastRun) -> Result<(), String> {
+    let path = get_history_path();
+    let entry = HistoryEntry {
+        job_id: id.to_string(),
+        run,
+    };
+    let line = serde_json::to_string(&entry).map_err(|e| e.to_string())?;
+    let _guard = HISTORY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let content = fs::read_to_string(&path).unwrap_or_default();
+    let mut lines: Vec<&str> = content.lines().collect();
+    lines.push(&line);
+    let excess = lines.len().saturating_sub(HISTORY_LIMIT);
+    let mut content = lines[excess..].join("\n");
+    content.push('\n');
+    fs::write(&path, content).map_err(|e| e.to_string())?;
     Ok(())
 }
 
 pub fn set_status_initial() -> Result<(), String> {
-    let state_path = get_status_path();
+    let _guard = lock_status()?;
+    write_status_log(&initial_status_log())
+}
+
+/// A status entry for every job, keeping the last runs of the current file
+fn initial_status_log() -> StatusLog {
+    // Statuses start over, but the last runs are history worth keeping. The file is read
+    // directly since get_status_log() comes back here when it can't parse it.
+    let previous: Vec<JobStatusStruct> = fs::read_to_string(get_status_path())
+        .ok()
+        .and_then(|content| serde_json::from_str::<StatusLog>(&jsonc_parse(&content)).ok())
+        .map(|log| log.statuses)
+        .unwrap_or_default();
     let mut status_log: StatusLog = StatusLog {
         time: chrono::Local::now().to_string(),
         statuses: Vec::new(),
     };
 
-    for job in get_jobs(true) {
+    for job in get_all_jobs(true) {
+        let last_run = previous
+            .iter()
+            .find(|item| item.id == job.id)
+            .and_then(|item| item.last_run.clone());
         status_log.statuses.push(JobStatusStruct {
             id: job.id,
             name: job.name,
-            status: JobStatusEnum::Unknown,
+            status: if job.enabled {
+                JobStatusEnum::Unknown
+            } else {
+                JobStatusEnum::Disabled
+            },
+            last_run,
         });
     }
-    let json = serde_json::to_string_pretty(&status_log).map_err(|e| e.to_string())?;
-    // println!("json : {}", json);
-    fs::write(&state_path, json).map_err(|e| e.to_string())?;
+    status_log
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+
+    use super::*;
+    use crate::fs::TempJobsDir;
+
+    #[test]
+    fn test_runs_finishing_together_are_all_recorded() {
+        let _jobs = TempJobsDir::new();
+        let _ = fs::remove_file(get_status_path());
+        let threads: Vec<_> = (0..8)
+            .map(|index| {
+                std::thread::spawn(move || {
+                    let last_run = LastRun {
+                        run_id: None,
+                        started: Local::now(),
+                        finished: Local::now(),
+                        status: JobStatusEnum::Completed,
+                    };
+                    let id = format!("job-{}", index);
+                    set_finished_run(&id, &id, last_run).unwrap();
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // None of the jobs had an entry, each run adds its own
+        let status_log = read_status_log().unwrap();
+        for index in 0..8 {
+            let id = format!("job-{}", index);
+            let item = status_log.statuses.iter().find(|item| item.id == id);
+            assert!(item.is_some_and(|item| item.last_run.is_some()), "{}", id);
+        }
+    }
 }
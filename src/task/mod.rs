@@ -1,10 +1,11 @@
 use serde::{Deserialize, Serialize};
 use tokio::task::JoinHandle;
+use utoipa::ToSchema;
 
 use crate::error::AutoPilotError;
 
 pub mod runner;
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct Task {
     pub command: String,
@@ -14,15 +15,13 @@ impl Task {
     pub fn new(command: String) -> Self {
         Task { command }
     }
-    pub fn run(&self) -> JoinHandle<Result<(), AutoPilotError>> {
+    /// Runs the task on a blocking thread, handing every output line to `on_line`
+    pub fn run_with_output<F>(&self, on_line: F) -> JoinHandle<Result<(), AutoPilotError>>
+    where
+        F: FnMut(&str) + Send + 'static,
+    {
         let command = self.command.clone();
-        tokio::task::spawn(async move {
-            return runner::async_run(command).await;
-        })
-    }
-    pub fn run_sync(&self) -> Result<(), AutoPilotError> {
-        runner::sync_run(self.command.clone())?;
-        Ok(())
+        tokio::task::spawn_blocking(move || runner::sync_run_with_output(command, on_line))
     }
 }
 
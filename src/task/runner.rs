@@ -1,19 +1,41 @@
+use std::io::{BufRead, BufReader};
+
 use log::error;
 
-use crate::error::AutoPilotError;
+use crate::{config::get_config, error::AutoPilotError};
 
-pub fn sync_run(command: String) -> Result<(), AutoPilotError> {
-    if let Err(e) = duct_sh::sh_dangerous(&command).run() {
-        // error!("Failed to run task '{}': {}", command, e);
-        return Err(AutoPilotError::JobExecution(format!(
-            "Failed to run task '{}': {}",
-            command, e
-        )));
+/// The program and arguments a task command is run with, `defaults.shell` or the platform shell
+pub fn shell_command_line(command: &str) -> Vec<String> {
+    if let Some(shell) = get_config().defaults.shell {
+        vec![shell, "-c".to_string(), command.to_string()]
+    } else if cfg!(windows) {
+        let comspec = std::env::var("COMSPEC").unwrap_or_else(|_| "cmd.exe".to_string());
+        vec![comspec, "/C".to_string(), command.to_string()]
+    } else {
+        vec!["/bin/sh".to_string(), "-c".to_string(), command.to_string()]
     }
-    Ok(())
 }
 
-pub async fn async_run(command: String) -> Result<(), AutoPilotError> {
-    sync_run(command)?;
+fn shell_expression(command: &str) -> duct::Expression {
+    let command_line = shell_command_line(command);
+    duct::cmd(&command_line[0], &command_line[1..])
+}
+
+/// Runs a command with stdout and stderr merged, calling `on_line` for every line it prints
+pub fn sync_run_with_output<F>(command: String, mut on_line: F) -> Result<(), AutoPilotError>
+where
+    F: FnMut(&str),
+{
+    let to_error = |e: std::io::Error| {
+        AutoPilotError::JobExecution(format!("Failed to run task '{}': {}", command, e))
+    };
+    let reader = shell_expression(&command)
+        .stderr_to_stdout()
+        .reader()
+        .map_err(to_error)?;
+    // The reader reports a non-zero exit status as an error once the output is exhausted
+    for line in BufReader::new(reader).lines() {
+        on_line(&line.map_err(to_error)?);
+    }
     Ok(())
 }
@@ -1,6 +1,10 @@
-use std::future::Future;
+use std::{
+    future::Future,
+    sync::{Mutex, MutexGuard},
+};
 
 use tokio_cron_scheduler::JobScheduler;
+use uuid::Uuid;
 
 use crate::{
     error::AutoPilotError,
@@ -9,6 +13,13 @@ use crate::{
     time::{When, to_cron_expression},
 };
 
+/// Scheduler entries by job id, so a single job can be taken off the scheduler
+static SCHEDULED: Mutex<Vec<(String, Uuid)>> = Mutex::new(Vec::new());
+
+fn scheduled() -> MutexGuard<'static, Vec<(String, Uuid)>> {
+    SCHEDULED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
 pub async fn add_job<Fut, F>(
     job: &Job,
     scheduler: &JobScheduler,
@@ -55,21 +66,22 @@ where
                 let run_job = run_job_clone.clone();
 
                 Box::pin(async move {
-                    run_job(job);
+                    run_job(job).await;
                 })
             })?;
-            // Add job to scheduler
-            scheduler.add(cron_job).await?;
         }
         When::Cron(expression) => {
             let job_clone = job.clone();
             let run_job_clone = run_job.clone();
 
-            cron_job = tokio_cron_scheduler::Job::new(expression.as_str(), move |_, _| {
+            // Recurring schedules have always been evaluated in UTC
+            cron_job = tokio_cron_scheduler::Job::new_async(expression.as_str(), move |_, _| {
                 let job = job_clone.clone();
-                run_job_clone(job);
+                let run_job = run_job_clone.clone();
+                Box::pin(async move {
+                    run_job(job).await;
+                })
             })?;
-            scheduler.add(cron_job).await?;
             _once = false;
         }
         _ => {
@@ -77,17 +89,46 @@ where
             let run_job_clone = run_job.clone();
             let cron_expression = to_cron_expression(when).unwrap();
 
-            cron_job = tokio_cron_scheduler::Job::new(cron_expression.as_str(), move |_, _| {
-                let job = job_clone.clone();
-                run_job_clone(job);
-            })?;
-            scheduler.add(cron_job).await?;
+            cron_job =
+                tokio_cron_scheduler::Job::new_async(cron_expression.as_str(), move |_, _| {
+                    let job = job_clone.clone();
+                    let run_job = run_job_clone.clone();
+                    Box::pin(async move {
+                        run_job(job).await;
+                    })
+                })?;
             _once = false;
         }
     }
 
+    let uuid = scheduler.add(cron_job).await?;
+    scheduled().push((job.id.clone(), uuid));
+
     // The following variables are still unused, but now they are only warnings.
     // _cron_exp = to_cron_expression(when).unwrap();
 
     Ok(())
 }
+
+/// Takes a job off the scheduler, leaving the other jobs scheduled
+pub async fn unschedule_job(job_id: &str, scheduler: &JobScheduler) -> Result<(), AutoPilotError> {
+    let uuids: Vec<Uuid> = {
+        let mut scheduled = scheduled();
+        let uuids = scheduled
+            .iter()
+            .filter(|(id, _)| id == job_id)
+            .map(|(_, uuid)| *uuid)
+            .collect();
+        scheduled.retain(|(id, _)| id != job_id);
+        uuids
+    };
+    for uuid in uuids {
+        scheduler.remove(&uuid).await?;
+    }
+    Ok(())
+}
+
+/// Forgets every scheduler entry, once the scheduler itself is shut down
+pub fn clear_scheduled() {
+    scheduled().clear();
+}
@@ -1,14 +1,17 @@
-
-use chrono::{
-    DateTime, Local, NaiveDate, NaiveTime, ParseError, TimeZone, Timelike,
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, ParseError, TimeZone, Timelike, Utc};
+use croner::{
+    Cron,
+    parser::{CronParser, Seconds},
 };
 use log::error;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
+use crate::error::AutoPilotError;
 
 pub mod add;
 pub mod init;
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 #[serde(deny_unknown_fields)]
 #[serde(tag = "type", content = "trigger", rename_all = "lowercase")]
 pub enum When {
@@ -20,11 +23,46 @@ pub enum When {
     Cron(String),
 }
 
+impl When {
+    /// Human readable form of the schedule
+    pub fn describe(&self) -> String {
+        match self {
+            When::Once(value) => format!("once at {} {} local time", value.date, value.time),
+            When::Daily(value) => format!("daily at {} UTC", value.time),
+            When::Weekly(value) => format!("weekly on Monday at {} UTC", value.time),
+            When::Monthly(value) => format!("monthly on the 1st at {} UTC", value.time),
+            When::Yearly(value) => format!("yearly on January 1st at {} UTC", value.time),
+            When::Cron(expression) => format!("cron \"{}\" in UTC", expression),
+        }
+    }
+
+    /// The timezone the schedule is written in. Recurring schedules are handed to the scheduler
+    /// as cron expressions, which it evaluates in UTC.
+    pub fn timezone(&self) -> &'static str {
+        match self {
+            When::Once(_) => "local",
+            _ => "UTC",
+        }
+    }
+
+    /// The `type` of the schedule as written in job files
+    pub fn kind(&self) -> &'static str {
+        match self {
+            When::Once(_) => "once",
+            When::Daily(_) => "daily",
+            When::Weekly(_) => "weekly",
+            When::Monthly(_) => "monthly",
+            When::Yearly(_) => "yearly",
+            When::Cron(_) => "cron",
+        }
+    }
+}
+
 // pub struct When {
 //     type: WhenTypes,
 // }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct DateTimeScheme {
     pub date: String,
     pub time: String,
@@ -65,7 +103,7 @@ impl DateTimeScheme {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct TimeScheme {
     pub time: String,
 }
@@ -87,27 +125,110 @@ pub fn to_cron_expression(when: When) -> Result<String, ParseError> {
         When::Daily(time_scheme) => {
             let time = time_scheme.parse()?;
             // Run every day at the specified time
-            // Format: "minute hour * * *"
-            Ok(format!("{} {} * * *", time.minute(), time.hour()))
+            // Format: "second minute hour * * *"
+            Ok(format!("0 {} {} * * *", time.minute(), time.hour()))
         }
         When::Weekly(time_scheme) => {
             let time = time_scheme.parse()?;
             // Run every week on Monday at the specified time
-            // Format: "minute hour * * 1" (1 = Monday)
-            Ok(format!("{} {} * * 1", time.minute(), time.hour()))
+            // Format: "second minute hour * * 1" (1 = Monday)
+            Ok(format!("0 {} {} * * 1", time.minute(), time.hour()))
         }
         When::Monthly(time_scheme) => {
             let time = time_scheme.parse()?;
             // Run every month on the 1st at the specified time
-            // Format: "minute hour 1 * *"
-            Ok(format!("{} {} 1 * *", time.minute(), time.hour()))
+            // Format: "second minute hour 1 * *"
+            Ok(format!("0 {} {} 1 * *", time.minute(), time.hour()))
         }
         When::Yearly(time_scheme) => {
             let time = time_scheme.parse()?;
             // Run every year on January 1st at the specified time
-            // Format: "minute hour 1 1 *"
-            Ok(format!("{} {} 1 1 *", time.minute(), time.hour()))
+            // Format: "second minute hour 1 1 *"
+            Ok(format!("0 {} {} 1 1 *", time.minute(), time.hour()))
         }
+        When::Cron(expression) => Ok(expression),
         _ => Ok("".to_string()),
     }
 }
+
+/// Parses a cron expression with the same rules the scheduler uses (seconds field required)
+pub fn parse_cron(expression: &str) -> Result<Cron, AutoPilotError> {
+    CronParser::builder()
+        .seconds(Seconds::Required)
+        .dom_and_dow(true)
+        .build()
+        .parse(expression)
+        .map_err(|e| {
+            AutoPilotError::Time(format!("Invalid cron expression '{}': {}", expression, e))
+        })
+}
+
+/// Computes the next time a schedule fires, or `None` for a one-off date in the past
+pub fn next_run(when: &When) -> Result<Option<DateTime<Local>>, AutoPilotError> {
+    let now = Local::now().with_nanosecond(0).unwrap_or_else(Local::now);
+    match when {
+        When::Once(value) => {
+            let target_time = value
+                .parse()
+                .map_err(|err| AutoPilotError::Time(format!("Failed to parse time : {}", err)))?;
+            Ok((target_time > now).then_some(target_time))
+        }
+        // Recurring schedules are evaluated in UTC by the scheduler
+        _ => {
+            let expression = to_cron_expression(when.clone())
+                .map_err(|err| AutoPilotError::Time(format!("Failed to parse time : {}", err)))?;
+            let next = parse_cron(&expression)?
+                .find_next_occurrence(&now.with_timezone(&Utc), false)
+                .map_err(|e| AutoPilotError::Time(format!("Failed to find next run: {}", e)))?;
+            Ok(Some(next.with_timezone(&Local)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Datelike, Weekday};
+
+    use super::*;
+
+    #[test]
+    fn test_fixed_schedules_find_their_next_run_in_utc() {
+        let at = || TimeScheme {
+            time: "09:30".to_string(),
+        };
+        let cases = [
+            (When::Daily(at()), "0 30 9 * * *"),
+            (When::Weekly(at()), "0 30 9 * * 1"),
+            (When::Monthly(at()), "0 30 9 1 * *"),
+            (When::Yearly(at()), "0 30 9 1 1 *"),
+        ];
+        for (when, expression) in cases {
+            assert_eq!(to_cron_expression(when.clone()).unwrap(), expression);
+            let next = next_run(&when).unwrap().unwrap();
+            assert!(next > Local::now(), "{}", when.describe());
+            let next = next.with_timezone(&Utc);
+            assert_eq!((next.hour(), next.minute(), next.second()), (9, 30, 0));
+            match when {
+                When::Weekly(_) => assert_eq!(next.weekday(), Weekday::Mon),
+                When::Monthly(_) => assert_eq!(next.day(), 1),
+                When::Yearly(_) => assert_eq!((next.month(), next.day()), (1, 1)),
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn test_cron_expressions_run_in_utc() {
+        let next = next_run(&When::Cron("0 30 9 * * *".to_string()))
+            .unwrap()
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!((next.hour(), next.minute(), next.second()), (9, 30, 0));
+    }
+
+    #[test]
+    fn test_parse_cron_requires_the_seconds_field() {
+        assert!(parse_cron("0 30 9 * * *").is_ok());
+        assert!(parse_cron("30 9 * * *").is_err());
+    }
+}
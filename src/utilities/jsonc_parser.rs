@@ -1,12 +1,20 @@
+use crate::error::AutoPilotError;
+
+/// Turns JSONC into plain JSON. Comments and trailing commas are blanked out with spaces rather
+/// than removed, so lines and columns in parse errors still point into the original text.
 pub fn jsonc_parse(jsonc: &str) -> String {
     let mut in_string = false;
     let mut escaped = false;
     let mut in_line_comment = false;
     let mut in_block_comment = false;
     let mut result = String::new();
-    
-    // Stack to track braces/brackets for trailing comma detection
-    let mut stack = Vec::new();
+    let blank = |result: &mut String, ch: char| {
+        if ch == '\n' {
+            result.push('\n');
+        } else {
+            result.extend(std::iter::repeat_n(' ', ch.len_utf8()));
+        }
+    };
 
     let mut chars = jsonc.chars().peekable();
 
@@ -14,14 +22,16 @@ pub fn jsonc_parse(jsonc: &str) -> String {
         if in_line_comment {
             if ch == '\n' {
                 in_line_comment = false;
-                result.push('\n');
             }
+            blank(&mut result, ch);
             continue;
         } else if in_block_comment {
             if ch == '*' && chars.peek() == Some(&'/') {
                 in_block_comment = false;
                 chars.next(); // Skip the '/'
+                result.push(' ');
             }
+            blank(&mut result, ch);
             continue;
         } else if in_string {
             result.push(ch);
@@ -43,27 +53,15 @@ pub fn jsonc_parse(jsonc: &str) -> String {
                     if chars.peek() == Some(&'/') {
                         in_line_comment = true;
                         chars.next(); // Skip second '/'
+                        result.push_str("  ");
                     } else if chars.peek() == Some(&'*') {
                         in_block_comment = true;
                         chars.next(); // Skip '*'
+                        result.push_str("  ");
                     } else {
                         result.push(ch);
                     }
                 }
-                '{' | '[' => {
-                    stack.push(ch);
-                    result.push(ch);
-                }
-                '}' | ']' => {
-                    // Remove trailing comma before closing brace/bracket
-                    let mut temp_result = result.trim_end().to_string();
-                    if temp_result.ends_with(',') {
-                        temp_result.pop(); // Remove the trailing comma
-                    }
-                    result = temp_result;
-                    result.push(ch);
-                    stack.pop();
-                }
                 ',' => {
                     // Check if this is a trailing comma
                     let mut lookahead = chars.clone();
@@ -101,10 +99,8 @@ pub fn jsonc_parse(jsonc: &str) -> String {
                         }
                     }
                     
-                    if !is_trailing {
-                        result.push(',');
-                    }
-                    // If it's trailing, we simply skip adding it
+                    // A trailing comma is blanked out like a comment
+                    result.push(if is_trailing { ' ' } else { ',' });
                 }
                 _ => result.push(ch),
             }
@@ -112,4 +108,343 @@ pub fn jsonc_parse(jsonc: &str) -> String {
     }
 
     result
-}
\ No newline at end of file
+}
+/// A top-level member of a JSONC object, as byte offsets into the source text
+struct JsoncMember {
+    key: String,
+    key_start: usize,
+    value_start: usize,
+    value_end: usize,
+}
+
+fn skip_whitespace_and_comments(bytes: &[u8], mut pos: usize) -> usize {
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b' ' | b'\t' | b'\n' | b'\r' => pos += 1,
+            b'/' if bytes.get(pos + 1) == Some(&b'/') => {
+                while pos < bytes.len() && bytes[pos] != b'\n' {
+                    pos += 1;
+                }
+            }
+            b'/' if bytes.get(pos + 1) == Some(&b'*') => {
+                pos += 2;
+                while pos < bytes.len()
+                    && !(bytes[pos] == b'*' && bytes.get(pos + 1) == Some(&b'/'))
+                {
+                    pos += 1;
+                }
+                pos = (pos + 2).min(bytes.len());
+            }
+            _ => break,
+        }
+    }
+    pos
+}
+
+fn skip_string(bytes: &[u8], mut pos: usize) -> usize {
+    // `pos` points at the opening quote
+    pos += 1;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => pos += 2,
+            b'"' => return pos + 1,
+            _ => pos += 1,
+        }
+    }
+    bytes.len()
+}
+
+fn skip_value(bytes: &[u8], mut pos: usize) -> usize {
+    match bytes.get(pos) {
+        Some(b'"') => skip_string(bytes, pos),
+        Some(b'{') | Some(b'[') => {
+            let mut depth = 0;
+            while pos < bytes.len() {
+                pos = skip_whitespace_and_comments(bytes, pos);
+                match bytes.get(pos) {
+                    Some(b'"') => {
+                        pos = skip_string(bytes, pos);
+                        continue;
+                    }
+                    Some(b'{') | Some(b'[') => depth += 1,
+                    Some(b'}') | Some(b']') => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return pos + 1;
+                        }
+                    }
+                    _ => {}
+                }
+                pos += 1;
+            }
+            bytes.len()
+        }
+        _ => {
+            while pos < bytes.len()
+                && !matches!(
+                    bytes[pos],
+                    b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r' | b'/'
+                )
+            {
+                pos += 1;
+            }
+            pos
+        }
+    }
+}
+
+/// Locates the members of the top-level object, returning the offset of its opening brace too
+fn jsonc_top_level_members(jsonc: &str) -> Option<(usize, Vec<JsoncMember>)> {
+    let open = skip_whitespace_and_comments(jsonc.as_bytes(), 0);
+    Some((open, jsonc_object_members(jsonc, open)?))
+}
+
+/// Locates the members of the object whose opening brace is at `open`
+fn jsonc_object_members(jsonc: &str, open: usize) -> Option<Vec<JsoncMember>> {
+    let bytes = jsonc.as_bytes();
+    if bytes.get(open) != Some(&b'{') {
+        return None;
+    }
+    let mut members = Vec::new();
+    let mut pos = open + 1;
+    loop {
+        pos = skip_whitespace_and_comments(bytes, pos);
+        match bytes.get(pos) {
+            Some(b'}') => return Some(members),
+            Some(b'"') => {
+                let key_end = skip_string(bytes, pos);
+                let key: String = serde_json::from_str(&jsonc[pos..key_end]).ok()?;
+                let colon = skip_whitespace_and_comments(bytes, key_end);
+                if bytes.get(colon) != Some(&b':') {
+                    return None;
+                }
+                let value_start = skip_whitespace_and_comments(bytes, colon + 1);
+                let value_end = skip_value(bytes, value_start);
+                members.push(JsoncMember {
+                    key,
+                    key_start: pos,
+                    value_start,
+                    value_end,
+                });
+                pos = skip_whitespace_and_comments(bytes, value_end);
+                if bytes.get(pos) == Some(&b',') {
+                    pos += 1;
+                }
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// Offsets of the elements of the array whose opening bracket is at `open`
+fn jsonc_array_elements(bytes: &[u8], open: usize) -> Option<Vec<usize>> {
+    if bytes.get(open) != Some(&b'[') {
+        return None;
+    }
+    let mut elements = Vec::new();
+    let mut pos = open + 1;
+    loop {
+        pos = skip_whitespace_and_comments(bytes, pos);
+        match bytes.get(pos) {
+            Some(b']') => return Some(elements),
+            Some(_) => {
+                let end = skip_value(bytes, pos);
+                if end == pos {
+                    return None;
+                }
+                elements.push(pos);
+                pos = skip_whitespace_and_comments(bytes, end);
+                if bytes.get(pos) == Some(&b',') {
+                    pos += 1;
+                }
+            }
+            None => return None,
+        }
+    }
+}
+
+/// 1-based line and column (in bytes, like serde_json's errors) of a byte offset
+pub fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text.as_bytes()[..offset.min(text.len())];
+    let line_start = before
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+    (line, before.len() - line_start + 1)
+}
+
+/// Finds the value at a path like `conditions[1].condition.operator` (as reported by
+/// serde_path_to_error) and returns its line and column
+pub fn jsonc_position(jsonc: &str, path: &str) -> Option<(usize, usize)> {
+    let bytes = jsonc.as_bytes();
+    let mut pos = skip_whitespace_and_comments(bytes, 0);
+    for part in path.split('.').filter(|part| !part.is_empty()) {
+        let mut indexes = part.split('[');
+        let key = indexes.next().unwrap_or_default();
+        if !key.is_empty() {
+            pos = jsonc_object_members(jsonc, pos)?
+                .into_iter()
+                .find(|member| member.key == key)?
+                .value_start;
+        }
+        for index in indexes {
+            let index: usize = index.trim_end_matches(']').parse().ok()?;
+            pos = *jsonc_array_elements(bytes, pos)?.get(index)?;
+        }
+    }
+    Some(line_column(jsonc, pos))
+}
+
+fn line_indent(jsonc: &str, pos: usize) -> &str {
+    let line_start = jsonc[..pos].rfind('\n').map_or(0, |i| i + 1);
+    let line = &jsonc[line_start..pos];
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// Sets a top-level field of a JSONC object to the given raw JSON value, rewriting only that
+/// value so the comments and formatting of the rest of the document are kept.
+/// The field is appended after the last member if it does not exist yet.
+pub fn jsonc_set_field(jsonc: &str, key: &str, value: &str) -> Result<String, AutoPilotError> {
+    let (open, members) = jsonc_top_level_members(jsonc)
+        .ok_or_else(|| AutoPilotError::Json("Document is not a JSON object".to_string()))?;
+    let bytes = jsonc.as_bytes();
+    let mut result = jsonc.to_string();
+
+    if let Some(member) = members.iter().find(|member| member.key == key) {
+        let indent = line_indent(jsonc, member.key_start);
+        let value = value.replace('\n', &format!("\n{}", indent));
+        result.replace_range(member.value_start..member.value_end, &value);
+        return Ok(result);
+    }
+
+    let key = serde_json::to_string(key).map_err(|e| AutoPilotError::Json(e.to_string()))?;
+    match members.last() {
+        Some(last) => {
+            let indent = line_indent(jsonc, last.key_start).to_string();
+            let value = value.replace('\n', &format!("\n{}", indent));
+            let after = skip_whitespace_and_comments(bytes, last.value_end);
+            if bytes.get(after) == Some(&b',') {
+                // Keep a comment that trails the comma on the previous member's line
+                let rest_of_line = jsonc[after + 1..].split('\n').next().unwrap_or_default();
+                let insert_at =
+                    if rest_of_line.trim().is_empty() || rest_of_line.trim().starts_with("//") {
+                        after + 1 + rest_of_line.len()
+                    } else {
+                        after + 1
+                    };
+                result.insert_str(insert_at, &format!("\n{}{}: {},", indent, key, value));
+            } else {
+                result.insert_str(last.value_end, &format!(",\n{}{}: {}", indent, key, value));
+            }
+        }
+        None => {
+            result.insert_str(open + 1, &format!("\n  {}: {}\n", key, value));
+        }
+    }
+    Ok(result)
+}
+
+/// Removes a top-level field of a JSONC object along with the rest of its line, leaving the
+/// rest of the document untouched. Removing a field that does not exist is not an error.
+pub fn jsonc_remove_field(jsonc: &str, key: &str) -> Result<String, AutoPilotError> {
+    let (_, members) = jsonc_top_level_members(jsonc)
+        .ok_or_else(|| AutoPilotError::Json("Document is not a JSON object".to_string()))?;
+    let Some(member) = members.iter().find(|member| member.key == key) else {
+        return Ok(jsonc.to_string());
+    };
+    let bytes = jsonc.as_bytes();
+
+    let mut end = member.value_end;
+    while matches!(bytes.get(end), Some(b' ') | Some(b'\t')) {
+        end += 1;
+    }
+    if bytes.get(end) == Some(&b',') {
+        end += 1;
+    }
+    // A member on a line of its own goes away with its line, including a trailing comment
+    let line_start = jsonc[..member.key_start].rfind('\n').map_or(0, |i| i + 1);
+    let rest_of_line = jsonc[end..].split('\n').next().unwrap_or_default();
+    let trailing = rest_of_line.trim();
+    let start = if jsonc[line_start..member.key_start].trim().is_empty()
+        && (trailing.is_empty()
+            || trailing.starts_with("//")
+            || (trailing.starts_with("/*") && trailing.ends_with("*/")))
+    {
+        end += rest_of_line.len();
+        if bytes.get(end) == Some(&b'\n') {
+            end += 1;
+        }
+        line_start
+    } else {
+        member.key_start
+    };
+
+    let mut result = jsonc.to_string();
+    result.replace_range(start..end, "");
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JOB: &str = r#"{
+  // morning job
+  "id": "job_1",
+  "name": "Morning", /* trailing */
+  "tasks": [
+    { "command": "echo hi" }, // greet
+  ], // tasks
+}
+"#;
+
+    #[test]
+    fn test_jsonc_set_field_replaces_value_in_place() {
+        let updated = jsonc_set_field(JOB, "name", "\"Evening\"").unwrap();
+        assert_eq!(updated, JOB.replace("\"Morning\"", "\"Evening\""));
+    }
+
+    #[test]
+    fn test_jsonc_set_field_appends_missing_field() {
+        let updated = jsonc_set_field(JOB, "enabled", "false").unwrap();
+        assert!(updated.contains("  ], // tasks\n  \"enabled\": false,\n}"));
+        assert!(updated.contains("\"name\": \"Morning\", /* trailing */\n"));
+
+        let value: serde_json::Value = serde_json::from_str(&jsonc_parse(&updated)).unwrap();
+        assert_eq!(value["enabled"], false);
+        assert_eq!(value["tasks"][0]["command"], "echo hi");
+    }
+
+    #[test]
+    fn test_jsonc_remove_field_keeps_the_rest() {
+        let updated = jsonc_remove_field(JOB, "name").unwrap();
+        assert_eq!(
+            updated,
+            JOB.replace("  \"name\": \"Morning\", /* trailing */\n", "")
+        );
+        let updated = jsonc_remove_field(&updated, "tasks").unwrap();
+        let value: serde_json::Value = serde_json::from_str(&jsonc_parse(&updated)).unwrap();
+        assert_eq!(value, serde_json::json!({ "id": "job_1" }));
+        assert!(updated.contains("// morning job"));
+    }
+
+    #[test]
+    fn test_jsonc_parse_keeps_positions() {
+        let parsed = jsonc_parse(JOB);
+        assert_eq!(parsed.lines().count(), JOB.lines().count());
+        let error = serde_json::from_str::<serde_json::Value>(&jsonc_parse(
+            "{\n  /* a */ \"id\": \"x\", // b\n  \"tasks\": [1,],\n  oops\n}",
+        ))
+        .unwrap_err();
+        assert_eq!((error.line(), error.column()), (4, 3));
+        assert_eq!(jsonc_position(JOB, "tasks[0].command"), Some((6, 18)));
+        assert_eq!(jsonc_position(JOB, "name"), Some((4, 11)));
+        assert_eq!(jsonc_position(JOB, "tasks[1]"), None);
+    }
+
+    #[test]
+    fn test_jsonc_set_field_rejects_non_objects() {
+        assert!(jsonc_set_field("[1, 2]", "enabled", "true").is_err());
+    }
+}
@@ -0,0 +1,33 @@
+//! Development tasks, run with `cargo xtask <task>`
+
+use std::path::Path;
+
+use autopilot_rs::api::openapi::job_file_schema;
+
+fn main() {
+    match std::env::args().nth(1).as_deref() {
+        Some("schema") => schema(),
+        _ => {
+            eprintln!(
+                "Usage: cargo xtask <task>\n\nTasks:\n  schema  Regenerate json_schema/schema.json from the job types"
+            );
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Writes the job file JSON Schema next to the crate, for editors validating job files
+fn schema() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask lives inside the workspace")
+        .join("json_schema/schema.json");
+    let schema = serde_json::to_string_pretty(&job_file_schema())
+        .expect("Failed to serialize the job file schema")
+        + "\n";
+    if let Err(e) = std::fs::write(&path, schema) {
+        eprintln!("Failed to write {}: {}", path.display(), e);
+        std::process::exit(1);
+    }
+    println!("Wrote {}", path.display());
+}